@@ -1,6 +1,8 @@
 #ifndef SLIME_GC_H
 #define SLIME_GC_H
 
+#include <stddef.h>
+
 #ifdef __cplusplus
 extern "C" {
 #endif
@@ -8,6 +10,173 @@ extern "C" {
 // 前向声明垃圾回收器类型
 typedef struct GarbageCollector GarbageCollector;
 
+// 前向声明线程安全的垃圾回收器类型
+typedef struct ConcurrentGarbageCollector ConcurrentGarbageCollector;
+
+// 线程注册缓冲区，由一个变更线程使用
+typedef struct ThreadBuffer SlimeGcThreadBuffer;
+
+// 状态码
+#define SLIME_GC_OK           0  // 操作成功
+#define SLIME_GC_OBJECT_LIMIT 1  // 注册对象数量已达上限
+#define SLIME_GC_NO_SCOPE     2  // 没有打开的根作用域
+#define SLIME_GC_IO_ERROR     3  // 写入文件失败
+#define SLIME_GC_NOT_REGISTERED 4  // 对象未注册
+#define SLIME_GC_INCONSISTENT 5  // 内部状态不一致
+#define SLIME_GC_VERSION_MISMATCH 6  // 对象图导出的格式版本不同
+#define SLIME_GC_INVALID_FORMAT 7  // 对象图导出的内容不符合格式
+
+// 重定向标志
+#define SLIME_GC_REDIRECT_UNREGISTER   1  // 完成后注销原对象
+#define SLIME_GC_REDIRECT_MIGRATE_ROOT 2  // 将原对象的根标记转移到目标对象
+#define SLIME_GC_REDIRECT_MIGRATE_PIN  4  // 将原对象的固定次数转移到目标对象
+#define SLIME_GC_REDIRECT_WEAK         8  // 指向原对象的弱引用也改为指向目标对象，默认弱引用不随重定向改写
+
+// 对象图JSON导出的格式版本，格式不兼容地变化时递增
+#define SLIME_GC_GRAPH_FORMAT_VERSION 2
+
+// 存活原因：引用路径的起点类型
+#define SLIME_GC_ANCHOR_ROOT          1  // 从根对象可达
+#define SLIME_GC_ANCHOR_SCOPED_ROOT   2  // 从作用域根可达
+#define SLIME_GC_ANCHOR_PIN           3  // 从固定的对象可达
+#define SLIME_GC_ANCHOR_THREAD_BUFFER 4  // 从线程缓冲区持有的对象可达
+
+// 存活状态
+#define SLIME_GC_LIVENESS_DEAD      0  // 回收器不认识该对象（未注册，或已被清除、注销）
+#define SLIME_GC_LIVENESS_LIVE      1  // 已注册且没有被证明不可达
+#define SLIME_GC_LIVENESS_CONDEMNED 2  // 已被证明不可达但仍保持注册，等待清除或取出
+
+// 使用最近注销对象时的回调：(from, to, 距今注销次数, 用户数据)
+typedef void (*SlimeGcRecentlyUnregisteredCallback)(void* from, void* to, unsigned long long age, void* user_data);
+
+// 地址失效原因
+#define SLIME_GC_INVALIDATE_SWEPT        1  // 对象被垃圾回收清除
+#define SLIME_GC_INVALIDATE_UNREGISTERED 2  // 对象被显式注销
+#define SLIME_GC_INVALIDATE_REDIRECTED   3  // 对象被重定向后注销
+
+// 弱引用清除原因
+#define SLIME_GC_WEAK_CLEARED_COLLECTED 1  // 目标被垃圾回收清除
+#define SLIME_GC_WEAK_CLEARED_REMOVED   2  // 目标被显式注销或重定向后注销
+
+// 延迟工作类别，用作slime_gc_pump的priorities位掩码，数值越小优先级越高
+#define SLIME_GC_WORK_MARK      1   // 增量回收的标记，包括回收到期但尚未开始的新一轮
+#define SLIME_GC_WORK_SWEEP     2   // 增量回收的清除
+#define SLIME_GC_WORK_GARBAGE   4   // 已回收但未被slime_gc_collect_into取走的对象
+#define SLIME_GC_WORK_CALLBACKS 8   // 尚未派发的回调
+
+// 操作耗时类别，用于slime_gc_ts_op_latency
+#define SLIME_GC_OP_REGISTER         0  // 注册和注销对象
+#define SLIME_GC_OP_ADD_REFERENCE    1  // 添加强引用，批量添加算一次
+#define SLIME_GC_OP_REMOVE_REFERENCE 2  // 移除强引用，批量移除和清除对象的所有引用算一次
+#define SLIME_GC_OP_ROOT             3  // 根对象、固定和作用域根的增减
+#define SLIME_GC_OP_WEAK             4  // 添加和移除弱引用
+
+// 操作耗时直方图的桶数
+#define SLIME_GC_LATENCY_BUCKETS 32
+#define SLIME_GC_WORK_ALL       15  // 所有类别
+
+// 释放回调：(被回收的对象, 用户数据)
+typedef void (*SlimeGcFreeCallback)(void* obj, void* user_data);
+
+// 弱引用清除回调：(引用方, 被清除的目标, 用户数据)
+typedef void (*SlimeGcWeakClearCallback)(void* from, void* to, void* user_data);
+
+// 带原因的弱引用清除回调：(引用方, 被清除的目标, 清除原因, 用户数据)
+typedef void (*SlimeGcWeakClearReasonCallback)(void* from, void* to, int reason, void* user_data);
+
+// 地址失效通知回调：(地址数组, 数量, 失效原因, 用户数据)
+typedef void (*SlimeGcInvalidationCallback)(void* const* addrs, size_t count, int reason, void* user_data);
+
+// 流式导出的写入回调：(数据, 字节数, 用户数据)，返回非0时中止导出
+typedef int (*SlimeGcWriteCallback)(const unsigned char* data, size_t len, void* ctx);
+
+// 回收复用回调：(被清除的对象数组, 数量, 用户数据)，返回从数组开头起接受复用的对象数量
+typedef size_t (*SlimeGcRecyclerCallback)(void* const* objs, size_t count, void* ctx);
+
+// 批量查询中单个对象的结果
+typedef struct SlimeGcObjectQuery {
+    int registered;         // 对象是否已注册
+    int root;               // 对象是否为根对象，只看mark_root设置的根，固定和作用域根分别由pinned和scoped_root报告
+    int reachable;          // 对象是否可从根对象到达
+    int out_degree;         // 对象引用的对象数量
+    int pinned;             // 对象是否被固定
+    int scoped_root;        // 对象是否为打开的作用域中的作用域根
+    int in_degree;          // 引用该对象的已注册对象数量
+    size_t size;            // 对象大小（字节），不含外部内存
+    unsigned int type_tag;  // 对象的类型标签，未设置时为0
+    int liveness;           // 对象的存活状态，为SLIME_GC_LIVENESS_*之一，与slime_gc_liveness相同
+} SlimeGcObjectQuery;
+
+// 一次回收的详细结果
+typedef struct SlimeGcCollectResult {
+    size_t freed;                     // 清除的对象数量
+    size_t queued_for_disposal;       // 放入处置队列、等待宿主取出的对象数量
+    size_t deferred_by_backpressure;  // 所属类型的处置队列已满、推迟到以后回收的不可达对象数量
+} SlimeGcCollectResult;
+
+// 一类操作的耗时直方图，耗时以纳秒为单位
+// buckets[0]为耗时0纳秒的次数，buckets[i]（i≥1）为耗时在[2^(i-1), 2^i)纳秒内的次数，最后一个桶还包括所有更长的耗时
+typedef struct SlimeGcHistogram {
+    unsigned long long count;        // 记录的操作次数
+    unsigned long long total_nanos;  // 所有操作的总耗时
+    unsigned long long max_nanos;    // 最长的单次耗时
+    unsigned long long buckets[SLIME_GC_LATENCY_BUCKETS];  // 各桶的操作次数
+} SlimeGcHistogram;
+
+// 按类型查找引用方时的单条结果
+typedef struct SlimeGcReferrerRecord {
+    void* from;   // 引用方
+    void* to;     // 被引用的指定类型对象
+} SlimeGcReferrerRecord;
+
+// 创建或替换回收器时使用的配置，默认值为{0, 0, 1048576, 0}
+typedef struct SlimeGcConfig {
+    size_t max_objects;               // 注册对象数量上限，0表示不限制
+    size_t default_object_size;       // 未指定大小的对象按多少字节计入
+    size_t collection_threshold;      // 自动回收阈值（字节）
+    size_t recent_unregister_window;  // 最近注销对象的检测窗口，0表示关闭检测
+} SlimeGcConfig;
+
+// 一致性检查或修复的结果，各字段为对应类别发现（或修复）的问题数量
+typedef struct SlimeGcRepairReport {
+    size_t missing_reverse_edges;  // 正向引用存在但反向索引中缺失的条目，强弱引用合计
+    size_t stale_reverse_edges;    // 反向索引中存在但没有对应正向引用的条目，强弱引用合计
+    size_t dangling_edges;         // 引用方未注册的强引用
+    size_t dangling_weak_edges;    // 引用方未注册的弱引用
+    size_t dangling_roots;         // 指向未注册地址的根标记
+    size_t dangling_pins;          // 指向未注册地址的固定和线程缓冲区持有
+    size_t dangling_scope_roots;   // 指向未注册地址的作用域根
+    size_t stale_records;          // 未注册地址的大小、外部内存和类型标签记录
+    size_t counters_fixed;         // 与实际状态不符的计数：存活字节数、作用域根计数和为0的固定次数
+    int repaired;                  // 发现的问题是否已经修复，1表示已修复
+} SlimeGcRepairReport;
+
+// 回收器统计信息，耗时以纳秒为单位
+typedef struct SlimeGcStats {
+    size_t total_objects;          // 当前注册对象数量
+    size_t root_count;             // 当前根对象数量（不含作用域根）
+    size_t total_edges;            // 已注册对象之间的强引用数量
+    size_t collected_last_cycle;   // 上次回收清除的对象数量
+    unsigned long long last_mark_nanos;   // 上次回收标记阶段的耗时
+    unsigned long long last_sweep_nanos;  // 上次回收清除阶段的耗时
+    size_t max_objects;            // 注册对象数量上限，0表示不限制
+    size_t reserved_objects;       // 已预留但尚未使用的对象数量
+    size_t used_object_slots;      // 计入数量上限的用量：注册对象数量加上预留数量
+} SlimeGcStats;
+
+// 一次pump的结果，回调以批计，其他类别以对象计
+typedef struct SlimeGcPumpResult {
+    size_t mark_done;            // 本次扫描过引用的对象数量
+    size_t mark_remaining;       // 已发现但尚未扫描的对象数量，继续扫描还可能发现更多
+    size_t sweep_done;           // 本次检查过是否需要清除的对象数量
+    size_t sweep_remaining;      // 尚未检查的对象数量，标记完成前为0
+    size_t garbage_done;         // 本次交给释放回调的未取走对象数量
+    size_t garbage_remaining;    // 仍未取走的对象数量
+    size_t callbacks_done;       // 本次派发的回调批数
+    size_t callbacks_remaining;  // 尚未派发的回调批数
+    size_t freed;                // 本次增量回收清除的对象数量
+} SlimeGcPumpResult;
+
 // 创建新的垃圾回收器
 GarbageCollector* slime_gc_new();
 
@@ -17,6 +186,40 @@ void slime_gc_destroy(GarbageCollector* gc);
 // 注册对象
 void slime_gc_register_object(GarbageCollector* gc, void* obj);
 
+// 设置注册对象数量上限，0表示不限制
+void slime_gc_set_max_objects(GarbageCollector* gc, size_t max);
+
+// 获取当前注册对象数量
+size_t slime_gc_get_object_count(const GarbageCollector* gc);
+
+// 为接下来的n次注册预留空位，保证这些注册不会因数量上限失败
+int slime_gc_reserve_objects(GarbageCollector* gc, size_t n);
+
+// 释放最多n个尚未使用的预留空位
+void slime_gc_release_reservation(GarbageCollector* gc, size_t n);
+
+// 获取已预留但尚未使用的对象数量
+size_t slime_gc_get_reserved_objects(const GarbageCollector* gc);
+
+// 注册对象，达到数量上限且紧急回收后仍无空位时返回SLIME_GC_OBJECT_LIMIT
+int slime_gc_register_object_checked(GarbageCollector* gc, void* obj);
+
+// 注册指定大小（字节）的对象，重复注册时以新的大小为准，标签重置为0
+int slime_gc_register_object_sized(GarbageCollector* gc, void* obj, size_t size_bytes);
+
+// 设置未指定大小的对象按多少字节计入（默认0）
+void slime_gc_set_default_object_size(GarbageCollector* gc, size_t size_bytes);
+
+// 获取所有已注册对象的总字节数，包括归属于它们的外部内存
+size_t slime_gc_get_live_bytes(const GarbageCollector* gc);
+
+// 设置自动回收阈值（字节，默认1MB）
+// 每次回收后阈值调整为max(该值, 存活字节数的2倍)
+void slime_gc_set_collection_threshold(GarbageCollector* gc, size_t bytes);
+
+// 存活字节数超过阈值时执行回收，返回回收的对象数量，否则返回0
+int slime_gc_maybe_collect(GarbageCollector* gc);
+
 // 添加根对象
 void slime_gc_mark_root(GarbageCollector* gc, void* obj);
 
@@ -32,6 +235,17 @@ void slime_gc_remove_roots(GarbageCollector* gc, void** roots, int count);
 // 清除所有根对象标记
 void slime_gc_clear_roots(GarbageCollector* gc);
 
+// 打开一个新的根作用域
+void slime_gc_push_scope(GarbageCollector* gc);
+
+// 在最内层作用域中加入临时根对象，作用域关闭时自动移除
+// 没有打开的作用域时返回SLIME_GC_NO_SCOPE
+int slime_gc_add_scoped_root(GarbageCollector* gc, void* obj);
+
+// 关闭最内层作用域，移除其中加入的所有临时根对象
+// 没有打开的作用域时返回SLIME_GC_NO_SCOPE
+int slime_gc_pop_scope(GarbageCollector* gc);
+
 // 获取当前根对象数量
 int slime_gc_get_root_count(const GarbageCollector* gc);
 
@@ -41,6 +255,18 @@ void slime_gc_add_reference(GarbageCollector* gc, void* from, void* to);
 // 移除引用关系
 void slime_gc_remove_reference(GarbageCollector* gc, void* from, void* to);
 
+// 添加弱引用，弱引用不会让目标对象存活
+void slime_gc_add_weak_reference(GarbageCollector* gc, void* from, void* to);
+
+// 移除弱引用
+void slime_gc_remove_weak_reference(GarbageCollector* gc, void* from, void* to);
+
+// 设置弱引用被清除时的回调，目标被回收或注销时调用；替换之前设置的回调，包括带原因的回调
+void slime_gc_set_weak_clear_callback(GarbageCollector* gc, SlimeGcWeakClearCallback callback, void* user_data);
+
+// 检查对象是否仍被回收器追踪（未被回收或注销）
+int slime_gc_is_alive(const GarbageCollector* gc, void* obj);
+
 // 移除对象的所有引用
 void slime_gc_clear_references(GarbageCollector* gc, void* obj);
 
@@ -53,12 +279,561 @@ void slime_gc_add_references(GarbageCollector* gc, void* from, void** to_list, i
 // 批量移除引用
 void slime_gc_remove_references(GarbageCollector* gc, void* from, void** to_list, int count);
 
+// 以“被拥有”的方式添加引用：记录为owner引用child
+void slime_gc_add_owner(GarbageCollector* gc, void* child, void* owner);
+
+// 以“被拥有”的方式移除引用
+void slime_gc_remove_owner(GarbageCollector* gc, void* child, void* owner);
+
+// 批量添加拥有者
+void slime_gc_add_owners(GarbageCollector* gc, void* child, void** owner_list, int count);
+
+// 批量移除拥有者
+void slime_gc_remove_owners(GarbageCollector* gc, void* child, void** owner_list, int count);
+
 // 注销对象
 void slime_gc_unregister_object(GarbageCollector* gc, void* obj);
 
 // 执行垃圾回收
+// 设置了释放回调时，会在所有记录清理完毕后对每个被回收的对象调用一次
+// 所有回调都在函数内部状态更新完毕后才调用，回调中可以重入回收器（如注销其他对象）
 int slime_gc_collect(GarbageCollector* gc);
 
+// 执行垃圾回收并把被回收的对象写入out_buf，返回写入数量
+// 上次回收的对象未取完时只继续取出；返回值小于capacity说明已全部取完
+int slime_gc_collect_into(GarbageCollector* gc, void** out_buf, int capacity);
+
+// 设置释放回调，传入NULL表示只移除追踪记录
+void slime_gc_set_free_callback(GarbageCollector* gc, SlimeGcFreeCallback callback, void* user_data);
+
+// 将所有指向from_obj的强引用改为指向to_obj，返回改写的引用数量
+// 弱引用默认不随重定向改写，带上SLIME_GC_REDIRECT_WEAK时也改为指向to_obj；否则随SLIME_GC_REDIRECT_UNREGISTER被清除并通知
+// 返回值只计实际移到to_obj上的引用：引用方已经引用to_obj时两条引用合并为一条，不计入
+// 原对象的根标记和固定次数只在带上对应的MIGRATE标志时转移，否则随SLIME_GC_REDIRECT_UNREGISTER一并丢弃
+size_t slime_gc_redirect(GarbageCollector* gc, void* from_obj, void* to_obj, int flags);
+
+// 设置最近注销检测的窗口大小（记录最近多少次注销），0表示关闭；注销未注册的地址不计入
+void slime_gc_set_recent_unregister_window(GarbageCollector* gc, size_t window);
+
+// 设置使用最近注销对象时的回调
+void slime_gc_set_recently_unregistered_callback(GarbageCollector* gc, SlimeGcRecentlyUnregisteredCallback callback, void* user_data);
+
+// 获取使用最近注销对象的次数
+int slime_gc_get_recently_unregistered_count(const GarbageCollector* gc);
+
+// 订阅地址失效通知，返回订阅ID（失败返回0）
+unsigned long long slime_gc_subscribe_invalidation(GarbageCollector* gc, SlimeGcInvalidationCallback callback, void* user_data);
+
+// 取消地址失效通知订阅
+void slime_gc_unsubscribe_invalidation(GarbageCollector* gc, unsigned long long id);
+
+// 批量查询对象状态，所有对象共用一次标记遍历
+void slime_gc_query_many(const GarbageCollector* gc, void* const* objs, size_t count, SlimeGcObjectQuery* out);
+
+// 拆除回收器并将残留状态报告写入缓冲区，调用后回收器即被销毁
+// 报告以'\0'结尾，超出容量时截断，返回完整报告的字节数。残留对象按类型标签分组，存活起点按根对象、作用域根、固定、
+// 线程缓冲区分组；尚未派发的回调和处置队列中未取出的对象单独报告。
+// 线程安全的版本不刷新线程缓冲区，尚未应用的操作单独报告后丢弃
+int slime_gc_teardown_report(GarbageCollector* gc, char* out_buf, int capacity);
+
+// 创建线程安全的垃圾回收器，以下slime_gc_ts_*函数可以从多个线程同时调用
+// 所有回调都在内部锁释放后调用，可以在回调中重入回收器
+ConcurrentGarbageCollector* slime_gc_new_threadsafe(void);
+
+// 销毁线程安全的垃圾回收器，调用时不能有其他线程仍在使用它
+void slime_gc_ts_destroy(ConcurrentGarbageCollector* gc);
+
+// 以下函数与不带ts_的同名函数行为一致
+// 根作用域栈由所有线程共享，多个线程同时使用时须由宿主保证按栈顺序开闭；
+// slime_gc_ts_teardown_report调用后回收器即被销毁，调用时不能有其他线程仍在使用它
+int slime_gc_ts_register_object(const ConcurrentGarbageCollector* gc, void* obj);
+int slime_gc_ts_register_object_sized(const ConcurrentGarbageCollector* gc, void* obj, size_t size_bytes);
+void slime_gc_ts_unregister_object(const ConcurrentGarbageCollector* gc, void* obj);
+void slime_gc_ts_add_reference(const ConcurrentGarbageCollector* gc, void* from, void* to);
+void slime_gc_ts_remove_reference(const ConcurrentGarbageCollector* gc, void* from, void* to);
+void slime_gc_ts_add_references(const ConcurrentGarbageCollector* gc, void* from, void** to_list, int count);
+void slime_gc_ts_remove_references(const ConcurrentGarbageCollector* gc, void* from, void** to_list, int count);
+void slime_gc_ts_clear_references(const ConcurrentGarbageCollector* gc, void* obj);
+int slime_gc_ts_get_reference_count(const ConcurrentGarbageCollector* gc, void* obj);
+void slime_gc_ts_add_weak_reference(const ConcurrentGarbageCollector* gc, void* from, void* to);
+void slime_gc_ts_remove_weak_reference(const ConcurrentGarbageCollector* gc, void* from, void* to);
+void slime_gc_ts_mark_root(const ConcurrentGarbageCollector* gc, void* obj);
+void slime_gc_ts_unmark_root(const ConcurrentGarbageCollector* gc, void* obj);
+void slime_gc_ts_clear_roots(const ConcurrentGarbageCollector* gc);
+void slime_gc_ts_set_free_callback(const ConcurrentGarbageCollector* gc, SlimeGcFreeCallback callback, void* user_data);
+int slime_gc_ts_collect(const ConcurrentGarbageCollector* gc);
+int slime_gc_ts_maybe_collect(const ConcurrentGarbageCollector* gc);
+size_t slime_gc_ts_get_object_count(const ConcurrentGarbageCollector* gc);
+size_t slime_gc_ts_get_live_bytes(const ConcurrentGarbageCollector* gc);
+int slime_gc_ts_is_alive(const ConcurrentGarbageCollector* gc, void* obj);
+void slime_gc_ts_set_default_object_size(const ConcurrentGarbageCollector* gc, size_t size_bytes);
+void slime_gc_ts_set_collection_threshold(const ConcurrentGarbageCollector* gc, size_t bytes);
+void slime_gc_ts_set_weak_clear_callback(const ConcurrentGarbageCollector* gc, SlimeGcWeakClearCallback callback, void* user_data);
+void slime_gc_ts_push_scope(const ConcurrentGarbageCollector* gc);
+int slime_gc_ts_add_scoped_root(const ConcurrentGarbageCollector* gc, void* obj);
+int slime_gc_ts_pop_scope(const ConcurrentGarbageCollector* gc);
+int slime_gc_ts_collect_into(const ConcurrentGarbageCollector* gc, void** out_buf, int capacity);
+size_t slime_gc_ts_redirect(const ConcurrentGarbageCollector* gc, void* from_obj, void* to_obj, int flags);
+void slime_gc_ts_set_recent_unregister_window(const ConcurrentGarbageCollector* gc, size_t window);
+void slime_gc_ts_set_recently_unregistered_callback(const ConcurrentGarbageCollector* gc, SlimeGcRecentlyUnregisteredCallback callback, void* user_data);
+int slime_gc_ts_get_recently_unregistered_count(const ConcurrentGarbageCollector* gc);
+unsigned long long slime_gc_ts_subscribe_invalidation(const ConcurrentGarbageCollector* gc, SlimeGcInvalidationCallback callback, void* user_data);
+void slime_gc_ts_unsubscribe_invalidation(const ConcurrentGarbageCollector* gc, unsigned long long id);
+void slime_gc_ts_add_owner(const ConcurrentGarbageCollector* gc, void* child, void* owner);
+void slime_gc_ts_remove_owner(const ConcurrentGarbageCollector* gc, void* child, void* owner);
+void slime_gc_ts_add_owners(const ConcurrentGarbageCollector* gc, void* child, void** owner_list, int count);
+void slime_gc_ts_remove_owners(const ConcurrentGarbageCollector* gc, void* child, void** owner_list, int count);
+void slime_gc_ts_query_many(const ConcurrentGarbageCollector* gc, void* const* objs, size_t count, SlimeGcObjectQuery* out);
+int slime_gc_ts_teardown_report(ConcurrentGarbageCollector* gc, char* out_buf, int capacity);
+void slime_gc_ts_set_max_objects(const ConcurrentGarbageCollector* gc, size_t max);
+int slime_gc_ts_reserve_objects(const ConcurrentGarbageCollector* gc, size_t n);
+void slime_gc_ts_release_reservation(const ConcurrentGarbageCollector* gc, size_t n);
+size_t slime_gc_ts_get_reserved_objects(const ConcurrentGarbageCollector* gc);
+
+// 获取回收器统计信息
+void slime_gc_get_stats(const GarbageCollector* gc, SlimeGcStats* out);
+
+// 把对象图以DOT格式写入文件，根对象画成方框；成功返回SLIME_GC_OK，失败返回SLIME_GC_IO_ERROR
+int slime_gc_dump_dot(const GarbageCollector* gc, const char* path);
+
+// 把对象图以JSON格式分块流式写入文件，返回值同slime_gc_dump_dot
+// 格式版本为SLIME_GC_GRAPH_FORMAT_VERSION：{"format_version","nodes":[{"id","flags":{...},"pin_count","size","external_bytes","tag","liveness"}],
+// "edges":[{"from","to","kind"}]}。flags包含root（只表示根对象）、pinned、scoped_root、buffer_held、weakly_referenced
+// 和finalizer_registered（设置了释放回调）；节点的liveness为live或condemned；kind为strong、owner（以slime_gc_add_owner添加的强引用）或weak
+int slime_gc_dump_json(const GarbageCollector* gc, const char* path);
+
+// 把一条从根对象到obj的最短引用路径写入out_buf（根对象在前，obj在后）
+// 路径也可能从作用域根、固定的对象或线程缓冲区持有的对象开始，用slime_gc_retaining_anchor区分
+// 超出容量时截断，返回完整路径的长度；obj不可达时返回0
+int slime_gc_retaining_path(const GarbageCollector* gc, void* obj, void** out_buf, int capacity);
+
+// 以上函数在线程安全的回收器上的版本
+void slime_gc_ts_get_stats(const ConcurrentGarbageCollector* gc, SlimeGcStats* out);
+int slime_gc_ts_dump_dot(const ConcurrentGarbageCollector* gc, const char* path);
+int slime_gc_ts_dump_json(const ConcurrentGarbageCollector* gc, const char* path);
+int slime_gc_ts_retaining_path(const ConcurrentGarbageCollector* gc, void* obj, void** out_buf, int capacity);
+
+// 注册带类型标签的对象，未指定标签的对象标签为0；重复注册时以新的标签为准，大小重置为默认大小
+int slime_gc_register_object_tagged(GarbageCollector* gc, void* obj, unsigned int tag);
+
+// 注册指定大小和类型标签的对象，重复注册时大小和标签都以新的值为准
+int slime_gc_register_object_sized_tagged(GarbageCollector* gc, void* obj, size_t size_bytes, unsigned int tag);
+
+// 获取对象的类型标签，对象已注册时写入out_tag并返回1，否则返回0
+int slime_gc_get_type_tag(const GarbageCollector* gc, void* obj, unsigned int* out_tag);
+
+// 设置回收时报告被清除对象的顺序：按标签在tags中的顺序分组，其他标签排在最后
+// 释放回调、collect_into和失效通知都遵循此顺序；count为0表示取消排序
+void slime_gc_set_finalization_order(GarbageCollector* gc, const unsigned int* tags, int count);
+
+// 统计带有指定类型标签的对象数量
+size_t slime_gc_count_by_tag(const GarbageCollector* gc, unsigned int tag);
+
+// 以上函数在线程安全的回收器上的版本
+int slime_gc_ts_register_object_tagged(const ConcurrentGarbageCollector* gc, void* obj, unsigned int tag);
+int slime_gc_ts_register_object_sized_tagged(const ConcurrentGarbageCollector* gc, void* obj, size_t size_bytes, unsigned int tag);
+int slime_gc_ts_get_type_tag(const ConcurrentGarbageCollector* gc, void* obj, unsigned int* out_tag);
+void slime_gc_ts_set_finalization_order(const ConcurrentGarbageCollector* gc, const unsigned int* tags, int count);
+size_t slime_gc_ts_count_by_tag(const ConcurrentGarbageCollector* gc, unsigned int tag);
+
+// 执行一步增量回收，每步最多处理budget个对象，用于限制单次停顿时间
+// 本轮未完成时返回-1，完成时返回本轮清除的对象数量；两步之间可以继续修改对象图，
+// 期间新注册的对象、新根对象和新引用的目标本轮视为存活。调用slime_gc_collect会放弃进行中的增量回收
+// 清除阶段每步只检查本轮开始时已注册对象中的budget个，终结顺序只在同一步清除的对象之间保证
+// 清除阶段中重新变为存活的对象不会立即标记整个子图，之后每步先扫描它们，剩余的预算再用于清除
+int slime_gc_collect_step(GarbageCollector* gc, int budget);
+int slime_gc_ts_collect_step(const ConcurrentGarbageCollector* gc, int budget);
+
+// 固定对象，每次固定使固定次数加1；固定的对象在标记时视为根对象，不受slime_gc_clear_roots影响
+// 对象未注册时返回SLIME_GC_NOT_REGISTERED；注销对象会清除它的固定状态
+int slime_gc_pin(GarbageCollector* gc, void* obj);
+
+// 取消一次固定，固定次数最少减到0
+void slime_gc_unpin(GarbageCollector* gc, void* obj);
+
+// 获取对象的固定次数
+int slime_gc_pin_count(const GarbageCollector* gc, void* obj);
+
+// 以上函数在线程安全的回收器上的版本
+int slime_gc_ts_pin(const ConcurrentGarbageCollector* gc, void* obj);
+void slime_gc_ts_unpin(const ConcurrentGarbageCollector* gc, void* obj);
+int slime_gc_ts_pin_count(const ConcurrentGarbageCollector* gc, void* obj);
+
+// 查找所有直接引用指定类型对象的已注册对象，结果按地址排序
+// 超出容量时只写入前capacity条，返回结果总数
+size_t slime_gc_find_referrers_of_type(const GarbageCollector* gc, unsigned int tag, SlimeGcReferrerRecord* out, size_t capacity);
+size_t slime_gc_ts_find_referrers_of_type(const ConcurrentGarbageCollector* gc, unsigned int tag, SlimeGcReferrerRecord* out, size_t capacity);
+
+// 创建线程注册缓冲区，用完后须调用slime_gc_thread_buffer_end关闭；销毁回收器前须关闭所有缓冲区
+SlimeGcThreadBuffer* slime_gc_thread_buffer_begin(const ConcurrentGarbageCollector* gc);
+
+// 在缓冲区中记录注册和添加引用，不获取任何锁
+void slime_gc_buffered_register(SlimeGcThreadBuffer* buf, void* obj, size_t size_bytes, unsigned int type_id);
+void slime_gc_buffered_add_reference(SlimeGcThreadBuffer* buf, void* from, void* to);
+
+// 在一次加锁中应用缓冲区中的操作，其他线程缓冲区中的操作也会一并应用；gc须为创建该缓冲区的回收器
+// 每次回收前都会先应用所有缓冲区，从缓冲区注册的对象在所属线程刷新该缓冲区之前一直视为存活，
+// 这种持有不计入固定次数；注销对象会解除它的持有
+// 自上次刷新以来该缓冲区有注册因数量上限失败时返回SLIME_GC_OBJECT_LIMIT
+int slime_gc_thread_buffer_flush(const ConcurrentGarbageCollector* gc, SlimeGcThreadBuffer* buf);
+
+// 刷新并关闭缓冲区，释放它持有的所有对象，之后不能再使用该缓冲区
+int slime_gc_thread_buffer_end(const ConcurrentGarbageCollector* gc, SlimeGcThreadBuffer* buf);
+
+// 获取回收器获取写锁的次数，用于衡量锁的使用频率
+unsigned long long slime_gc_ts_get_write_lock_count(const ConcurrentGarbageCollector* gc);
+
+// 获取当前有待推进的延迟工作类别（SLIME_GC_WORK_*的组合），没有时返回0
+unsigned int slime_gc_pending_work(const GarbageCollector* gc);
+
+// 在max_micros微秒的预算内推进priorities选中的延迟工作，宿主每帧调用一次即可
+// 按标记、清除、未取走对象、回调的顺序推进：回收到期时开始新一轮增量回收，未取走的对象交给释放回调；
+// 每推进一块检查一次预算，只要有选中的工作就至少推进一块。未选中回调类别时，回调留到下一次调用其他函数时派发
+SlimeGcPumpResult slime_gc_pump(GarbageCollector* gc, unsigned long long max_micros, unsigned int priorities);
+
+// 以上函数在线程安全的回收器上的版本
+unsigned int slime_gc_ts_pending_work(const ConcurrentGarbageCollector* gc);
+SlimeGcPumpResult slime_gc_ts_pump(const ConcurrentGarbageCollector* gc, unsigned long long max_micros, unsigned int priorities);
+
+// 按新配置重建回收器内部状态，句柄保持不变；new_cfg为空时使用默认配置
+// 对象及其大小、外部内存和类型标签、强弱引用、根对象、固定、作用域根、预留空位、处置队列和尚未派发的回调原样迁移，不会清除或释放任何对象
+// 回调（包括回收复用回调）、地址失效订阅和自动修复需要重新设置；统计计数和最近注销记录清零，标签的回收报告顺序恢复默认，进行中的增量回收被放弃
+// 新的数量上限容纳不下现有对象和预留空位时返回SLIME_GC_OBJECT_LIMIT，回收器保持不变
+int slime_gc_swap_in(GarbageCollector* old, const SlimeGcConfig* new_cfg);
+int slime_gc_ts_swap_in(const ConcurrentGarbageCollector* gc, const SlimeGcConfig* new_cfg);
+
+// 把bytes字节外部内存（如对象持有的malloc缓冲区）归属到obj，对象未注册时返回SLIME_GC_NOT_REGISTERED
+// 外部内存计入存活字节数，参与自动回收阈值的判断；对象被清除或注销时自动扣除
+int slime_gc_add_external_bytes(GarbageCollector* gc, void* obj, size_t bytes);
+
+// 从obj归属的外部内存中扣除bytes字节，最多扣到0
+void slime_gc_remove_external_bytes(GarbageCollector* gc, void* obj, size_t bytes);
+
+// 获取归属于obj的外部内存字节数，未注册的对象为0
+size_t slime_gc_get_external_bytes(const GarbageCollector* gc, void* obj);
+
+// 以上函数在线程安全的回收器上的版本
+int slime_gc_ts_add_external_bytes(const ConcurrentGarbageCollector* gc, void* obj, size_t bytes);
+void slime_gc_ts_remove_external_bytes(const ConcurrentGarbageCollector* gc, void* obj, size_t bytes);
+size_t slime_gc_ts_get_external_bytes(const ConcurrentGarbageCollector* gc, void* obj);
+
+// 设置带原因的弱引用清除回调，原因为SLIME_GC_WEAK_CLEARED_*；替换之前设置的回调，包括不带原因的回调
+void slime_gc_set_weak_clear_reason_callback(GarbageCollector* gc, SlimeGcWeakClearReasonCallback callback, void* user_data);
+void slime_gc_ts_set_weak_clear_reason_callback(const ConcurrentGarbageCollector* gc, SlimeGcWeakClearReasonCallback callback, void* user_data);
+
+// 检查正向引用、反向索引、根、固定、作用域根、记录和计数之间是否一致，out_report可以为NULL
+// 一致时返回SLIME_GC_OK，否则返回SLIME_GC_INCONSISTENT；开启自动修复时发现问题立即修复，报告的repaired为1
+int slime_gc_verify(GarbageCollector* gc, SlimeGcRepairReport* out_report);
+
+// 设置一致性检查发现问题时是否自动修复，默认关闭
+void slime_gc_set_auto_repair(GarbageCollector* gc, int enabled);
+
+// 修复检查发现的所有问题，把修复前发现的问题写入out_report（可以为NULL）
+// 按正向引用重建反向索引，删除引用方未注册的引用，删除指向未注册地址的根、固定、线程缓冲区持有、作用域根和记录，
+// 重新计算存活字节数和作用域根计数；指向尚未注册对象的引用是允许的，不会被删除。
+// 不清除或释放任何已注册对象，不派发回调，可以在任何两次调用之间执行
+int slime_gc_repair(GarbageCollector* gc, SlimeGcRepairReport* out_report);
+
+// 以上函数在线程安全的回收器上的版本
+int slime_gc_ts_verify(const ConcurrentGarbageCollector* gc, SlimeGcRepairReport* out_report);
+void slime_gc_ts_set_auto_repair(const ConcurrentGarbageCollector* gc, int enabled);
+int slime_gc_ts_repair(const ConcurrentGarbageCollector* gc, SlimeGcRepairReport* out_report);
+
+// 获取obj存活的起点类型，返回SLIME_GC_ANCHOR_*；obj未注册或不可达时返回0
+// 被固定的对象即使同时可以从根对象到达，也报告为SLIME_GC_ANCHOR_PIN
+int slime_gc_retaining_anchor(const GarbageCollector* gc, void* obj);
+
+// 获取obj的存活状态，返回SLIME_GC_LIVENESS_*，不做标记遍历
+// 放入处置队列的对象直到被取出前都是CONDEMNED，重新变为可达也不会移出队列；增量回收进入清除阶段后，
+// 本轮未被标记的对象在被清除前都是CONDEMNED，清除阶段中新添加引用或加入根的对象恢复为LIVE。
+// 清除阶段中有重新标记的对象尚未扫描完时，以及因处置队列已满而推迟的对象，都报告为LIVE
+int slime_gc_liveness(const GarbageCollector* gc, void* obj);
+
+// 把对象的说明文本（大小、所有标志、存活原因）以'\0'结尾写入out_buf，超出容量时截断，返回完整文本的字节数
+int slime_gc_explain(const GarbageCollector* gc, void* obj, char* out_buf, int capacity);
+
+// 以上函数在线程安全的回收器上的版本
+int slime_gc_ts_retaining_anchor(const ConcurrentGarbageCollector* gc, void* obj);
+int slime_gc_ts_liveness(const ConcurrentGarbageCollector* gc, void* obj);
+int slime_gc_ts_explain(const ConcurrentGarbageCollector* gc, void* obj, char* out_buf, int capacity);
+
+// 检查以'\0'结尾的文本是否符合当前版本的对象图JSON导出格式，多出的字段不视为错误
+// 符合时返回SLIME_GC_OK，版本不同或缺少版本时返回SLIME_GC_VERSION_MISMATCH，其他问题返回SLIME_GC_INVALID_FORMAT
+int slime_gc_validate_graph_json(const char* text);
+
+// 以JSON格式把对象图分块交给write_cb，内容与slime_gc_dump_json写入的文件相同
+// 先冻结对象和边的快照，每块最多包含1024个节点或边，额外内存只与块大小有关
+// 成功返回SLIME_GC_OK；回调返回非0时立即中止并返回SLIME_GC_IO_ERROR，已交出的内容不完整。回调期间不能调用修改回收器的函数
+int slime_gc_export_json_cb(const GarbageCollector* gc, SlimeGcWriteCallback write_cb, void* ctx);
+int slime_gc_ts_export_json_cb(const ConcurrentGarbageCollector* gc, SlimeGcWriteCallback write_cb, void* ctx);
+
+// 为类型标签设置回收复用回调，传入NULL表示移除；标签0表示未设置类型，忽略
+// 之后回收时该类型被清除的对象按回收顺序成批交给回调，回调返回从数组开头起接受复用的数量：
+// 接受的对象由宿主保留内存，不再调用释放回调；其余对象照常交给释放回调。交给回调时对象已经注销，存活字节数已扣除它的大小；
+// 地址同时记入最近注销记录，复用前误用旧地址会被报告，重新注册同一地址时记录被清除，不会产生诊断。
+// 与其他回调一样在调用结束后派发；设置了处置队列的类型先进入处置队列，slime_gc_collect_into取出的对象也不经过回调
+void slime_gc_set_recycler(GarbageCollector* gc, unsigned int type_id, SlimeGcRecyclerCallback callback, void* ctx);
+void slime_gc_ts_set_recycler(const ConcurrentGarbageCollector* gc, unsigned int type_id, SlimeGcRecyclerCallback callback, void* ctx);
+
+// 开启或关闭线程安全的回收器的操作耗时记录，默认关闭；关闭时保留已记录的直方图，未开启时每次操作只多一次分支
+// 记录注册和注销、添加和移除强引用、根对象、固定和作用域根的增减、添加和移除弱引用这些直接调用的耗时（包括等锁和派发回调），
+// 线程缓冲区中的操作不记录
+void slime_gc_ts_set_op_latency(const ConcurrentGarbageCollector* gc, int enabled);
+
+// 获取一类操作（SLIME_GC_OP_*）的耗时直方图，写入out并返回1；类别无效时返回0
+int slime_gc_ts_op_latency(const ConcurrentGarbageCollector* gc, int op_kind, SlimeGcHistogram* out);
+
+// 把Prometheus文本格式的统计信息和操作耗时直方图（slime_gc_op_latency_seconds）以'\0'结尾写入out_buf，
+// 超出容量时截断，返回完整文本的字节数
+int slime_gc_ts_metrics_text(const ConcurrentGarbageCollector* gc, char* out_buf, int capacity);
+
+// 执行垃圾回收并返回详细结果，释放回调的处理与slime_gc_collect相同
+SlimeGcCollectResult slime_gc_collect_detailed(GarbageCollector* gc);
+
+// 为类型标签设置容量为capacity的处置队列，用于必须由特定线程释放的对象；标签0表示未设置类型，忽略
+// 之后回收时该类型的不可达对象不再清除，而是放入队列并保持注册，直到用slime_gc_pop_disposal取出，它们引用的对象也一起保留；
+// 放入队列的对象不调用释放回调，重新变为可达也不会移出队列。队列已满时该类型新的不可达对象推迟到队列有空位后的回收。
+// 已有队列时只修改容量；capacity为0时不再放入新对象。销毁回收器时队列中尚未取出的对象交给释放回调
+void slime_gc_set_type_disposal_queue(GarbageCollector* gc, unsigned int type_id, size_t capacity);
+
+// 从类型标签的处置队列中按放入顺序最多取出capacity个对象写入out，返回写入数量
+// 取出的对象此时才被注销，派发地址失效（SLIME_GC_INVALIDATE_SWEPT）和弱引用清除通知；不调用释放回调，对象由宿主释放
+size_t slime_gc_pop_disposal(GarbageCollector* gc, unsigned int type_id, void** out, size_t capacity);
+
+// 以上函数在线程安全的回收器上的版本
+SlimeGcCollectResult slime_gc_ts_collect_detailed(const ConcurrentGarbageCollector* gc);
+void slime_gc_ts_set_type_disposal_queue(const ConcurrentGarbageCollector* gc, unsigned int type_id, size_t capacity);
+size_t slime_gc_ts_pop_disposal(const ConcurrentGarbageCollector* gc, unsigned int type_id, void** out, size_t capacity);
+
+// C接口函数表，新函数只追加在末尾，旧版本的表是新版本的前缀
+typedef struct SlimeGcVTable {
+    size_t size;              // 本表的有效字节数，调用方据此检查字段是否存在
+    unsigned int version;     // 本表对应的ABI版本
+
+    // 版本1
+    GarbageCollector* (*new_gc)(void);
+    void (*destroy)(GarbageCollector* gc);
+    void (*register_object)(GarbageCollector* gc, void* obj);
+    void (*unregister_object)(GarbageCollector* gc, void* obj);
+    void (*add_reference)(GarbageCollector* gc, void* from, void* to);
+    void (*remove_reference)(GarbageCollector* gc, void* from, void* to);
+    void (*clear_references)(GarbageCollector* gc, void* obj);
+    int (*get_reference_count)(const GarbageCollector* gc, void* obj);
+    void (*add_references)(GarbageCollector* gc, void* from, void** to_list, int count);
+    void (*remove_references)(GarbageCollector* gc, void* from, void** to_list, int count);
+    void (*mark_root)(GarbageCollector* gc, void* obj);
+    void (*unmark_root)(GarbageCollector* gc, void* obj);
+    void (*clear_roots)(GarbageCollector* gc);
+    int (*collect)(GarbageCollector* gc);
+
+    // 版本2
+    size_t (*redirect)(GarbageCollector* gc, void* from_obj, void* to_obj, int flags);
+    void (*set_recent_unregister_window)(GarbageCollector* gc, size_t window);
+    void (*set_recently_unregistered_callback)(GarbageCollector* gc, SlimeGcRecentlyUnregisteredCallback callback, void* user_data);
+    int (*get_recently_unregistered_count)(const GarbageCollector* gc);
+    unsigned long long (*subscribe_invalidation)(GarbageCollector* gc, SlimeGcInvalidationCallback callback, void* user_data);
+    void (*unsubscribe_invalidation)(GarbageCollector* gc, unsigned long long id);
+    void (*add_owner)(GarbageCollector* gc, void* child, void* owner);
+    void (*remove_owner)(GarbageCollector* gc, void* child, void* owner);
+    void (*add_owners)(GarbageCollector* gc, void* child, void** owner_list, int count);
+    void (*remove_owners)(GarbageCollector* gc, void* child, void** owner_list, int count);
+    void (*query_many)(const GarbageCollector* gc, void* const* objs, size_t count, SlimeGcObjectQuery* out);
+
+    // 版本3
+    int (*teardown_report)(GarbageCollector* gc, char* out_buf, int capacity);
+
+    // 版本4
+    void (*set_max_objects)(GarbageCollector* gc, size_t max);
+    size_t (*get_object_count)(const GarbageCollector* gc);
+    int (*reserve_objects)(GarbageCollector* gc, size_t n);
+    void (*release_reservation)(GarbageCollector* gc, size_t n);
+    size_t (*get_reserved_objects)(const GarbageCollector* gc);
+    int (*register_object_checked)(GarbageCollector* gc, void* obj);
+
+    // 版本5
+    int (*collect_into)(GarbageCollector* gc, void** out_buf, int capacity);
+    void (*set_free_callback)(GarbageCollector* gc, SlimeGcFreeCallback callback, void* user_data);
+
+    // 版本6
+    void (*push_scope)(GarbageCollector* gc);
+    int (*add_scoped_root)(GarbageCollector* gc, void* obj);
+    int (*pop_scope)(GarbageCollector* gc);
+
+    // 版本7
+    int (*register_object_sized)(GarbageCollector* gc, void* obj, size_t size_bytes);
+    void (*set_default_object_size)(GarbageCollector* gc, size_t size_bytes);
+    size_t (*get_live_bytes)(const GarbageCollector* gc);
+    void (*set_collection_threshold)(GarbageCollector* gc, size_t bytes);
+    int (*maybe_collect)(GarbageCollector* gc);
+
+    // 版本8
+    void (*add_weak_reference)(GarbageCollector* gc, void* from, void* to);
+    void (*remove_weak_reference)(GarbageCollector* gc, void* from, void* to);
+    void (*set_weak_clear_callback)(GarbageCollector* gc, SlimeGcWeakClearCallback callback, void* user_data);
+    int (*is_alive)(const GarbageCollector* gc, void* obj);
+
+    // 版本9
+    ConcurrentGarbageCollector* (*new_threadsafe)(void);
+    void (*ts_destroy)(ConcurrentGarbageCollector* gc);
+    int (*ts_register_object)(const ConcurrentGarbageCollector* gc, void* obj);
+    int (*ts_register_object_sized)(const ConcurrentGarbageCollector* gc, void* obj, size_t size_bytes);
+    void (*ts_unregister_object)(const ConcurrentGarbageCollector* gc, void* obj);
+    void (*ts_add_reference)(const ConcurrentGarbageCollector* gc, void* from, void* to);
+    void (*ts_remove_reference)(const ConcurrentGarbageCollector* gc, void* from, void* to);
+    void (*ts_add_references)(const ConcurrentGarbageCollector* gc, void* from, void** to_list, int count);
+    void (*ts_remove_references)(const ConcurrentGarbageCollector* gc, void* from, void** to_list, int count);
+    void (*ts_clear_references)(const ConcurrentGarbageCollector* gc, void* obj);
+    int (*ts_get_reference_count)(const ConcurrentGarbageCollector* gc, void* obj);
+    void (*ts_add_weak_reference)(const ConcurrentGarbageCollector* gc, void* from, void* to);
+    void (*ts_remove_weak_reference)(const ConcurrentGarbageCollector* gc, void* from, void* to);
+    void (*ts_mark_root)(const ConcurrentGarbageCollector* gc, void* obj);
+    void (*ts_unmark_root)(const ConcurrentGarbageCollector* gc, void* obj);
+    void (*ts_clear_roots)(const ConcurrentGarbageCollector* gc);
+    void (*ts_set_free_callback)(const ConcurrentGarbageCollector* gc, SlimeGcFreeCallback callback, void* user_data);
+    int (*ts_collect)(const ConcurrentGarbageCollector* gc);
+    int (*ts_maybe_collect)(const ConcurrentGarbageCollector* gc);
+    size_t (*ts_get_object_count)(const ConcurrentGarbageCollector* gc);
+    size_t (*ts_get_live_bytes)(const ConcurrentGarbageCollector* gc);
+    int (*ts_is_alive)(const ConcurrentGarbageCollector* gc, void* obj);
+    void (*ts_set_default_object_size)(const ConcurrentGarbageCollector* gc, size_t size_bytes);
+    void (*ts_set_collection_threshold)(const ConcurrentGarbageCollector* gc, size_t bytes);
+    void (*ts_set_weak_clear_callback)(const ConcurrentGarbageCollector* gc, SlimeGcWeakClearCallback callback, void* user_data);
+    void (*ts_push_scope)(const ConcurrentGarbageCollector* gc);
+    int (*ts_add_scoped_root)(const ConcurrentGarbageCollector* gc, void* obj);
+    int (*ts_pop_scope)(const ConcurrentGarbageCollector* gc);
+    int (*ts_collect_into)(const ConcurrentGarbageCollector* gc, void** out_buf, int capacity);
+    size_t (*ts_redirect)(const ConcurrentGarbageCollector* gc, void* from_obj, void* to_obj, int flags);
+    void (*ts_set_recent_unregister_window)(const ConcurrentGarbageCollector* gc, size_t window);
+    void (*ts_set_recently_unregistered_callback)(const ConcurrentGarbageCollector* gc, SlimeGcRecentlyUnregisteredCallback callback, void* user_data);
+    int (*ts_get_recently_unregistered_count)(const ConcurrentGarbageCollector* gc);
+    unsigned long long (*ts_subscribe_invalidation)(const ConcurrentGarbageCollector* gc, SlimeGcInvalidationCallback callback, void* user_data);
+    void (*ts_unsubscribe_invalidation)(const ConcurrentGarbageCollector* gc, unsigned long long id);
+    void (*ts_add_owner)(const ConcurrentGarbageCollector* gc, void* child, void* owner);
+    void (*ts_remove_owner)(const ConcurrentGarbageCollector* gc, void* child, void* owner);
+    void (*ts_add_owners)(const ConcurrentGarbageCollector* gc, void* child, void** owner_list, int count);
+    void (*ts_remove_owners)(const ConcurrentGarbageCollector* gc, void* child, void** owner_list, int count);
+    void (*ts_query_many)(const ConcurrentGarbageCollector* gc, void* const* objs, size_t count, SlimeGcObjectQuery* out);
+    int (*ts_teardown_report)(ConcurrentGarbageCollector* gc, char* out_buf, int capacity);
+    void (*ts_set_max_objects)(const ConcurrentGarbageCollector* gc, size_t max);
+    int (*ts_reserve_objects)(const ConcurrentGarbageCollector* gc, size_t n);
+    void (*ts_release_reservation)(const ConcurrentGarbageCollector* gc, size_t n);
+    size_t (*ts_get_reserved_objects)(const ConcurrentGarbageCollector* gc);
+
+    // 版本10
+    void (*get_stats)(const GarbageCollector* gc, SlimeGcStats* out);
+    int (*dump_dot)(const GarbageCollector* gc, const char* path);
+    int (*dump_json)(const GarbageCollector* gc, const char* path);
+    int (*retaining_path)(const GarbageCollector* gc, void* obj, void** out_buf, int capacity);
+    void (*ts_get_stats)(const ConcurrentGarbageCollector* gc, SlimeGcStats* out);
+    int (*ts_dump_dot)(const ConcurrentGarbageCollector* gc, const char* path);
+    int (*ts_dump_json)(const ConcurrentGarbageCollector* gc, const char* path);
+    int (*ts_retaining_path)(const ConcurrentGarbageCollector* gc, void* obj, void** out_buf, int capacity);
+
+    // 版本11
+    int (*register_object_tagged)(GarbageCollector* gc, void* obj, unsigned int tag);
+    int (*register_object_sized_tagged)(GarbageCollector* gc, void* obj, size_t size_bytes, unsigned int tag);
+    int (*get_type_tag)(const GarbageCollector* gc, void* obj, unsigned int* out_tag);
+    void (*set_finalization_order)(GarbageCollector* gc, const unsigned int* tags, int count);
+    size_t (*count_by_tag)(const GarbageCollector* gc, unsigned int tag);
+    int (*ts_register_object_tagged)(const ConcurrentGarbageCollector* gc, void* obj, unsigned int tag);
+    int (*ts_register_object_sized_tagged)(const ConcurrentGarbageCollector* gc, void* obj, size_t size_bytes, unsigned int tag);
+    int (*ts_get_type_tag)(const ConcurrentGarbageCollector* gc, void* obj, unsigned int* out_tag);
+    void (*ts_set_finalization_order)(const ConcurrentGarbageCollector* gc, const unsigned int* tags, int count);
+    size_t (*ts_count_by_tag)(const ConcurrentGarbageCollector* gc, unsigned int tag);
+
+    // 版本12
+    int (*collect_step)(GarbageCollector* gc, int budget);
+    int (*ts_collect_step)(const ConcurrentGarbageCollector* gc, int budget);
+
+    // 版本13
+    int (*pin)(GarbageCollector* gc, void* obj);
+    void (*unpin)(GarbageCollector* gc, void* obj);
+    int (*pin_count)(const GarbageCollector* gc, void* obj);
+    int (*ts_pin)(const ConcurrentGarbageCollector* gc, void* obj);
+    void (*ts_unpin)(const ConcurrentGarbageCollector* gc, void* obj);
+    int (*ts_pin_count)(const ConcurrentGarbageCollector* gc, void* obj);
+
+    // 版本14
+    size_t (*find_referrers_of_type)(const GarbageCollector* gc, unsigned int tag, SlimeGcReferrerRecord* out, size_t capacity);
+    size_t (*ts_find_referrers_of_type)(const ConcurrentGarbageCollector* gc, unsigned int tag, SlimeGcReferrerRecord* out, size_t capacity);
+
+    // 版本15
+    SlimeGcThreadBuffer* (*thread_buffer_begin)(const ConcurrentGarbageCollector* gc);
+    void (*buffered_register)(SlimeGcThreadBuffer* buf, void* obj, size_t size_bytes, unsigned int type_id);
+    void (*buffered_add_reference)(SlimeGcThreadBuffer* buf, void* from, void* to);
+    int (*thread_buffer_flush)(const ConcurrentGarbageCollector* gc, SlimeGcThreadBuffer* buf);
+    int (*thread_buffer_end)(const ConcurrentGarbageCollector* gc, SlimeGcThreadBuffer* buf);
+    unsigned long long (*ts_get_write_lock_count)(const ConcurrentGarbageCollector* gc);
+
+    // 版本16
+    unsigned int (*pending_work)(const GarbageCollector* gc);
+    SlimeGcPumpResult (*pump)(GarbageCollector* gc, unsigned long long max_micros, unsigned int priorities);
+    unsigned int (*ts_pending_work)(const ConcurrentGarbageCollector* gc);
+    SlimeGcPumpResult (*ts_pump)(const ConcurrentGarbageCollector* gc, unsigned long long max_micros, unsigned int priorities);
+
+    // 版本17
+    int (*swap_in)(GarbageCollector* old, const SlimeGcConfig* new_cfg);
+    int (*ts_swap_in)(const ConcurrentGarbageCollector* gc, const SlimeGcConfig* new_cfg);
+
+    // 版本18
+    int (*add_external_bytes)(GarbageCollector* gc, void* obj, size_t bytes);
+    void (*remove_external_bytes)(GarbageCollector* gc, void* obj, size_t bytes);
+    size_t (*get_external_bytes)(const GarbageCollector* gc, void* obj);
+    int (*ts_add_external_bytes)(const ConcurrentGarbageCollector* gc, void* obj, size_t bytes);
+    void (*ts_remove_external_bytes)(const ConcurrentGarbageCollector* gc, void* obj, size_t bytes);
+    size_t (*ts_get_external_bytes)(const ConcurrentGarbageCollector* gc, void* obj);
+
+    // 版本19
+    void (*set_weak_clear_reason_callback)(GarbageCollector* gc, SlimeGcWeakClearReasonCallback callback, void* user_data);
+    void (*ts_set_weak_clear_reason_callback)(const ConcurrentGarbageCollector* gc, SlimeGcWeakClearReasonCallback callback, void* user_data);
+
+    // 版本20
+    int (*verify)(GarbageCollector* gc, SlimeGcRepairReport* out_report);
+    void (*set_auto_repair)(GarbageCollector* gc, int enabled);
+    int (*repair)(GarbageCollector* gc, SlimeGcRepairReport* out_report);
+    int (*ts_verify)(const ConcurrentGarbageCollector* gc, SlimeGcRepairReport* out_report);
+    void (*ts_set_auto_repair)(const ConcurrentGarbageCollector* gc, int enabled);
+    int (*ts_repair)(const ConcurrentGarbageCollector* gc, SlimeGcRepairReport* out_report);
+
+    // 版本21
+    int (*retaining_anchor)(const GarbageCollector* gc, void* obj);
+    int (*explain)(const GarbageCollector* gc, void* obj, char* out_buf, int capacity);
+    int (*ts_retaining_anchor)(const ConcurrentGarbageCollector* gc, void* obj);
+    int (*ts_explain)(const ConcurrentGarbageCollector* gc, void* obj, char* out_buf, int capacity);
+    int (*validate_graph_json)(const char* text);
+
+    // 版本22
+    SlimeGcCollectResult (*collect_detailed)(GarbageCollector* gc);
+    void (*set_type_disposal_queue)(GarbageCollector* gc, unsigned int type_id, size_t capacity);
+    size_t (*pop_disposal)(GarbageCollector* gc, unsigned int type_id, void** out, size_t capacity);
+    SlimeGcCollectResult (*ts_collect_detailed)(const ConcurrentGarbageCollector* gc);
+    void (*ts_set_type_disposal_queue)(const ConcurrentGarbageCollector* gc, unsigned int type_id, size_t capacity);
+    size_t (*ts_pop_disposal)(const ConcurrentGarbageCollector* gc, unsigned int type_id, void** out, size_t capacity);
+
+    // 版本23
+    int (*export_json_cb)(const GarbageCollector* gc, SlimeGcWriteCallback write_cb, void* ctx);
+    int (*ts_export_json_cb)(const ConcurrentGarbageCollector* gc, SlimeGcWriteCallback write_cb, void* ctx);
+
+    // 版本24
+    void (*ts_set_op_latency)(const ConcurrentGarbageCollector* gc, int enabled);
+    int (*ts_op_latency)(const ConcurrentGarbageCollector* gc, int op_kind, SlimeGcHistogram* out);
+    int (*ts_metrics_text)(const ConcurrentGarbageCollector* gc, char* out_buf, int capacity);
+
+    // 版本25
+    void (*set_recycler)(GarbageCollector* gc, unsigned int type_id, SlimeGcRecyclerCallback callback, void* ctx);
+    void (*ts_set_recycler)(const ConcurrentGarbageCollector* gc, unsigned int type_id, SlimeGcRecyclerCallback callback, void* ctx);
+
+    // 版本26
+    int (*liveness)(const GarbageCollector* gc, void* obj);
+    int (*ts_liveness)(const ConcurrentGarbageCollector* gc, void* obj);
+} SlimeGcVTable;
+
+// 当前函数表的ABI版本
+#define SLIME_GC_VTABLE_VERSION 26
+
+// 获取指定ABI版本的函数表，版本不受支持时返回NULL
+const SlimeGcVTable* slime_gc_get_vtable(unsigned int version);
+
 #ifdef __cplusplus
 }
 #endif
@@ -1,352 +1,10164 @@
 //! Slime语言的垃圾回收器实现
 //! 使用Rust编写以确保内存安全
 
-use std::collections::{HashSet, HashMap};
-use std::os::raw::{c_int, c_void};
+use std::borrow::Borrow;
+use std::collections::{HashSet, HashMap, VecDeque};
+use std::ffi::CStr;
+use std::fmt::{self, Write as _};
+use std::os::raw::{c_char, c_int, c_void};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::ptr::NonNull;
+use std::time::{Duration, Instant};
 
-/// 垃圾回收器
-pub struct GarbageCollector {
-    /// 所有对象的集合
-    objects: HashSet<*mut c_void>,
-    /// 根对象集合
-    roots: HashSet<*mut c_void>,
-    /// 对象引用关系：从一个对象到它引用的所有对象
-    references: HashMap<*mut c_void, HashSet<*mut c_void>>,
+/// 状态码：操作成功
+pub const SLIME_GC_OK: c_int = 0;
+/// 状态码：注册对象数量已达上限
+pub const SLIME_GC_OBJECT_LIMIT: c_int = 1;
+/// 状态码：没有打开的根作用域
+pub const SLIME_GC_NO_SCOPE: c_int = 2;
+/// 状态码：写入文件失败
+pub const SLIME_GC_IO_ERROR: c_int = 3;
+/// 状态码：对象未注册
+pub const SLIME_GC_NOT_REGISTERED: c_int = 4;
+/// 状态码：内部状态不一致
+pub const SLIME_GC_INCONSISTENT: c_int = 5;
+/// 状态码：对象图导出的格式版本不同
+pub const SLIME_GC_VERSION_MISMATCH: c_int = 6;
+/// 状态码：对象图导出的内容不符合格式
+pub const SLIME_GC_INVALID_FORMAT: c_int = 7;
+
+/// 重定向标志：完成后注销原对象
+pub const SLIME_GC_REDIRECT_UNREGISTER: c_int = 1;
+/// 重定向标志：将原对象的根标记转移到目标对象
+pub const SLIME_GC_REDIRECT_MIGRATE_ROOT: c_int = 2;
+/// 重定向标志：将原对象的固定次数转移到目标对象
+pub const SLIME_GC_REDIRECT_MIGRATE_PIN: c_int = 4;
+/// 重定向标志：指向原对象的弱引用也改为指向目标对象，默认弱引用不随重定向改写
+pub const SLIME_GC_REDIRECT_WEAK: c_int = 8;
+
+/// 对象图JSON导出的格式版本，格式不兼容地变化时递增
+pub const SLIME_GC_GRAPH_FORMAT_VERSION: u64 = 2;
+
+/// 存活原因：从根对象可达
+pub const SLIME_GC_ANCHOR_ROOT: c_int = 1;
+/// 存活原因：从作用域根可达
+pub const SLIME_GC_ANCHOR_SCOPED_ROOT: c_int = 2;
+/// 存活原因：从固定的对象可达
+pub const SLIME_GC_ANCHOR_PIN: c_int = 3;
+/// 存活原因：从线程缓冲区持有的对象可达
+pub const SLIME_GC_ANCHOR_THREAD_BUFFER: c_int = 4;
+
+/// 存活状态：回收器不认识该对象（未注册或已被清除）
+pub const SLIME_GC_LIVENESS_DEAD: c_int = 0;
+/// 存活状态：已注册且没有被证明不可达
+pub const SLIME_GC_LIVENESS_LIVE: c_int = 1;
+/// 存活状态：已被证明不可达，但还在等待清除或取出
+pub const SLIME_GC_LIVENESS_CONDEMNED: c_int = 2;
+
+/// 地址失效原因：对象被垃圾回收清除
+pub const SLIME_GC_INVALIDATE_SWEPT: c_int = 1;
+/// 地址失效原因：对象被显式注销
+pub const SLIME_GC_INVALIDATE_UNREGISTERED: c_int = 2;
+/// 地址失效原因：对象被重定向后注销
+pub const SLIME_GC_INVALIDATE_REDIRECTED: c_int = 3;
+
+/// 弱引用清除原因：目标被垃圾回收清除
+pub const SLIME_GC_WEAK_CLEARED_COLLECTED: c_int = 1;
+/// 弱引用清除原因：目标被显式注销或重定向后注销
+pub const SLIME_GC_WEAK_CLEARED_REMOVED: c_int = 2;
+
+/// 延迟工作类别：增量回收的标记，包括回收到期但尚未开始的新一轮
+pub const SLIME_GC_WORK_MARK: u32 = 1;
+/// 延迟工作类别：增量回收的清除
+pub const SLIME_GC_WORK_SWEEP: u32 = 2;
+/// 延迟工作类别：已回收但未被collect_into取走的对象
+pub const SLIME_GC_WORK_GARBAGE: u32 = 4;
+/// 延迟工作类别：尚未派发的回调
+pub const SLIME_GC_WORK_CALLBACKS: u32 = 8;
+/// 所有延迟工作类别
+pub const SLIME_GC_WORK_ALL: u32 = SLIME_GC_WORK_MARK | SLIME_GC_WORK_SWEEP | SLIME_GC_WORK_GARBAGE | SLIME_GC_WORK_CALLBACKS;
+
+/// 操作耗时类别：注册和注销对象
+pub const SLIME_GC_OP_REGISTER: c_int = 0;
+/// 操作耗时类别：添加强引用，批量添加算一次
+pub const SLIME_GC_OP_ADD_REFERENCE: c_int = 1;
+/// 操作耗时类别：移除强引用，批量移除和清除对象的所有引用算一次
+pub const SLIME_GC_OP_REMOVE_REFERENCE: c_int = 2;
+/// 操作耗时类别：根对象、固定和作用域根的增减
+pub const SLIME_GC_OP_ROOT: c_int = 3;
+/// 操作耗时类别：添加和移除弱引用
+pub const SLIME_GC_OP_WEAK: c_int = 4;
+/// 操作耗时类别的数量
+const OP_KINDS: usize = 5;
+/// 各操作耗时类别在指标文本中的名称
+const OP_KIND_NAMES: [&str; OP_KINDS] = ["register", "add_reference", "remove_reference", "root", "weak"];
+
+/// 操作耗时直方图的桶数
+pub const SLIME_GC_LATENCY_BUCKETS: usize = 32;
+
+/// 释放回调：(被回收的对象, 用户数据)
+pub type SlimeGcFreeCallback = extern "C" fn(*mut c_void, *mut c_void);
+
+/// 弱引用清除回调：(引用方, 被清除的目标, 用户数据)
+pub type SlimeGcWeakClearCallback = extern "C" fn(*mut c_void, *mut c_void, *mut c_void);
+
+/// 带原因的弱引用清除回调：(引用方, 被清除的目标, 清除原因, 用户数据)
+pub type SlimeGcWeakClearReasonCallback = extern "C" fn(*mut c_void, *mut c_void, c_int, *mut c_void);
+
+/// 地址失效通知回调：(地址数组, 数量, 失效原因, 用户数据)
+pub type SlimeGcInvalidationCallback = extern "C" fn(*const *mut c_void, usize, c_int, *mut c_void);
+
+/// 流式导出的写入回调：(数据, 字节数, 用户数据)，返回非0时中止导出
+pub type SlimeGcWriteCallback = extern "C" fn(*const u8, usize, *mut c_void) -> c_int;
+
+/// 回收复用回调：(被清除的对象数组, 数量, 用户数据)，返回从数组开头起接受复用的对象数量
+pub type SlimeGcRecyclerCallback = extern "C" fn(*const *mut c_void, usize, *mut c_void) -> usize;
+
+/// 回收器追踪的对象，保证非空
+///
+/// 与`*mut c_void`布局相同，回收器只保存和比较地址，从不解引用。
+/// Rust接口的对象参数都接受IntoObjRef，返回的对象都是ObjRef；C接口仍使用原始指针
+#[repr(transparent)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjRef(NonNull<c_void>);
+
+impl ObjRef {
+    /// 从原始指针创建，空指针返回None
+    pub fn new(ptr: *mut c_void) -> Option<Self> {
+        NonNull::new(ptr).map(ObjRef)
+    }
+
+    /// 获取原始指针
+    pub fn as_ptr(self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+}
+
+impl fmt::Debug for ObjRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Pointer for ObjRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.0, f)
+    }
+}
+
+impl From<NonNull<c_void>> for ObjRef {
+    fn from(ptr: NonNull<c_void>) -> Self {
+        ObjRef(ptr)
+    }
+}
+
+impl From<ObjRef> for NonNull<c_void> {
+    fn from(obj: ObjRef) -> Self {
+        obj.0
+    }
+}
+
+impl From<ObjRef> for *mut c_void {
+    fn from(obj: ObjRef) -> Self {
+        obj.as_ptr()
+    }
+}
+
+/// 把空指针转换为ObjRef时的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NullObjRef;
+
+impl fmt::Display for NullObjRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "null object pointer")
+    }
+}
+
+impl std::error::Error for NullObjRef {}
+
+impl TryFrom<*mut c_void> for ObjRef {
+    type Error = NullObjRef;
+
+    fn try_from(ptr: *mut c_void) -> Result<Self, NullObjRef> {
+        ObjRef::new(ptr).ok_or(NullObjRef)
+    }
+}
+
+impl PartialEq<*mut c_void> for ObjRef {
+    fn eq(&self, other: &*mut c_void) -> bool {
+        self.as_ptr() == *other
+    }
+}
+
+impl PartialEq<ObjRef> for *mut c_void {
+    fn eq(&self, other: &ObjRef) -> bool {
+        *self == other.as_ptr()
+    }
+}
+
+/// 返回的`HashSet<ObjRef>`等集合可以直接用原始指针查询，如`refs.contains(&ptr)`
+///
+/// ObjRef与`*mut c_void`布局相同，哈希、相等和排序都只取决于地址，满足Borrow的约定
+impl Borrow<*mut c_void> for ObjRef {
+    fn borrow(&self) -> &*mut c_void {
+        // repr(transparent)包装的NonNull<c_void>与*mut c_void布局相同
+        unsafe { &*(self as *const ObjRef).cast::<*mut c_void>() }
+    }
+}
+
+/// 可以作为对象参数传给Rust接口的类型
+///
+/// 原有的`*mut c_void`调用方式照常可用：空指针转换为None，
+/// 接口把它当作无操作或“对象不存在”处理，与引入ObjRef之前的行为一致
+///
+/// 不使用`Into<ObjRef>`：`*mut c_void`到ObjRef的转换可能因空指针失败，
+/// Into只能表达不会失败的转换，对空指针只能panic，无法保留原来的无操作语义
+pub trait IntoObjRef: Copy {
+    /// 转换为ObjRef，空指针返回None
+    fn into_obj_ref(self) -> Option<ObjRef>;
+}
+
+impl IntoObjRef for ObjRef {
+    fn into_obj_ref(self) -> Option<ObjRef> {
+        Some(self)
+    }
+}
+
+impl IntoObjRef for Option<ObjRef> {
+    fn into_obj_ref(self) -> Option<ObjRef> {
+        self
+    }
+}
+
+impl IntoObjRef for NonNull<c_void> {
+    fn into_obj_ref(self) -> Option<ObjRef> {
+        Some(ObjRef(self))
+    }
+}
+
+impl IntoObjRef for *mut c_void {
+    fn into_obj_ref(self) -> Option<ObjRef> {
+        ObjRef::new(self)
+    }
+}
+
+/// 批量查询中单个对象的结果
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SlimeGcObjectQuery {
+    /// 对象是否已注册
+    pub registered: c_int,
+    /// 对象是否为根对象，只看mark_root设置的根，固定和作用域根分别由pinned和scoped_root报告
+    pub root: c_int,
+    /// 对象是否可从根对象到达
+    pub reachable: c_int,
+    /// 对象引用的对象数量
+    pub out_degree: c_int,
+    /// 对象是否被固定
+    pub pinned: c_int,
+    /// 对象是否为打开的作用域中的作用域根
+    pub scoped_root: c_int,
+    /// 引用该对象的已注册对象数量，来自反向索引
+    pub in_degree: c_int,
+    /// 对象大小（字节），不含外部内存
+    pub size: usize,
+    /// 对象的类型标签，未设置时为0
+    pub type_tag: u32,
+    /// 对象的存活状态，为SLIME_GC_LIVENESS_*之一，与liveness相同
+    pub liveness: c_int,
+}
+
+/// 按类型查找引用方时的单条结果
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlimeGcReferrerRecord {
+    /// 引用方
+    pub from: *mut c_void,
+    /// 被引用的指定类型对象
+    pub to: *mut c_void,
+}
+
+/// 创建或替换回收器时使用的配置
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlimeGcConfig {
+    /// 注册对象数量上限，0表示不限制
+    pub max_objects: usize,
+    /// 未指定大小的对象按多少字节计入
+    pub default_object_size: usize,
+    /// 自动回收阈值（字节）
+    pub collection_threshold: usize,
+    /// 最近注销对象的检测窗口，0表示关闭检测
+    pub recent_unregister_window: usize,
+}
+
+impl Default for SlimeGcConfig {
+    fn default() -> Self {
+        SlimeGcConfig {
+            max_objects: 0,
+            default_object_size: 0,
+            collection_threshold: DEFAULT_COLLECTION_THRESHOLD,
+            recent_unregister_window: 0,
+        }
+    }
+}
+
+/// 一致性检查或修复的结果，各字段为对应类别发现（或修复）的问题数量
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SlimeGcRepairReport {
+    /// 正向引用存在但反向索引中缺失的条目，强弱引用合计
+    pub missing_reverse_edges: usize,
+    /// 反向索引中存在但没有对应正向引用的条目，强弱引用合计
+    pub stale_reverse_edges: usize,
+    /// 引用方未注册的强引用
+    pub dangling_edges: usize,
+    /// 引用方未注册的弱引用
+    pub dangling_weak_edges: usize,
+    /// 指向未注册地址的根标记
+    pub dangling_roots: usize,
+    /// 指向未注册地址的固定和线程缓冲区持有
+    pub dangling_pins: usize,
+    /// 指向未注册地址的作用域根
+    pub dangling_scope_roots: usize,
+    /// 未注册地址的大小、外部内存和类型标签记录
+    pub stale_records: usize,
+    /// 与实际状态不符的计数：存活字节数、作用域根计数和为0的固定次数
+    pub counters_fixed: usize,
+    /// 发现的问题是否已经修复，1表示已修复
+    pub repaired: c_int,
+}
+
+impl SlimeGcRepairReport {
+    /// 没有发现任何问题
+    pub fn is_clean(&self) -> bool {
+        SlimeGcRepairReport { repaired: self.repaired, ..Default::default() } == *self
+    }
+}
+
+/// 回收器统计信息
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// 当前注册对象数量
+    pub total_objects: usize,
+    /// 当前根对象数量（不含作用域根）
+    pub root_count: usize,
+    /// 已注册对象之间的强引用数量
+    pub total_edges: usize,
+    /// 上次回收清除的对象数量
+    pub collected_last_cycle: usize,
+    /// 上次回收标记阶段的耗时
+    pub last_mark_duration: Duration,
+    /// 上次回收清除阶段的耗时
+    pub last_sweep_duration: Duration,
+    /// 注册对象数量上限，0表示不限制
+    pub max_objects: usize,
+    /// 已预留但尚未使用的对象数量
+    pub reserved_objects: usize,
+    /// 计入数量上限的用量：注册对象数量加上预留数量
+    pub used_object_slots: usize,
+}
+
+/// C接口使用的统计信息，耗时以纳秒为单位
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SlimeGcStats {
+    pub total_objects: usize,
+    pub root_count: usize,
+    pub total_edges: usize,
+    pub collected_last_cycle: usize,
+    pub last_mark_nanos: u64,
+    pub last_sweep_nanos: u64,
+    pub max_objects: usize,
+    pub reserved_objects: usize,
+    pub used_object_slots: usize,
+}
+
+/// 一类操作的耗时直方图，耗时以纳秒为单位
+///
+/// buckets[0]为耗时0纳秒的次数，buckets[i]（i≥1）为耗时在[2^(i-1), 2^i)纳秒内的次数，
+/// 最后一个桶还包括所有更长的耗时
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SlimeGcHistogram {
+    /// 记录的操作次数
+    pub count: u64,
+    /// 所有操作的总耗时
+    pub total_nanos: u64,
+    /// 最长的单次耗时
+    pub max_nanos: u64,
+    /// 各桶的操作次数
+    pub buckets: [u64; SLIME_GC_LATENCY_BUCKETS],
+}
+
+/// 可以从多个线程同时记录的耗时直方图
+struct LatencyHistogram {
+    count: AtomicU64,
+    total_nanos: AtomicU64,
+    max_nanos: AtomicU64,
+    buckets: [AtomicU64; SLIME_GC_LATENCY_BUCKETS],
+}
+
+impl LatencyHistogram {
+    const fn new() -> Self {
+        LatencyHistogram {
+            count: AtomicU64::new(0),
+            total_nanos: AtomicU64::new(0),
+            max_nanos: AtomicU64::new(0),
+            buckets: [const { AtomicU64::new(0) }; SLIME_GC_LATENCY_BUCKETS],
+        }
+    }
+
+    fn record(&self, nanos: u64) {
+        let bucket = ((u64::BITS - nanos.leading_zeros()) as usize).min(SLIME_GC_LATENCY_BUCKETS - 1);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    /// 读出当前的计数，与并发的记录之间不保证各字段完全一致
+    fn snapshot(&self) -> SlimeGcHistogram {
+        SlimeGcHistogram {
+            count: self.count.load(Ordering::Relaxed),
+            total_nanos: self.total_nanos.load(Ordering::Relaxed),
+            max_nanos: self.max_nanos.load(Ordering::Relaxed),
+            buckets: std::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// 增量回收单步的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CollectStepResult {
+    /// 本轮回收是否已经完成
+    pub finished: bool,
+    /// 本轮回收到目前为止清除的对象数量
+    pub freed: usize,
+}
+
+/// 一次回收的详细结果
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SlimeGcCollectResult {
+    /// 清除的对象数量
+    pub freed: usize,
+    /// 放入处置队列、等待宿主取出的对象数量
+    pub queued_for_disposal: usize,
+    /// 所属类型的处置队列已满、推迟到以后回收的不可达对象数量
+    pub deferred_by_backpressure: usize,
+}
+
+/// 一次pump的结果，回调以批计，其他类别以对象计
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SlimeGcPumpResult {
+    /// 本次扫描过引用的对象数量
+    pub mark_done: usize,
+    /// 已发现但尚未扫描的对象数量，继续扫描还可能发现更多
+    pub mark_remaining: usize,
+    /// 本次检查过是否需要清除的对象数量
+    pub sweep_done: usize,
+    /// 尚未检查的对象数量，标记完成前为0
+    pub sweep_remaining: usize,
+    /// 本次交给释放回调的未取走对象数量
+    pub garbage_done: usize,
+    /// 仍未取走的对象数量
+    pub garbage_remaining: usize,
+    /// 本次派发的回调批数
+    pub callbacks_done: usize,
+    /// 尚未派发的回调批数
+    pub callbacks_remaining: usize,
+    /// 本次增量回收清除的对象数量
+    pub freed: usize,
+}
+
+/// 增量回收所处的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IncrementalPhase {
+    Mark,
+    Sweep,
+}
+
+/// 正在进行的增量回收
+struct IncrementalCycle {
+    phase: IncrementalPhase,
+    /// 本轮已标记为存活的对象
+    marked: HashSet<ObjRef>,
+    /// 已标记但尚未扫描其引用的对象
+    worklist: Vec<ObjRef>,
+    /// 本轮开始时已注册的对象，清除阶段逐段检查其中未标记的对象
+    snapshot: Vec<ObjRef>,
+    /// snapshot中下一个待检查的位置
+    next_sweep: usize,
+    /// 本轮到目前为止清除的对象数量
+    freed: usize,
+    /// 本轮标记阶段的累计耗时
+    mark_duration: Duration,
+    /// 本轮清除阶段的累计耗时
+    sweep_duration: Duration,
+    /// 本轮放入处置队列的对象数量
+    queued: usize,
+    /// 本轮因处置队列已满而推迟的对象数量
+    deferred: usize,
+}
+
+/// 一个类型的处置队列
+struct DisposalQueue {
+    /// 队列容量，为0时不再放入新对象
+    capacity: usize,
+    /// 等待宿主取出的对象，按放入顺序排列
+    objs: VecDeque<ObjRef>,
+}
+
+impl From<GcStats> for SlimeGcStats {
+    fn from(stats: GcStats) -> Self {
+        SlimeGcStats {
+            total_objects: stats.total_objects,
+            root_count: stats.root_count,
+            total_edges: stats.total_edges,
+            collected_last_cycle: stats.collected_last_cycle,
+            last_mark_nanos: stats.last_mark_duration.as_nanos() as u64,
+            last_sweep_nanos: stats.last_sweep_duration.as_nanos() as u64,
+            max_objects: stats.max_objects,
+            reserved_objects: stats.reserved_objects,
+            used_object_slots: stats.used_object_slots,
+        }
+    }
+}
+
+/// 使用最近注销对象时的回调：(from, to, 距今注销次数, 用户数据)
+pub type SlimeGcRecentlyUnregisteredCallback = extern "C" fn(*mut c_void, *mut c_void, u64, *mut c_void);
+
+/// 最近注销对象的环形缓冲区
+struct RecentUnregisters {
+    /// 固定容量的槽位：(对象, 注销序号)，None表示尚未使用
+    slots: Vec<(Option<ObjRef>, u64)>,
+    /// 下一个写入位置
+    next: usize,
+    /// 已记录的注销次数
+    seq: u64,
+    /// 对象到其最近一次注销序号的索引，槽位被覆盖时同步移除
+    index: HashMap<ObjRef, u64>,
+}
+
+impl RecentUnregisters {
+    fn new(window: usize) -> Self {
+        RecentUnregisters {
+            slots: vec![(None, 0); window],
+            next: 0,
+            seq: 0,
+            index: HashMap::new(),
+        }
+    }
+
+    /// 记录一次注销，覆盖最旧的槽位
+    fn record(&mut self, obj: ObjRef) {
+        if self.slots.is_empty() {
+            return;
+        }
+        self.seq += 1;
+        // 被覆盖的槽位若仍是该对象最近一次注销，则从索引中移除
+        if let (Some(evicted), evicted_seq) = self.slots[self.next]
+            && self.index.get(&evicted) == Some(&evicted_seq)
+        {
+            self.index.remove(&evicted);
+        }
+        self.slots[self.next] = (Some(obj), self.seq);
+        self.index.insert(obj, self.seq);
+        self.next = (self.next + 1) % self.slots.len();
+    }
+
+    /// 对象被重新注册后不再视为已注销；残留的槽位覆盖时因序号不符而被忽略
+    fn forget(&mut self, obj: ObjRef) {
+        self.index.remove(&obj);
+    }
+
+    /// 查询对象距今被注销的次数，1表示最近一次注销
+    fn age(&self, obj: Option<ObjRef>) -> Option<u64> {
+        obj.and_then(|obj| self.index.get(&obj)).map(|&seq| self.seq - seq + 1)
+    }
+}
+
+/// 默认的自动回收阈值（字节）
+const DEFAULT_COLLECTION_THRESHOLD: usize = 1024 * 1024;
+
+/// 拆除报告中每类残留状态最多列出的示例数量
+const TEARDOWN_REPORT_EXAMPLES: usize = 8;
+
+/// pump每推进这么多个对象检查一次时间预算
+const PUMP_CHUNK_OBJECTS: usize = 256;
+
+/// pump在回收器内推进的类别，按优先级排列；回调在借用释放后最后派发
+const PUMP_ORDER: [u32; 3] = [SLIME_GC_WORK_MARK, SLIME_GC_WORK_SWEEP, SLIME_GC_WORK_GARBAGE];
+
+/// 回收器拆除时仍残留的状态
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TeardownReport {
+    /// 拆除时仍处于注册状态的对象数量
+    pub leftover_objects: usize,
+    /// 残留对象示例，按地址排序
+    pub leftover_object_examples: Vec<ObjRef>,
+    /// 残留对象按类型标签分组的数量，按标签排序；未设置标签的对象计入标签0
+    pub leftover_objects_by_tag: Vec<(u32, usize)>,
+    /// 仍让对象存活的起点按种类分组的数量，按SLIME_GC_ANCHOR_*排序，只列出不为0的种类；
+    /// 作用域根只计打开的作用域中的对象，线程缓冲区只计尚未刷新的缓冲区持有的对象
+    pub leftover_roots_by_set: Vec<(LivenessAnchor, usize)>,
+    /// 拆除时仍未取消的根对象数量
+    pub leftover_roots: usize,
+    /// 残留根对象示例，按地址排序
+    pub leftover_root_examples: Vec<ObjRef>,
+    /// 拆除时仍未关闭的根作用域数量
+    pub open_root_scopes: usize,
+    /// 拆除时仍未释放的对象预留数量
+    pub outstanding_reservations: usize,
+    /// collect_into已回收但宿主尚未取走的对象数量
+    pub untaken_garbage: usize,
+    /// 拆除时仍未移除的弱引用数量
+    pub leftover_weak_references: usize,
+    /// 仍持有弱引用的对象示例，按地址排序
+    pub leftover_weak_holder_examples: Vec<ObjRef>,
+    /// 拆除时仍被固定的对象数量
+    pub leftover_pins: usize,
+    /// 仍被固定的对象示例，按地址排序
+    pub leftover_pin_examples: Vec<ObjRef>,
+    /// 仍有尚未应用的操作的线程缓冲区数量，只有线程安全的回收器会报告
+    pub unflushed_thread_buffers: usize,
+    /// 这些线程缓冲区中尚未应用的操作数量，拆除时不会应用
+    pub unflushed_buffer_ops: usize,
+    /// 尚未派发的回调批数，拆除时丢弃
+    pub undispatched_callbacks: usize,
+    /// 处置队列中尚未被取出的对象数量，它们同时计入残留对象
+    pub undrained_disposal_objects: usize,
+}
+
+impl TeardownReport {
+    /// 没有任何残留状态时返回true
+    pub fn is_empty(&self) -> bool {
+        self.leftover_objects == 0
+            && self.leftover_roots == 0
+            && self.open_root_scopes == 0
+            && self.outstanding_reservations == 0
+            && self.untaken_garbage == 0
+            && self.leftover_weak_references == 0
+            && self.leftover_pins == 0
+            && self.leftover_roots_by_set.is_empty()
+            && self.unflushed_buffer_ops == 0
+            && self.undispatched_callbacks == 0
+            && self.undrained_disposal_objects == 0
+    }
+}
+
+/// 在反向索引中记录from引用了to
+fn index_insert(index: &mut HashMap<ObjRef, HashSet<ObjRef>>, to: ObjRef, from: ObjRef) {
+    index.entry(to).or_default().insert(from);
+}
+
+/// 从反向索引中移除from对to的引用，集合为空时删除整个条目
+fn index_remove(index: &mut HashMap<ObjRef, HashSet<ObjRef>>, to: ObjRef, from: ObjRef) {
+    if let Some(froms) = index.get_mut(&to) {
+        froms.remove(&from);
+        if froms.is_empty() {
+            index.remove(&to);
+        }
+    }
+}
+
+/// 统计引用方未注册的引用数量，fix为true时删除这些引用
+fn reconcile_sources(objects: &HashSet<ObjRef>, forward: &mut HashMap<ObjRef, HashSet<ObjRef>>, fix: bool) -> usize {
+    let dangling: Vec<_> = forward.keys().copied().filter(|from| !objects.contains(from)).collect();
+    let count = dangling.iter().map(|from| forward[from].len()).sum();
+    if fix {
+        for from in dangling {
+            forward.remove(&from);
+        }
+    }
+    count
+}
+
+/// 对照正向引用统计反向索引中缺失和多余的条目，fix为true时按正向引用重建反向索引
+///
+/// 引用方未注册的正向引用视为已删除，返回(缺失数量, 多余数量)
+fn reconcile_index(
+    objects: &HashSet<ObjRef>,
+    forward: &HashMap<ObjRef, HashSet<ObjRef>>,
+    reverse: &mut HashMap<ObjRef, HashSet<ObjRef>>,
+    fix: bool,
+) -> (usize, usize) {
+    let mut expected: HashMap<ObjRef, HashSet<ObjRef>> = HashMap::new();
+    for (&from, tos) in forward.iter().filter(|(from, _)| objects.contains(*from)) {
+        for &to in tos {
+            index_insert(&mut expected, to, from);
+        }
+    }
+
+    let count_absent = |a: &HashMap<ObjRef, HashSet<ObjRef>>, b: &HashMap<ObjRef, HashSet<ObjRef>>| -> usize {
+        a.iter()
+            .map(|(to, froms)| froms.iter().filter(|from| !b.get(to).is_some_and(|f| f.contains(*from))).count())
+            .sum()
+    };
+    let missing = count_absent(&expected, reverse);
+    let stale = count_absent(reverse, &expected);
+    if fix {
+        *reverse = expected;
+    }
+    (missing, stale)
+}
+
+/// 取按地址排序后的前若干个示例
+fn sorted_examples(set: &HashSet<ObjRef>) -> Vec<ObjRef> {
+    let mut examples: Vec<_> = set.iter().copied().collect();
+    examples.sort();
+    examples.truncate(TEARDOWN_REPORT_EXAMPLES);
+    examples
+}
+
+/// 写出一类残留状态及其示例
+fn write_examples(f: &mut fmt::Formatter<'_>, title: &str, count: usize, examples: &[ObjRef]) -> fmt::Result {
+    write!(f, "\n{} ({}):", title, count)?;
+    for obj in examples {
+        write!(f, " {:p}", *obj)?;
+    }
+    if count > examples.len() {
+        write!(f, " ...")?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for TeardownReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "teardown report: clean");
+        }
+
+        write!(f, "teardown report: {} leftover objects, {} leftover roots, {} open root scopes, {} outstanding reservations, {} untaken garbage, {} leftover weak references, {} leftover pins, \
+                   {} unflushed buffer operations, {} undispatched callbacks, {} undrained disposal objects",
+            self.leftover_objects, self.leftover_roots, self.open_root_scopes, self.outstanding_reservations, self.untaken_garbage,
+            self.leftover_weak_references, self.leftover_pins, self.unflushed_buffer_ops, self.undispatched_callbacks, self.undrained_disposal_objects)?;
+        // 线程缓冲区和回调在其他状态之前报告：它们没有应用或派发，下面各项不含它们的影响
+        if self.unflushed_buffer_ops > 0 {
+            write!(f, "\nthread buffers never flushed: {} ({} operations)", self.unflushed_thread_buffers, self.unflushed_buffer_ops)?;
+        }
+        if self.undispatched_callbacks > 0 {
+            write!(f, "\ncallback batches never dispatched: {}", self.undispatched_callbacks)?;
+        }
+        if self.leftover_objects > 0 {
+            write_examples(f, "objects still registered", self.leftover_objects, &self.leftover_object_examples)?;
+            write!(f, "\nobjects by type tag:")?;
+            for (tag, count) in &self.leftover_objects_by_tag {
+                write!(f, " {}={}", tag, count)?;
+            }
+        }
+        if !self.leftover_roots_by_set.is_empty() {
+            write!(f, "\nroots by root set:")?;
+            for (anchor, count) in &self.leftover_roots_by_set {
+                write!(f, " {}={}", anchor.set_name(), count)?;
+            }
+        }
+        if self.leftover_roots > 0 {
+            write_examples(f, "roots never unrooted", self.leftover_roots, &self.leftover_root_examples)?;
+        }
+        if self.open_root_scopes > 0 {
+            write!(f, "\nroot scopes never popped: {}", self.open_root_scopes)?;
+        }
+        if self.outstanding_reservations > 0 {
+            write!(f, "\nreservations never released: {}", self.outstanding_reservations)?;
+        }
+        if self.untaken_garbage > 0 {
+            write!(f, "\ncollected objects never taken: {}", self.untaken_garbage)?;
+        }
+        if self.leftover_weak_references > 0 {
+            write_examples(f, "weak references never removed", self.leftover_weak_references, &self.leftover_weak_holder_examples)?;
+        }
+        if self.leftover_pins > 0 {
+            write_examples(f, "pinned objects never unpinned", self.leftover_pins, &self.leftover_pin_examples)?;
+        }
+        if self.undrained_disposal_objects > 0 {
+            write!(f, "\ndisposal queue objects never popped: {}", self.undrained_disposal_objects)?;
+        }
+        Ok(())
+    }
+}
+
+/// 对象存活的起点：引用路径从这种对象出发
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LivenessAnchor {
+    /// 根对象
+    Root,
+    /// 作用域根
+    ScopedRoot,
+    /// 固定的对象
+    Pin,
+    /// 线程缓冲区持有的对象
+    ThreadBuffer,
+}
+
+impl LivenessAnchor {
+    /// 对应的SLIME_GC_ANCHOR_*常量
+    pub fn code(self) -> c_int {
+        match self {
+            LivenessAnchor::Root => SLIME_GC_ANCHOR_ROOT,
+            LivenessAnchor::ScopedRoot => SLIME_GC_ANCHOR_SCOPED_ROOT,
+            LivenessAnchor::Pin => SLIME_GC_ANCHOR_PIN,
+            LivenessAnchor::ThreadBuffer => SLIME_GC_ANCHOR_THREAD_BUFFER,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            LivenessAnchor::Root => "a root",
+            LivenessAnchor::ScopedRoot => "a scoped root",
+            LivenessAnchor::Pin => "a pinned object",
+            LivenessAnchor::ThreadBuffer => "an object held by a thread buffer",
+        }
+    }
+
+    /// 拆除报告中的分组名称
+    fn set_name(self) -> &'static str {
+        match self {
+            LivenessAnchor::Root => "root",
+            LivenessAnchor::ScopedRoot => "scoped_root",
+            LivenessAnchor::Pin => "pin",
+            LivenessAnchor::ThreadBuffer => "thread_buffer",
+        }
+    }
+}
+
+/// 对象的存活状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liveness {
+    /// 回收器不认识该对象：未注册，或已被清除、注销
+    Dead,
+    /// 已注册且没有被证明不可达
+    Live,
+    /// 已被证明不可达但仍保持注册：在处置队列中等待取出，或在增量回收的清除阶段等待清除
+    Condemned,
+}
+
+impl Liveness {
+    /// 对应的SLIME_GC_LIVENESS_*常量
+    pub fn code(self) -> c_int {
+        match self {
+            Liveness::Dead => SLIME_GC_LIVENESS_DEAD,
+            Liveness::Live => SLIME_GC_LIVENESS_LIVE,
+            Liveness::Condemned => SLIME_GC_LIVENESS_CONDEMNED,
+        }
+    }
+
+    /// 导出中使用的名称，Dead不会出现在导出中
+    fn name(self) -> &'static str {
+        match self {
+            Liveness::Dead => "dead",
+            Liveness::Live => "live",
+            Liveness::Condemned => "condemned",
+        }
+    }
+}
+
+/// 对象图JSON不符合当前导出格式的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphSchemaError {
+    /// 不是合法的JSON
+    Syntax,
+    /// 格式版本与SLIME_GC_GRAPH_FORMAT_VERSION不同，None表示缺少版本字段
+    Version(Option<u64>),
+    /// 缺少字段或字段类型不符，内容为字段路径
+    Field(String),
 }
 
-impl GarbageCollector {
-    /// 创建新的垃圾回收器
-    pub fn new() -> Self {
-        GarbageCollector {
-            objects: HashSet::new(),
-            roots: HashSet::new(),
-            references: HashMap::new(),
-        }
+impl fmt::Display for GraphSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphSchemaError::Syntax => write!(f, "not valid JSON"),
+            GraphSchemaError::Version(Some(found)) => {
+                write!(f, "format version {} does not match {}", found, SLIME_GC_GRAPH_FORMAT_VERSION)
+            }
+            GraphSchemaError::Version(None) => write!(f, "missing format_version"),
+            GraphSchemaError::Field(path) => write!(f, "missing or mistyped field {}", path),
+        }
+    }
+}
+
+/// 校验对象图导出时使用的JSON值
+enum JsonValue {
+    Null,
+    Bool,
+    /// 非负整数以外的数字记为None
+    Number(Option<u64>),
+    Str(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn field(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// 嵌套层数上限，防止恶意输入耗尽栈空间
+const JSON_MAX_DEPTH: usize = 64;
+
+/// 最小的JSON解析器，只保留校验需要的信息
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn parse(text: &str) -> Option<JsonValue> {
+        let mut parser = JsonParser { bytes: text.as_bytes(), pos: 0 };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        (parser.pos == parser.bytes.len()).then_some(value)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// 跳过空白后读取一个字节，读到的字节不是expected时失败
+    fn expect(&mut self, expected: u8) -> Option<()> {
+        self.skip_whitespace();
+        (self.bytes.get(self.pos) == Some(&expected)).then(|| self.pos += 1)
+    }
+
+    fn value(&mut self, depth: usize) -> Option<JsonValue> {
+        if depth > JSON_MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        match *self.bytes.get(self.pos)? {
+            b'{' => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                if self.expect(b'}').is_some() {
+                    return Some(JsonValue::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((key, self.value(depth + 1)?));
+                    if self.expect(b',').is_none() {
+                        self.expect(b'}')?;
+                        return Some(JsonValue::Object(fields));
+                    }
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if self.expect(b']').is_some() {
+                    return Some(JsonValue::Array(items));
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    if self.expect(b',').is_none() {
+                        self.expect(b']')?;
+                        return Some(JsonValue::Array(items));
+                    }
+                }
+            }
+            b'"' => self.string().map(JsonValue::Str),
+            b't' => self.literal("true", JsonValue::Bool),
+            b'f' => self.literal("false", JsonValue::Bool),
+            b'n' => self.literal("null", JsonValue::Null),
+            _ => self.number(),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: JsonValue) -> Option<JsonValue> {
+        self.bytes[self.pos..].starts_with(word.as_bytes()).then(|| {
+            self.pos += word.len();
+            value
+        })
+    }
+
+    /// 读取字符串，转义序列原样保留
+    fn string(&mut self) -> Option<String> {
+        if self.bytes.get(self.pos) != Some(&b'"') {
+            return None;
+        }
+        let start = self.pos + 1;
+        let mut end = start;
+        loop {
+            match *self.bytes.get(end)? {
+                b'"' => break,
+                b'\\' => end += 2,
+                _ => end += 1,
+            }
+        }
+        self.pos = end + 1;
+        String::from_utf8(self.bytes[start..end].to_vec()).ok()
+    }
+
+    fn number(&mut self) -> Option<JsonValue> {
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            let from = parser.pos;
+            while parser.bytes.get(parser.pos).is_some_and(u8::is_ascii_digit) {
+                parser.pos += 1;
+            }
+            parser.pos > from
+        };
+        let negative = self.bytes[self.pos] == b'-';
+        if negative {
+            self.pos += 1;
+        }
+        if !digits(self) {
+            return None;
+        }
+        let integer_end = self.pos;
+        if self.bytes.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            if !digits(self) {
+                return None;
+            }
+        }
+        if matches!(self.bytes.get(self.pos), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.bytes.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                return None;
+            }
+        }
+        let integer = (!negative && integer_end == self.pos)
+            .then(|| std::str::from_utf8(&self.bytes[start..integer_end]).ok()?.parse().ok())
+            .flatten();
+        Some(JsonValue::Number(integer))
+    }
+}
+
+/// 导出中每个节点flags对象的字段
+const GRAPH_NODE_FLAGS: [&str; 6] = ["root", "pinned", "scoped_root", "buffer_held", "weakly_referenced", "finalizer_registered"];
+/// 导出中每个节点的数字字段
+const GRAPH_NODE_NUMBERS: [&str; 4] = ["pin_count", "size", "external_bytes", "tag"];
+/// 导出中节点liveness字段的取值
+const GRAPH_NODE_LIVENESS: [&str; 2] = ["live", "condemned"];
+/// 导出中边的种类
+const GRAPH_EDGE_KINDS: [&str; 3] = ["strong", "owner", "weak"];
+
+/// 检查文本是否符合当前版本的对象图JSON导出格式
+///
+/// 先检查format_version，再检查每个节点和边的必需字段及其类型；多出的字段不视为错误，
+/// 方便旧工具读取只增加了字段的导出
+pub fn validate_graph_json(text: &str) -> Result<(), GraphSchemaError> {
+    let root = JsonParser::parse(text).ok_or(GraphSchemaError::Syntax)?;
+    match root.field("format_version") {
+        Some(JsonValue::Number(Some(version))) if *version == SLIME_GC_GRAPH_FORMAT_VERSION => {}
+        Some(JsonValue::Number(Some(version))) => return Err(GraphSchemaError::Version(Some(*version))),
+        _ => return Err(GraphSchemaError::Version(None)),
+    }
+
+    let field_error = |path: String| Err(GraphSchemaError::Field(path));
+    let Some(JsonValue::Array(nodes)) = root.field("nodes") else {
+        return field_error("nodes".into());
+    };
+    for (i, node) in nodes.iter().enumerate() {
+        if !matches!(node.field("id"), Some(JsonValue::Str(_))) {
+            return field_error(format!("nodes[{}].id", i));
+        }
+        let Some(flags @ JsonValue::Object(_)) = node.field("flags") else {
+            return field_error(format!("nodes[{}].flags", i));
+        };
+        if let Some(flag) = GRAPH_NODE_FLAGS.iter().find(|flag| !matches!(flags.field(flag), Some(JsonValue::Bool))) {
+            return field_error(format!("nodes[{}].flags.{}", i, flag));
+        }
+        if let Some(name) = GRAPH_NODE_NUMBERS.iter().find(|name| !matches!(node.field(name), Some(JsonValue::Number(Some(_))))) {
+            return field_error(format!("nodes[{}].{}", i, name));
+        }
+        if !matches!(node.field("liveness"), Some(JsonValue::Str(state)) if GRAPH_NODE_LIVENESS.contains(&state.as_str())) {
+            return field_error(format!("nodes[{}].liveness", i));
+        }
+    }
+
+    let Some(JsonValue::Array(edges)) = root.field("edges") else {
+        return field_error("edges".into());
+    };
+    for (i, edge) in edges.iter().enumerate() {
+        if let Some(name) = ["from", "to"].iter().find(|name| !matches!(edge.field(name), Some(JsonValue::Str(_)))) {
+            return field_error(format!("edges[{}].{}", i, name));
+        }
+        if !matches!(edge.field("kind"), Some(JsonValue::Str(kind)) if GRAPH_EDGE_KINDS.contains(&kind.as_str())) {
+            return field_error(format!("edges[{}].kind", i));
+        }
+    }
+    Ok(())
+}
+
+/// 一次待派发的回调，回调和用户数据在产生时就已复制
+enum PendingCall {
+    Free {
+        callback: SlimeGcFreeCallback,
+        user_data: *mut c_void,
+        objs: Vec<ObjRef>,
+    },
+    Invalidation {
+        subscribers: Vec<(SlimeGcInvalidationCallback, *mut c_void)>,
+        addrs: Vec<ObjRef>,
+        reason: c_int,
+    },
+    RecentlyUnregistered {
+        callback: SlimeGcRecentlyUnregisteredCallback,
+        user_data: *mut c_void,
+        from: Option<ObjRef>,
+        to: Option<ObjRef>,
+        age: u64,
+    },
+    WeakClear {
+        callback: WeakClearCallback,
+        user_data: *mut c_void,
+        pairs: Vec<(ObjRef, ObjRef)>,
+        reason: c_int,
+    },
+    /// 先把一批同类型的对象交给回收复用回调，未被接受的再交给释放回调
+    Recycle {
+        callback: SlimeGcRecyclerCallback,
+        user_data: *mut c_void,
+        objs: Vec<ObjRef>,
+        free: Option<(SlimeGcFreeCallback, *mut c_void)>,
+    },
+}
+
+/// 两种形式的弱引用清除回调，同一时间只设置其中一种
+#[derive(Clone, Copy)]
+enum WeakClearCallback {
+    Plain(SlimeGcWeakClearCallback),
+    WithReason(SlimeGcWeakClearReasonCallback),
+}
+
+/// 从回收器取出的待派发回调
+///
+/// 取出后不再借用回收器，run中的回调可以通过原始指针重入回收器
+#[must_use]
+#[derive(Default)]
+pub struct PendingCallbacks {
+    calls: Vec<PendingCall>,
+}
+
+impl PendingCallbacks {
+    /// 没有待派发的回调时返回true
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// 待派发的回调批数，每批对应一次回收或通知
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    /// 按产生顺序调用所有回调
+    pub fn run(self) {
+        for call in self.calls {
+            call.run();
+        }
+    }
+
+    /// 按产生顺序逐批调用回调，每批之后检查预算，返回调用的批数和剩余的回调
+    ///
+    /// at_least_one为true时即使预算已用完也先调用一批
+    fn run_within(self, start: Instant, max_duration: Duration, at_least_one: bool) -> (usize, PendingCallbacks) {
+        let mut calls = self.calls.into_iter();
+        let mut done = 0;
+        if !at_least_one && start.elapsed() >= max_duration {
+            return (0, PendingCallbacks { calls: calls.collect() });
+        }
+        for call in calls.by_ref() {
+            call.run();
+            done += 1;
+            if start.elapsed() >= max_duration {
+                break;
+            }
+        }
+        (done, PendingCallbacks { calls: calls.collect() })
+    }
+}
+
+impl PendingCall {
+    /// 调用这一批回调
+    fn run(self) {
+        match self {
+            PendingCall::Free { callback, user_data, objs } => {
+                for obj in objs {
+                    callback(obj.as_ptr(), user_data);
+                }
+            }
+            PendingCall::Invalidation { subscribers, addrs, reason } => {
+                for (callback, user_data) in subscribers {
+                    // ObjRef与*mut c_void布局相同，可以直接作为指针数组传出
+                    callback(addrs.as_ptr().cast(), addrs.len(), reason, user_data);
+                }
+            }
+            PendingCall::RecentlyUnregistered { callback, user_data, from, to, age } => {
+                let raw = |obj: Option<ObjRef>| obj.map_or(std::ptr::null_mut(), ObjRef::as_ptr);
+                callback(raw(from), raw(to), age, user_data);
+            }
+            PendingCall::WeakClear { callback, user_data, pairs, reason } => {
+                for (from, to) in pairs {
+                    match callback {
+                        WeakClearCallback::Plain(callback) => callback(from.as_ptr(), to.as_ptr(), user_data),
+                        WeakClearCallback::WithReason(callback) => callback(from.as_ptr(), to.as_ptr(), reason, user_data),
+                    }
+                }
+            }
+            PendingCall::Recycle { callback, user_data, objs, free } => {
+                let accepted = callback(objs.as_ptr().cast(), objs.len(), user_data).min(objs.len());
+                if let Some((free_callback, free_user_data)) = free {
+                    for obj in &objs[accepted..] {
+                        free_callback(obj.as_ptr(), free_user_data);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 垃圾回收器
+///
+/// 方法内部从不直接调用宿主回调，而是记入待派发列表：持有&mut self时调用的回调
+/// 若通过原始指针重入回收器就会产生别名可变引用。C接口在每次调用结束、借用释放后
+/// 才派发这些回调；直接使用Rust接口时用take_pending_callbacks取出后调用run
+pub struct GarbageCollector {
+    /// 所有对象的集合
+    objects: HashSet<ObjRef>,
+    /// 对象大小（字节），大小为0的对象不记录
+    object_sizes: HashMap<ObjRef, usize>,
+    /// 归属于对象的外部内存（字节），为0的对象不记录
+    external_bytes: HashMap<ObjRef, usize>,
+    /// 所有已注册对象的总字节数，包括外部内存
+    live_bytes: usize,
+    /// 未指定大小的对象按此大小计入
+    default_object_size: usize,
+    /// 用户设置的自动回收阈值，调整后的阈值不会低于它
+    initial_threshold: usize,
+    /// 当前的自动回收阈值，存活字节数超过它时maybe_collect才执行回收
+    collection_threshold: usize,
+    /// 根对象集合
+    roots: HashSet<ObjRef>,
+    /// 被固定的对象及其固定次数，次数大于0的对象在标记时视为根对象，不受clear_roots影响
+    pins: HashMap<ObjRef, usize>,
+    /// 线程缓冲区持有的对象，按缓冲区编号分组；与固定的对象一样在标记时视为根对象，但不计入固定次数
+    buffer_holds: HashMap<u64, HashSet<ObjRef>>,
+    /// 所有打开的作用域中的临时根对象，按加入顺序排列；注销的对象置空
+    scope_roots: Vec<Option<ObjRef>>,
+    /// 每个打开的作用域在scope_roots中的起始位置
+    scope_starts: Vec<usize>,
+    /// 每个作用域根对象在scope_roots中出现的次数，用于常数时间判断是否为作用域根
+    scope_root_counts: HashMap<ObjRef, usize>,
+    /// 对象引用关系：从一个对象到它引用的所有对象
+    references: HashMap<ObjRef, HashSet<ObjRef>>,
+    /// 反向引用索引：从一个对象到所有引用它的对象，与references保持同步
+    referrers: HashMap<ObjRef, HashSet<ObjRef>>,
+    /// 以add_owner添加的强引用(owner, child)，是references的子集，只用于导出时区分引用种类
+    owner_edges: HashSet<(ObjRef, ObjRef)>,
+    /// 弱引用关系：从一个对象到它弱引用的所有对象，不参与标记
+    weak_references: HashMap<ObjRef, HashSet<ObjRef>>,
+    /// 弱引用的反向索引，与weak_references保持同步
+    weak_referrers: HashMap<ObjRef, HashSet<ObjRef>>,
+    /// 目标已失效、等待通知的弱引用：(引用方, 目标)
+    cleared_weak: Vec<(ObjRef, ObjRef)>,
+    /// 弱引用被清除时的回调
+    weak_clear_callback: Option<WeakClearCallback>,
+    /// 传给弱引用清除回调的用户数据
+    weak_clear_user_data: *mut c_void,
+    /// 最近注销的对象，默认容量为0即关闭检测
+    recent_unregisters: RecentUnregisters,
+    /// 使用最近注销对象的次数
+    recently_unregistered_hits: usize,
+    /// 使用最近注销对象时的回调
+    recently_unregistered_callback: Option<SlimeGcRecentlyUnregisteredCallback>,
+    /// 传给回调的用户数据
+    recently_unregistered_user_data: *mut c_void,
+    /// 地址失效通知的订阅者：(订阅ID, 回调, 用户数据)
+    invalidation_subscribers: Vec<(u64, SlimeGcInvalidationCallback, *mut c_void)>,
+    /// 下一个订阅ID
+    next_subscription_id: u64,
+    /// 注册对象数量上限，0表示不限制
+    max_objects: usize,
+    /// 已预留但尚未使用的对象数量
+    reserved_objects: usize,
+    /// 对每个被回收对象调用的释放回调
+    free_callback: Option<SlimeGcFreeCallback>,
+    /// 传给释放回调的用户数据
+    free_user_data: *mut c_void,
+    /// collect_into已回收但尚未取走的对象
+    pending_garbage: Vec<ObjRef>,
+    /// 等待派发的回调，按产生顺序排列
+    pending_callbacks: Vec<PendingCall>,
+    /// 对象的类型标签，标签为0的对象不记录
+    object_tags: HashMap<ObjRef, u32>,
+    /// 一致性检查发现问题时是否自动修复
+    auto_repair: bool,
+    /// 回收时报告被清除对象的标签顺序：标签到其在顺序中的位置
+    finalization_order: HashMap<u32, usize>,
+    /// 进行中的增量回收，没有时为None
+    incremental: Option<IncrementalCycle>,
+    /// 上次回收清除的对象数量
+    last_collected: usize,
+    /// 上次回收标记阶段的耗时
+    last_mark_duration: Duration,
+    /// 上次回收清除阶段的耗时
+    last_sweep_duration: Duration,
+    /// 从根对象出发的完整标记遍历次数，只读的查询也会遍历，因此用原子计数
+    mark_passes: AtomicUsize,
+    /// 按类型标签设置的处置队列
+    disposal_queues: HashMap<u32, DisposalQueue>,
+    /// 已放入处置队列、等待宿主取出的对象，它们保持注册，引用的对象在回收时一起保留
+    condemned: HashSet<ObjRef>,
+    /// 上次回收放入处置队列的对象数量
+    last_queued_for_disposal: usize,
+    /// 上次回收因处置队列已满而推迟的对象数量
+    last_deferred_by_backpressure: usize,
+    /// 按类型标签设置的回收复用回调及其用户数据
+    recyclers: HashMap<u32, (SlimeGcRecyclerCallback, *mut c_void)>,
+    /// 本次清除的对象中设置了回收复用回调的类型的对象及其标签，清理标签前记下，由queue_free_callbacks取走
+    recycle_offers: HashMap<ObjRef, u32>,
+}
+
+impl Default for GarbageCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GarbageCollector {
+    /// 创建新的垃圾回收器
+    pub fn new() -> Self {
+        GarbageCollector {
+            objects: HashSet::new(),
+            object_sizes: HashMap::new(),
+            external_bytes: HashMap::new(),
+            live_bytes: 0,
+            default_object_size: 0,
+            initial_threshold: DEFAULT_COLLECTION_THRESHOLD,
+            collection_threshold: DEFAULT_COLLECTION_THRESHOLD,
+            roots: HashSet::new(),
+            pins: HashMap::new(),
+            buffer_holds: HashMap::new(),
+            scope_roots: Vec::new(),
+            scope_starts: Vec::new(),
+            scope_root_counts: HashMap::new(),
+            references: HashMap::new(),
+            referrers: HashMap::new(),
+            owner_edges: HashSet::new(),
+            weak_references: HashMap::new(),
+            weak_referrers: HashMap::new(),
+            cleared_weak: Vec::new(),
+            weak_clear_callback: None,
+            weak_clear_user_data: std::ptr::null_mut(),
+            recent_unregisters: RecentUnregisters::new(0),
+            recently_unregistered_hits: 0,
+            recently_unregistered_callback: None,
+            recently_unregistered_user_data: std::ptr::null_mut(),
+            invalidation_subscribers: Vec::new(),
+            next_subscription_id: 1,
+            max_objects: 0,
+            reserved_objects: 0,
+            free_callback: None,
+            free_user_data: std::ptr::null_mut(),
+            pending_garbage: Vec::new(),
+            pending_callbacks: Vec::new(),
+            object_tags: HashMap::new(),
+            auto_repair: false,
+            finalization_order: HashMap::new(),
+            incremental: None,
+            last_collected: 0,
+            last_mark_duration: Duration::ZERO,
+            last_sweep_duration: Duration::ZERO,
+            mark_passes: AtomicUsize::new(0),
+            disposal_queues: HashMap::new(),
+            condemned: HashSet::new(),
+            last_queued_for_disposal: 0,
+            last_deferred_by_backpressure: 0,
+            recyclers: HashMap::new(),
+            recycle_offers: HashMap::new(),
+        }
+    }
+
+    /// 按配置创建新的垃圾回收器
+    pub fn with_config(config: &SlimeGcConfig) -> Self {
+        let mut gc = Self::new();
+        gc.max_objects = config.max_objects;
+        gc.default_object_size = config.default_object_size;
+        gc.set_collection_threshold(config.collection_threshold);
+        gc.recent_unregisters = RecentUnregisters::new(config.recent_unregister_window);
+        gc
+    }
+
+    /// 按新配置重建回收器内部状态，已注册的对象原样保留
+    ///
+    /// 迁移对象及其大小、外部内存和类型标签、强弱引用、根对象、固定、作用域根、线程缓冲区的持有、预留空位、
+    /// 处置队列及其中的对象，以及已回收但尚未取走或派发的对象和回调；不会清除或释放任何对象。
+    /// 以下状态不迁移：释放、弱引用清除、最近注销、回收复用回调和地址失效订阅都需要重新设置，自动修复恢复为关闭，
+    /// 统计计数（上次回收的数量和耗时、使用最近注销对象的次数）和最近注销记录清零，
+    /// 标签的回收报告顺序恢复默认，进行中的增量回收被放弃，下一次回收从头开始。
+    /// 订阅ID继续递增，旧的订阅ID不会分配给新的订阅。
+    /// 新的数量上限容纳不下现有对象和预留空位时返回SLIME_GC_OBJECT_LIMIT，回收器保持不变
+    pub fn swap_in(&mut self, config: &SlimeGcConfig) -> c_int {
+        if config.max_objects != 0 && self.objects.len() + self.reserved_objects > config.max_objects {
+            return SLIME_GC_OBJECT_LIMIT;
+        }
+
+        let mut fresh = Self::with_config(config);
+        fresh.objects = std::mem::take(&mut self.objects);
+        fresh.object_sizes = std::mem::take(&mut self.object_sizes);
+        fresh.external_bytes = std::mem::take(&mut self.external_bytes);
+        fresh.live_bytes = self.live_bytes;
+        fresh.object_tags = std::mem::take(&mut self.object_tags);
+        fresh.roots = std::mem::take(&mut self.roots);
+        fresh.pins = std::mem::take(&mut self.pins);
+        fresh.buffer_holds = std::mem::take(&mut self.buffer_holds);
+        fresh.scope_roots = std::mem::take(&mut self.scope_roots);
+        fresh.scope_starts = std::mem::take(&mut self.scope_starts);
+        fresh.scope_root_counts = std::mem::take(&mut self.scope_root_counts);
+        fresh.references = std::mem::take(&mut self.references);
+        fresh.referrers = std::mem::take(&mut self.referrers);
+        fresh.owner_edges = std::mem::take(&mut self.owner_edges);
+        fresh.weak_references = std::mem::take(&mut self.weak_references);
+        fresh.weak_referrers = std::mem::take(&mut self.weak_referrers);
+        fresh.cleared_weak = std::mem::take(&mut self.cleared_weak);
+        fresh.disposal_queues = std::mem::take(&mut self.disposal_queues);
+        fresh.condemned = std::mem::take(&mut self.condemned);
+        fresh.reserved_objects = self.reserved_objects;
+        fresh.pending_garbage = std::mem::take(&mut self.pending_garbage);
+        fresh.pending_callbacks = std::mem::take(&mut self.pending_callbacks);
+        fresh.next_subscription_id = self.next_subscription_id;
+        // 阈值不低于迁移后存活字节数的2倍，与回收后的调整一致
+        fresh.set_collection_threshold(config.collection_threshold);
+
+        *self = fresh;
+        SLIME_GC_OK
+    }
+
+    /// 注册新对象，大小按默认对象大小计入
+    ///
+    /// 达到对象数量上限时先尝试一次紧急回收，仍无空位则返回SLIME_GC_OBJECT_LIMIT
+    pub fn register_object(&mut self, obj: impl IntoObjRef) -> c_int {
+        self.register_object_sized(obj, self.default_object_size)
+    }
+
+    /// 注册指定大小（字节）的新对象，重复注册时以新的大小为准，标签重置为0
+    pub fn register_object_sized(&mut self, obj: impl IntoObjRef, size_bytes: usize) -> c_int {
+        self.register_object_with(obj, size_bytes, 0)
+    }
+
+    /// 注册带类型标签的新对象，重复注册时以新的标签为准，大小重置为默认大小
+    pub fn register_object_tagged(&mut self, obj: impl IntoObjRef, tag: u32) -> c_int {
+        self.register_object_with(obj, self.default_object_size, tag)
+    }
+
+    /// 注册指定大小和类型标签的新对象，重复注册时大小和标签都以新的值为准
+    ///
+    /// 其他注册函数都经由此处，未指定标签的对象标签为0
+    pub fn register_object_with(&mut self, obj: impl IntoObjRef, size_bytes: usize, tag: u32) -> c_int {
+        let Some(obj) = obj.into_obj_ref() else {
+            return SLIME_GC_OK;
+        };
+
+        if !self.objects.contains(&obj) {
+            if self.reserved_objects > 0 {
+                // 优先消耗预留的空位
+                self.reserved_objects -= 1;
+            } else if !self.ensure_headroom(1) {
+                return SLIME_GC_OBJECT_LIMIT;
+            }
+        }
+
+        self.recent_unregisters.forget(obj);
+        self.objects.insert(obj);
+        // 重复注册会清空对象原有的引用
+        self.clear_references(obj);
+        self.references.insert(obj, HashSet::new());
+        self.set_object_size(obj, size_bytes);
+        self.set_object_tag(obj, tag);
+        // 增量回收进行中注册的对象本轮视为存活
+        self.shade(obj);
+        SLIME_GC_OK
+    }
+
+    /// 更新对象的类型标签记录
+    fn set_object_tag(&mut self, obj: ObjRef, tag: u32) {
+        if tag != 0 {
+            self.object_tags.insert(obj, tag);
+        } else {
+            self.object_tags.remove(&obj);
+        }
+    }
+
+    /// 获取已注册对象的类型标签，未注册的对象返回None
+    pub fn get_type_tag(&self, obj: impl IntoObjRef) -> Option<u32> {
+        let obj = obj.into_obj_ref().filter(|obj| self.objects.contains(obj))?;
+        Some(self.object_tags.get(&obj).copied().unwrap_or(0))
+    }
+
+    /// 统计带有指定类型标签的已注册对象数量
+    pub fn count_objects_by_tag(&self, tag: u32) -> usize {
+        if tag == 0 {
+            self.objects.len() - self.object_tags.len()
+        } else {
+            self.object_tags.values().filter(|&&t| t == tag).count()
+        }
+    }
+
+    /// 查找所有直接引用指定类型对象的已注册对象，返回按地址排序的(引用方, 被引用对象)
+    ///
+    /// 只遍历该类型对象的反向索引，不扫描全部引用
+    pub fn referrers_of_type(&self, tag: u32) -> Vec<(ObjRef, ObjRef)> {
+        let targets: Vec<_> = if tag == 0 {
+            self.objects.iter().copied().filter(|obj| !self.object_tags.contains_key(obj)).collect()
+        } else {
+            self.object_tags.iter().filter(|&(_, &t)| t == tag).map(|(&obj, _)| obj).collect()
+        };
+
+        let mut pairs: Vec<_> = targets.into_iter()
+            .flat_map(|to| self.referrers.get(&to).into_iter().flatten().map(move |&from| (from, to)))
+            .filter(|(from, _)| self.objects.contains(from))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    /// 设置回收时报告被清除对象的顺序
+    ///
+    /// 释放回调、collect_into和失效通知都按标签在tags_in_order中的顺序分组报告，
+    /// 不在其中的标签（包括未指定标签的0）排在最后；同一标签内的顺序不确定
+    pub fn set_finalization_order(&mut self, tags_in_order: &[u32]) {
+        self.finalization_order.clear();
+        for (rank, &tag) in tags_in_order.iter().enumerate() {
+            // 重复出现的标签以第一次出现的位置为准
+            self.finalization_order.entry(tag).or_insert(rank);
+        }
+    }
+
+    /// 按终结顺序排列即将清除的对象，必须在标签记录被清理之前调用
+    fn sort_for_finalization(&self, objs: &mut [ObjRef]) {
+        if self.finalization_order.is_empty() {
+            return;
+        }
+        objs.sort_by_key(|obj| {
+            let tag = self.object_tags.get(obj).copied().unwrap_or(0);
+            self.finalization_order.get(&tag).copied().unwrap_or(usize::MAX)
+        });
+    }
+
+    /// 更新对象大小记录和总字节数
+    fn set_object_size(&mut self, obj: ObjRef, size_bytes: usize) {
+        let old_size = if size_bytes > 0 {
+            self.object_sizes.insert(obj, size_bytes)
+        } else {
+            self.object_sizes.remove(&obj)
+        };
+        self.live_bytes = self.live_bytes - old_size.unwrap_or(0) + size_bytes;
+    }
+
+    /// 设置未指定大小的对象按多少字节计入
+    pub fn set_default_object_size(&mut self, size_bytes: usize) {
+        self.default_object_size = size_bytes;
+    }
+
+    /// 获取所有已注册对象的总字节数，包括归属于它们的外部内存
+    pub fn get_live_bytes(&self) -> usize {
+        self.live_bytes
+    }
+
+    /// 把bytes字节外部内存（如对象持有的malloc缓冲区）归属到obj
+    ///
+    /// 外部内存计入存活字节数，参与自动回收阈值的判断；对象被清除或注销时自动扣除。
+    /// 对象未注册时返回SLIME_GC_NOT_REGISTERED
+    pub fn add_external_bytes(&mut self, obj: impl IntoObjRef, bytes: usize) -> c_int {
+        let Some(obj) = obj.into_obj_ref().filter(|obj| self.objects.contains(obj)) else {
+            return SLIME_GC_NOT_REGISTERED;
+        };
+        if bytes > 0 {
+            *self.external_bytes.entry(obj).or_insert(0) += bytes;
+            self.live_bytes += bytes;
+        }
+        SLIME_GC_OK
+    }
+
+    /// 从obj归属的外部内存中扣除bytes字节，最多扣到0
+    pub fn remove_external_bytes(&mut self, obj: impl IntoObjRef, bytes: usize) {
+        let Some(obj) = obj.into_obj_ref() else {
+            return;
+        };
+        if let Some(external) = self.external_bytes.get_mut(&obj) {
+            let removed = bytes.min(*external);
+            *external -= removed;
+            self.live_bytes -= removed;
+            if *external == 0 {
+                self.external_bytes.remove(&obj);
+            }
+        }
+    }
+
+    /// 获取归属于obj的外部内存字节数，未注册的对象为0
+    pub fn get_external_bytes(&self, obj: impl IntoObjRef) -> usize {
+        obj.into_obj_ref().and_then(|obj| self.external_bytes.get(&obj)).copied().unwrap_or(0)
+    }
+
+    /// 设置自动回收阈值（字节）
+    ///
+    /// 每次回收后阈值调整为max(该值, 存活字节数的2倍)，避免大而稳定的堆频繁回收
+    pub fn set_collection_threshold(&mut self, bytes: usize) {
+        self.initial_threshold = bytes;
+        self.collection_threshold = bytes.max(self.live_bytes.saturating_mul(2));
+    }
+
+    /// 获取当前的自动回收阈值
+    pub fn get_collection_threshold(&self) -> usize {
+        self.collection_threshold
+    }
+
+    /// 存活字节数超过阈值时执行回收，返回回收的对象数量，否则什么也不做并返回0
+    pub fn maybe_collect(&mut self) -> usize {
+        if self.live_bytes > self.collection_threshold {
+            self.collect_garbage()
+        } else {
+            0
+        }
+    }
+
+    /// 设置注册对象数量上限，0表示不限制
+    pub fn set_max_objects(&mut self, max: usize) {
+        self.max_objects = max;
+    }
+
+    /// 获取注册对象数量上限
+    pub fn get_max_objects(&self) -> usize {
+        self.max_objects
+    }
+
+    /// 获取当前注册对象数量
+    pub fn get_object_count(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// 为接下来的n次注册预留空位，保证这些注册不会因数量上限失败
+    pub fn reserve_objects(&mut self, n: usize) -> c_int {
+        if !self.ensure_headroom(n) {
+            return SLIME_GC_OBJECT_LIMIT;
+        }
+        self.reserved_objects += n;
+        SLIME_GC_OK
+    }
+
+    /// 释放最多n个尚未使用的预留空位
+    pub fn release_reservation(&mut self, n: usize) {
+        self.reserved_objects = self.reserved_objects.saturating_sub(n);
+    }
+
+    /// 获取已预留但尚未使用的对象数量
+    pub fn get_reserved_objects(&self) -> usize {
+        self.reserved_objects
+    }
+
+    /// 检查是否还有n个未预留的空位，不足时执行一次紧急回收后再检查
+    fn ensure_headroom(&mut self, n: usize) -> bool {
+        if self.max_objects == 0 {
+            return true;
+        }
+
+        let fits = |gc: &Self| gc.objects.len() + gc.reserved_objects + n <= gc.max_objects;
+        if fits(self) {
+            return true;
+        }
+        self.collect_garbage();
+        fits(self)
+    }
+
+    /// 注销对象
+    pub fn unregister_object(&mut self, obj: impl IntoObjRef) {
+        if let Some(obj) = obj.into_obj_ref() {
+            let was_registered = self.objects.contains(&obj);
+            self.teardown_object(obj);
+            // 只记录真正注销的对象，重复注销或注销未注册的地址不会挤掉窗口中的记录
+            if was_registered {
+                self.recent_unregisters.record(obj);
+                self.notify_invalidation(&[obj], SLIME_GC_INVALIDATE_UNREGISTERED);
+            }
+            self.notify_weak_cleared(SLIME_GC_WEAK_CLEARED_REMOVED);
+        }
+    }
+
+    /// 订阅地址失效通知，返回订阅ID
+    pub fn subscribe_invalidation(&mut self, callback: SlimeGcInvalidationCallback, user_data: *mut c_void) -> u64 {
+        let id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        self.invalidation_subscribers.push((id, callback, user_data));
+        id
+    }
+
+    /// 取消地址失效通知订阅
+    ///
+    /// 在回调中取消订阅时，正在派发的这一批通知仍会送达，之后不再通知
+    pub fn unsubscribe_invalidation(&mut self, id: u64) {
+        self.invalidation_subscribers.retain(|&(sub_id, _, _)| sub_id != id);
+    }
+
+    /// 向所有订阅者批量派发地址失效通知，须在内部状态一致后调用
+    fn notify_invalidation(&mut self, addrs: &[ObjRef], reason: c_int) {
+        if addrs.is_empty() || self.invalidation_subscribers.is_empty() {
+            return;
+        }
+
+        // 先复制订阅者列表，回调中修改订阅不影响本次派发
+        let subscribers = self.invalidation_subscribers.iter()
+            .map(|&(_, callback, user_data)| (callback, user_data))
+            .collect();
+        self.pending_callbacks.push(PendingCall::Invalidation {
+            subscribers,
+            addrs: addrs.to_vec(),
+            reason,
+        });
+    }
+
+    /// 取出所有待派发的回调
+    pub fn take_pending_callbacks(&mut self) -> PendingCallbacks {
+        PendingCallbacks {
+            calls: std::mem::take(&mut self.pending_callbacks),
+        }
+    }
+
+    /// 设置最近注销检测的窗口大小（记录最近多少次注销），0表示关闭；注销未注册的地址不计入
+    pub fn set_recent_unregister_window(&mut self, window: usize) {
+        self.recent_unregisters = RecentUnregisters::new(window);
+    }
+
+    /// 设置使用最近注销对象时的回调
+    pub fn set_recently_unregistered_callback(&mut self, callback: Option<SlimeGcRecentlyUnregisteredCallback>, user_data: *mut c_void) {
+        self.recently_unregistered_callback = callback;
+        self.recently_unregistered_user_data = user_data;
+    }
+
+    /// 获取使用最近注销对象的次数
+    pub fn get_recently_unregistered_count(&self) -> usize {
+        self.recently_unregistered_hits
+    }
+
+    /// 检查from或to是否刚被注销，命中时计数并调用回调
+    fn check_recently_unregistered(&mut self, from: Option<ObjRef>, to: Option<ObjRef>) {
+        let age = match self.recent_unregisters.age(from).or_else(|| self.recent_unregisters.age(to)) {
+            Some(age) => age,
+            None => return,
+        };
+
+        self.recently_unregistered_hits += 1;
+        if let Some(callback) = self.recently_unregistered_callback {
+            self.pending_callbacks.push(PendingCall::RecentlyUnregistered {
+                callback,
+                user_data: self.recently_unregistered_user_data,
+                from,
+                to,
+                age,
+            });
+        }
+    }
+
+    /// 清理对象的全部追踪状态，注销和回收共用此流程
+    ///
+    /// 清理顺序：根标记（含作用域根和固定次数）、对象自身的强/弱引用集合、对象大小和标签、
+    /// 其他对象指向它的强引用、其他对象指向它的弱引用（记入待通知列表）、对象登记
+    ///
+    /// 借助反向索引，只会访问真正引用了该对象的对象
+    fn teardown_object(&mut self, obj: ObjRef) {
+        // 处置队列中的对象被注销时同时移出队列
+        if self.condemned.remove(&obj) {
+            for queue in self.disposal_queues.values_mut() {
+                queue.objs.retain(|&queued| queued != obj);
+            }
+        }
+        self.roots.remove(&obj);
+        self.pins.remove(&obj);
+        if !self.buffer_holds.is_empty() {
+            for held in self.buffer_holds.values_mut() {
+                held.remove(&obj);
+            }
+            self.buffer_holds.retain(|_, held| !held.is_empty());
+        }
+        // 置空而不是删除，保持各作用域的起始位置不变；绝大多数对象不是作用域根，无需扫描
+        if self.scope_root_counts.remove(&obj).is_some() {
+            for root in self.scope_roots.iter_mut().filter(|root| **root == Some(obj)) {
+                *root = None;
+            }
+        }
+        self.clear_references(obj);
+        if let Some(weak_refs) = self.weak_references.remove(&obj) {
+            for to in weak_refs {
+                index_remove(&mut self.weak_referrers, to, obj);
+            }
+        }
+        self.set_object_size(obj, 0);
+        self.live_bytes -= self.external_bytes.remove(&obj).unwrap_or(0);
+        self.object_tags.remove(&obj);
+
+        // 从其他对象的引用列表中移除该对象
+        for from in self.referrers.remove(&obj).unwrap_or_default() {
+            if let Some(refs) = self.references.get_mut(&from) {
+                refs.remove(&obj);
+            }
+            self.owner_edges.remove(&(from, obj));
+        }
+
+        // 清除指向该对象的弱引用，等内部状态一致后再通知
+        for from in self.weak_referrers.remove(&obj).unwrap_or_default() {
+            if let Some(weak_refs) = self.weak_references.get_mut(&from) {
+                weak_refs.remove(&obj);
+                if weak_refs.is_empty() {
+                    self.weak_references.remove(&from);
+                }
+            }
+            self.cleared_weak.push((from, obj));
+        }
+
+        self.objects.remove(&obj);
+    }
+
+    /// 添加弱引用，弱引用不会让目标对象存活
+    pub fn add_weak_reference(&mut self, from: impl IntoObjRef, to: impl IntoObjRef) {
+        let (from, to) = (from.into_obj_ref(), to.into_obj_ref());
+        self.check_recently_unregistered(from, to);
+        if let (Some(from), Some(to)) = (from, to)
+            && self.objects.contains(&from)
+        {
+            self.weak_references.entry(from).or_default().insert(to);
+            index_insert(&mut self.weak_referrers, to, from);
+        }
+    }
+
+    /// 移除弱引用
+    pub fn remove_weak_reference(&mut self, from: impl IntoObjRef, to: impl IntoObjRef) {
+        if let (Some(from), Some(to)) = (from.into_obj_ref(), to.into_obj_ref())
+            && let Some(weak_refs) = self.weak_references.get_mut(&from)
+        {
+            weak_refs.remove(&to);
+            if weak_refs.is_empty() {
+                self.weak_references.remove(&from);
+            }
+            index_remove(&mut self.weak_referrers, to, from);
+        }
+    }
+
+    /// 获取对象的弱引用集合
+    pub fn get_weak_references(&self, obj: impl IntoObjRef) -> Option<&HashSet<ObjRef>> {
+        self.weak_references.get(&obj.into_obj_ref()?)
+    }
+
+    /// 设置弱引用被清除时的回调
+    ///
+    /// 目标被回收或注销时调用，引用方本身已不存在的弱引用不会通知；
+    /// 替换之前设置的回调，包括带原因的回调
+    pub fn set_weak_clear_callback(&mut self, callback: Option<SlimeGcWeakClearCallback>, user_data: *mut c_void) {
+        self.weak_clear_callback = callback.map(WeakClearCallback::Plain);
+        self.weak_clear_user_data = user_data;
+    }
+
+    /// 设置带原因的弱引用清除回调
+    ///
+    /// 目标被回收时原因为SLIME_GC_WEAK_CLEARED_COLLECTED，被注销或重定向后注销时为SLIME_GC_WEAK_CLEARED_REMOVED；
+    /// 替换之前设置的回调，包括不带原因的回调
+    pub fn set_weak_clear_reason_callback(&mut self, callback: Option<SlimeGcWeakClearReasonCallback>, user_data: *mut c_void) {
+        self.weak_clear_callback = callback.map(WeakClearCallback::WithReason);
+        self.weak_clear_user_data = user_data;
+    }
+
+    /// 检查对象是否仍被回收器追踪（未被回收或注销）
+    pub fn is_alive(&self, obj: impl IntoObjRef) -> bool {
+        obj.into_obj_ref().is_some_and(|obj| self.objects.contains(&obj))
+    }
+
+    /// 为所有待通知的弱引用清除记入回调，须在内部状态一致后调用
+    fn notify_weak_cleared(&mut self, reason: c_int) {
+        let cleared = std::mem::take(&mut self.cleared_weak);
+        let Some(callback) = self.weak_clear_callback else {
+            return;
+        };
+
+        // 引用方在同一次回收中被清除时不再通知
+        let pairs: Vec<_> = cleared.into_iter()
+            .filter(|(from, _)| self.objects.contains(from))
+            .collect();
+        if !pairs.is_empty() {
+            self.pending_callbacks.push(PendingCall::WeakClear {
+                callback,
+                user_data: self.weak_clear_user_data,
+                pairs,
+                reason,
+            });
+        }
+    }
+
+    /// 添加对象引用
+    pub fn add_reference(&mut self, from: impl IntoObjRef, to: impl IntoObjRef) {
+        let (from, to) = (from.into_obj_ref(), to.into_obj_ref());
+        self.check_recently_unregistered(from, to);
+        // 确保from对象已注册
+        if let (Some(from), Some(to)) = (from, to)
+            && self.objects.contains(&from)
+        {
+            // 获取或创建from对象的引用集合
+            let refs = self.references.entry(from).or_default();
+            // 添加引用
+            refs.insert(to);
+            index_insert(&mut self.referrers, to, from);
+            self.shade(to);
+        }
+    }
+
+    /// 移除对象引用
+    pub fn remove_reference(&mut self, from: impl IntoObjRef, to: impl IntoObjRef) {
+        if let (Some(from), Some(to)) = (from.into_obj_ref(), to.into_obj_ref())
+            && let Some(refs) = self.references.get_mut(&from)
+        {
+            refs.remove(&to);
+            index_remove(&mut self.referrers, to, from);
+            self.owner_edges.remove(&(from, to));
+        }
+    }
+
+    /// 移除对象的所有引用
+    pub fn clear_references(&mut self, obj: impl IntoObjRef) {
+        if let Some(obj) = obj.into_obj_ref()
+            && let Some(refs) = self.references.remove(&obj)
+        {
+            for to in refs {
+                index_remove(&mut self.referrers, to, obj);
+                self.owner_edges.remove(&(obj, to));
+            }
+        }
+    }
+
+    /// 获取对象的引用集合
+    pub fn get_references(&self, obj: impl IntoObjRef) -> Option<&HashSet<ObjRef>> {
+        self.references.get(&obj.into_obj_ref()?)
+    }
+
+    /// 获取引用了该对象的所有对象
+    pub fn get_referrers(&self, obj: impl IntoObjRef) -> Option<&HashSet<ObjRef>> {
+        self.referrers.get(&obj.into_obj_ref()?)
+    }
+
+    /// 批量添加引用
+    pub fn add_references(&mut self, from: impl IntoObjRef, to_list: &[impl IntoObjRef]) {
+        let from = from.into_obj_ref();
+        let to_list: Vec<_> = to_list.iter().map(|to| to.into_obj_ref()).collect();
+        for &to in &to_list {
+            self.check_recently_unregistered(from, to);
+        }
+        if let Some(from) = from
+            && !to_list.is_empty()
+            && self.objects.contains(&from)
+        {
+            let refs = self.references.entry(from).or_default();
+            for &to in to_list.iter().flatten() {
+                refs.insert(to);
+                index_insert(&mut self.referrers, to, from);
+            }
+            for &to in to_list.iter().flatten() {
+                self.shade(to);
+            }
+        }
+    }
+
+    /// 批量移除引用
+    pub fn remove_references(&mut self, from: impl IntoObjRef, to_list: &[impl IntoObjRef]) {
+        if let Some(from) = from.into_obj_ref()
+            && let Some(refs) = self.references.get_mut(&from)
+        {
+            for to in to_list.iter().filter_map(|to| to.into_obj_ref()) {
+                refs.remove(&to);
+                index_remove(&mut self.referrers, to, from);
+                self.owner_edges.remove(&(from, to));
+            }
+        }
+    }
+
+    /// 以“被拥有”的方式添加引用：记录为owner引用child
+    ///
+    /// 与add_reference一样是强引用，只在导出时标为owner；引用被移除后owner记录随之消失
+    pub fn add_owner(&mut self, child: impl IntoObjRef, owner: impl IntoObjRef) {
+        let (child, owner) = (child.into_obj_ref(), owner.into_obj_ref());
+        self.add_reference(owner, child);
+        if let (Some(child), Some(owner)) = (child, owner)
+            && self.references.get(&owner).is_some_and(|refs| refs.contains(&child))
+        {
+            self.owner_edges.insert((owner, child));
+        }
+    }
+
+    /// 以“被拥有”的方式移除引用：移除owner对child的引用
+    pub fn remove_owner(&mut self, child: impl IntoObjRef, owner: impl IntoObjRef) {
+        self.remove_reference(owner, child);
+    }
+
+    /// 批量添加child的拥有者
+    pub fn add_owners(&mut self, child: impl IntoObjRef, owner_list: &[impl IntoObjRef]) {
+        for &owner in owner_list {
+            self.add_owner(child, owner);
+        }
+    }
+
+    /// 批量移除child的拥有者
+    pub fn remove_owners(&mut self, child: impl IntoObjRef, owner_list: &[impl IntoObjRef]) {
+        for &owner in owner_list {
+            self.remove_owner(child, owner);
+        }
+    }
+
+    /// 将所有指向from_obj的强引用改为指向to_obj，返回改写的引用数量
+    ///
+    /// to_obj必须已注册；from_obj与to_obj相同时不做任何操作。弱引用默认不随重定向改写，
+    /// 带上SLIME_GC_REDIRECT_WEAK时也改为指向to_obj；否则随SLIME_GC_REDIRECT_UNREGISTER被清除并通知。
+    /// 返回值只计实际移到to_obj上的引用：引用方已经引用to_obj时两条引用合并为一条，不计入。
+    /// 原对象的根标记和固定次数只在带上对应的MIGRATE标志时转移，否则留在原对象上，
+    /// 并随SLIME_GC_REDIRECT_UNREGISTER一并丢弃
+    pub fn redirect(&mut self, from_obj: impl IntoObjRef, to_obj: impl IntoObjRef, flags: c_int) -> usize {
+        let (Some(from_obj), Some(to_obj)) = (from_obj.into_obj_ref(), to_obj.into_obj_ref()) else {
+            return 0;
+        };
+        if from_obj == to_obj {
+            return 0;
+        }
+        if !self.objects.contains(&from_obj) || !self.objects.contains(&to_obj) {
+            return 0;
+        }
+
+        let mut rewritten = 0;
+        for from in self.referrers.remove(&from_obj).unwrap_or_default() {
+            if let Some(refs) = self.references.get_mut(&from)
+                && refs.remove(&from_obj)
+            {
+                rewritten += refs.insert(to_obj) as usize;
+                index_insert(&mut self.referrers, to_obj, from);
+                // owner引用改写后仍是owner引用
+                if self.owner_edges.remove(&(from, from_obj)) {
+                    self.owner_edges.insert((from, to_obj));
+                }
+            }
+        }
+        if flags & SLIME_GC_REDIRECT_WEAK != 0 {
+            for from in self.weak_referrers.remove(&from_obj).unwrap_or_default() {
+                if let Some(weak_refs) = self.weak_references.get_mut(&from)
+                    && weak_refs.remove(&from_obj)
+                {
+                    rewritten += weak_refs.insert(to_obj) as usize;
+                    index_insert(&mut self.weak_referrers, to_obj, from);
+                }
+            }
+        }
+
+        if flags & SLIME_GC_REDIRECT_MIGRATE_ROOT != 0 && self.roots.remove(&from_obj) {
+            self.roots.insert(to_obj);
+        }
+        if flags & SLIME_GC_REDIRECT_MIGRATE_PIN != 0
+            && let Some(count) = self.pins.remove(&from_obj)
+        {
+            *self.pins.entry(to_obj).or_default() += count;
+        }
+        // 增量回收进行中时，改写后的引用目标本轮视为存活
+        self.shade(to_obj);
+
+        if flags & SLIME_GC_REDIRECT_UNREGISTER != 0 {
+            self.teardown_object(from_obj);
+            self.recent_unregisters.record(from_obj);
+            self.notify_invalidation(&[from_obj], SLIME_GC_INVALIDATE_REDIRECTED);
+            self.notify_weak_cleared(SLIME_GC_WEAK_CLEARED_REMOVED);
+        }
+
+        rewritten
+    }
+
+    /// 将对象标记为根对象
+    pub fn mark_root(&mut self, obj: impl IntoObjRef) {
+        let obj = obj.into_obj_ref();
+        self.check_recently_unregistered(obj, None);
+        if let Some(obj) = obj
+            && self.objects.contains(&obj)
+        {
+            self.roots.insert(obj);
+            self.shade(obj);
+        }
+    }
+
+    /// 将对象标记为非根对象
+    pub fn unmark_root(&mut self, obj: impl IntoObjRef) {
+        if let Some(obj) = obj.into_obj_ref() {
+            self.roots.remove(&obj);
+        }
+    }
+
+    /// 批量添加根对象
+    pub fn add_roots(&mut self, roots: &[impl IntoObjRef]) {
+        for &obj in roots {
+            self.mark_root(obj);
+        }
+    }
+
+    /// 批量移除根对象
+    pub fn remove_roots(&mut self, roots: &[impl IntoObjRef]) {
+        for &obj in roots {
+            self.unmark_root(obj);
+        }
+    }
+
+    /// 清除所有根对象标记，不影响固定的对象
+    pub fn clear_roots(&mut self) {
+        self.roots.clear();
+    }
+
+    /// 固定对象，每次固定使固定次数加1
+    ///
+    /// 固定次数大于0的对象在标记时视为根对象，不受clear_roots影响，
+    /// 适合原生扩展在回调解释器期间持有的对象。未注册的对象不能固定
+    pub fn pin(&mut self, obj: impl IntoObjRef) -> c_int {
+        let Some(obj) = obj.into_obj_ref().filter(|obj| self.objects.contains(obj)) else {
+            return SLIME_GC_NOT_REGISTERED;
+        };
+        *self.pins.entry(obj).or_default() += 1;
+        self.shade(obj);
+        SLIME_GC_OK
+    }
+
+    /// 取消一次固定，固定次数最少减到0
+    pub fn unpin(&mut self, obj: impl IntoObjRef) {
+        if let Some(obj) = obj.into_obj_ref()
+            && let Some(count) = self.pins.get_mut(&obj)
+        {
+            *count -= 1;
+            if *count == 0 {
+                self.pins.remove(&obj);
+            }
+        }
+    }
+
+    /// 检查对象是否被固定
+    pub fn is_pinned(&self, obj: impl IntoObjRef) -> bool {
+        self.pin_count(obj) > 0
+    }
+
+    /// 获取对象的固定次数
+    pub fn pin_count(&self, obj: impl IntoObjRef) -> usize {
+        obj.into_obj_ref().and_then(|obj| self.pins.get(&obj)).copied().unwrap_or(0)
+    }
+
+    /// 打开一个新的根作用域
+    pub fn push_root_scope(&mut self) {
+        self.scope_starts.push(self.scope_roots.len());
+    }
+
+    /// 在最内层作用域中加入临时根对象，作用域关闭时自动移除
+    pub fn add_scoped_root(&mut self, obj: impl IntoObjRef) -> c_int {
+        let obj = obj.into_obj_ref();
+        self.check_recently_unregistered(obj, None);
+        if self.scope_starts.is_empty() {
+            return SLIME_GC_NO_SCOPE;
+        }
+        if let Some(obj) = obj
+            && self.objects.contains(&obj)
+        {
+            self.scope_roots.push(Some(obj));
+            *self.scope_root_counts.entry(obj).or_insert(0) += 1;
+            self.shade(obj);
+        }
+        SLIME_GC_OK
+    }
+
+    /// 关闭最内层作用域，移除其中加入的所有临时根对象
+    pub fn pop_root_scope(&mut self) -> c_int {
+        match self.scope_starts.pop() {
+            Some(start) => {
+                for obj in self.scope_roots.drain(start..).flatten() {
+                    if let Some(count) = self.scope_root_counts.get_mut(&obj) {
+                        *count -= 1;
+                        if *count == 0 {
+                            self.scope_root_counts.remove(&obj);
+                        }
+                    }
+                }
+                SLIME_GC_OK
+            }
+            None => SLIME_GC_NO_SCOPE,
+        }
+    }
+
+    /// 获取当前打开的根作用域数量
+    pub fn get_scope_depth(&self) -> usize {
+        self.scope_starts.len()
+    }
+
+    /// 获取当前根对象数量
+    pub fn get_root_count(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// 执行垃圾回收
+    ///
+    /// 设置了释放回调时，为每个被回收的对象记入一次待派发的释放回调
+    pub fn collect_garbage(&mut self) -> usize {
+        let swept = self.sweep_unreachable();
+        self.queue_free_callbacks(&swept);
+        swept.len()
+    }
+
+    /// 执行一步增量回收，每步最多处理budget_objects个对象（至少为1）
+    ///
+    /// 标记和清除都分多步完成，两步之间宿主可以继续修改对象图。为了不清除可达对象，
+    /// 回收进行中新注册的对象、新加入的根对象和新添加的引用目标都视为存活，
+    /// 因此本轮可能少回收一些对象，它们留到下一轮回收。
+    /// 清除阶段每步只检查本轮开始时对象快照中的一段，单步停顿不随堆大小增长；
+    /// 终结顺序只在同一步清除的对象之间保证。清除阶段中重新变为存活的对象同样只放入工作列表，
+    /// 之后每步先扫描工作列表，剩余的预算再用于清除。
+    /// 每步清除的对象在该步内记入释放回调和失效通知；调用collect_garbage会放弃进行中的增量回收
+    pub fn collect_step(&mut self, budget_objects: usize) -> CollectStepResult {
+        self.advance_cycle(budget_objects.max(1), true).0
+    }
+
+    /// 推进增量回收一步，同时返回本步扫描和检查的对象数量；allow_sweep为false时只做标记
+    fn advance_cycle(&mut self, budget: usize, allow_sweep: bool) -> (CollectStepResult, usize, usize) {
+        let mut cycle = match self.incremental.take() {
+            Some(cycle) => cycle,
+            None => self.start_incremental_cycle(),
+        };
+        let phase = cycle.phase;
+
+        let mut swept = Vec::new();
+        let mut scanned = 0;
+        if phase == IncrementalPhase::Mark || !cycle.worklist.is_empty() {
+            let mark_start = Instant::now();
+            while scanned < budget && let Some(obj) = cycle.worklist.pop() {
+                if let Some(refs) = self.references.get(&obj) {
+                    for &ref_obj in refs {
+                        self.mark_one(ref_obj, &mut cycle.marked, &mut cycle.worklist);
+                    }
+                }
+                scanned += 1;
+            }
+
+            if cycle.worklist.is_empty() && cycle.phase == IncrementalPhase::Mark {
+                cycle.phase = IncrementalPhase::Sweep;
+                self.route_cycle_to_disposal(&mut cycle);
+            }
+            cycle.mark_duration += mark_start.elapsed();
+        }
+
+        // 清除阶段中重新标记的对象要先扫描完它们的引用，剩余的预算才用于清除；
+        // 标记阶段的一步只做标记
+        let mut checked = 0;
+        if allow_sweep && phase == IncrementalPhase::Sweep && cycle.worklist.is_empty() && scanned < budget {
+            // 每步只检查快照中的一段，停顿时间不随堆大小增长；
+            // 本轮开始后注册的对象不在快照中，它们本轮视为存活
+            let sweep_start = Instant::now();
+            let end = (cycle.next_sweep + budget - scanned).min(cycle.snapshot.len());
+            // 已被注销的对象和清除阶段中重新变为存活的对象都跳过
+            swept.extend(cycle.snapshot[cycle.next_sweep..end]
+                .iter()
+                .copied()
+                .filter(|obj| self.objects.contains(obj) && !cycle.marked.contains(obj)));
+            // 终结顺序只在本步清除的对象之间保证
+            self.sort_for_finalization(&mut swept);
+            self.note_recycle_offers(&swept);
+            for &obj in &swept {
+                self.teardown_object(obj);
+            }
+            checked = end - cycle.next_sweep;
+            cycle.next_sweep = end;
+            cycle.freed += swept.len();
+            cycle.sweep_duration += sweep_start.elapsed();
+        }
+
+        let result = CollectStepResult {
+            finished: cycle.phase == IncrementalPhase::Sweep && cycle.next_sweep == cycle.snapshot.len(),
+            freed: cycle.freed,
+        };
+        if result.finished {
+            self.last_collected = cycle.freed;
+            self.last_queued_for_disposal = cycle.queued;
+            self.last_deferred_by_backpressure = cycle.deferred;
+            self.last_mark_duration = cycle.mark_duration;
+            self.last_sweep_duration = cycle.sweep_duration;
+            self.collection_threshold = self.initial_threshold.max(self.live_bytes.saturating_mul(2));
+        } else {
+            self.incremental = Some(cycle);
+        }
+
+        self.notify_invalidation(&swept, SLIME_GC_INVALIDATE_SWEPT);
+        self.notify_weak_cleared(SLIME_GC_WEAK_CLEARED_COLLECTED);
+        self.queue_free_callbacks(&swept);
+
+        (result, scanned, checked)
+    }
+
+    /// 获取当前有待推进的延迟工作类别，没有时返回0
+    ///
+    /// 存活字节数超过自动回收阈值时，即使还没有开始增量回收也报告标记工作
+    pub fn pending_work(&self) -> u32 {
+        let mut work = match &self.incremental {
+            Some(cycle) if cycle.phase == IncrementalPhase::Mark || !cycle.worklist.is_empty() => SLIME_GC_WORK_MARK,
+            Some(_) => SLIME_GC_WORK_SWEEP,
+            None if self.live_bytes > self.collection_threshold => SLIME_GC_WORK_MARK,
+            None => 0,
+        };
+        if !self.pending_garbage.is_empty() {
+            work |= SLIME_GC_WORK_GARBAGE;
+        }
+        if !self.pending_callbacks.is_empty() {
+            work |= SLIME_GC_WORK_CALLBACKS;
+        }
+        work
+    }
+
+    /// 在时间预算内推进priorities选中的延迟工作，宿主每帧调用一次即可
+    ///
+    /// 按标记、清除、未取走对象的优先级顺序推进：回收到期时开始新一轮增量回收，
+    /// 未取走的对象交给释放回调（没有释放回调时只是丢弃）。
+    /// 每推进PUMP_CHUNK_OBJECTS个对象检查一次预算，因此只要有选中的工作就至少推进一块。
+    /// 回调最后派发，但持有&mut self时不能调用回调：这里只报告剩余批数，
+    /// C接口和线程安全版本在借用释放后用剩余预算派发
+    pub fn pump(&mut self, max_duration: Duration, priorities: u32) -> SlimeGcPumpResult {
+        let start = Instant::now();
+        let mut result = SlimeGcPumpResult::default();
+
+        while let Some(category) = PUMP_ORDER.into_iter().find(|&category| self.pending_work() & priorities & category != 0) {
+            if category == SLIME_GC_WORK_GARBAGE {
+                let count = self.pending_garbage.len().min(PUMP_CHUNK_OBJECTS);
+                let chunk: Vec<_> = self.pending_garbage.drain(..count).collect();
+                self.queue_free_callbacks(&chunk);
+                result.garbage_done += count;
+            } else {
+                let freed_before = self.incremental.as_ref().map_or(0, |cycle| cycle.freed);
+                let (step, scanned, checked) = self.advance_cycle(PUMP_CHUNK_OBJECTS, priorities & SLIME_GC_WORK_SWEEP != 0);
+                result.mark_done += scanned;
+                result.sweep_done += checked;
+                result.freed += step.freed - freed_before;
+            }
+
+            if start.elapsed() >= max_duration {
+                break;
+            }
+        }
+
+        if let Some(cycle) = &self.incremental {
+            result.mark_remaining = cycle.worklist.len();
+            if cycle.phase == IncrementalPhase::Sweep {
+                result.sweep_remaining = cycle.snapshot.len() - cycle.next_sweep;
+            }
+        }
+        result.garbage_remaining = self.pending_garbage.len();
+        result.callbacks_remaining = self.pending_callbacks.len();
+        result
+    }
+
+    /// 把pump未能派发的回调放回待派发列表的开头，保持产生顺序
+    fn requeue_callbacks(&mut self, pending: PendingCallbacks) {
+        self.pending_callbacks.splice(0..0, pending.calls);
+    }
+
+    /// 开始新一轮增量回收，把所有根对象、作用域根对象和固定的对象放入工作列表
+    ///
+    /// 同时把已注册对象的地址复制为快照，供清除阶段分段检查
+    fn start_incremental_cycle(&self) -> IncrementalCycle {
+        let mut cycle = IncrementalCycle {
+            phase: IncrementalPhase::Mark,
+            marked: HashSet::new(),
+            worklist: Vec::new(),
+            snapshot: self.objects.iter().copied().collect(),
+            next_sweep: 0,
+            freed: 0,
+            mark_duration: Duration::ZERO,
+            sweep_duration: Duration::ZERO,
+            queued: 0,
+            deferred: 0,
+        };
+        for root in self.mark_roots().chain(self.condemned.iter().copied()) {
+            self.mark_one(root, &mut cycle.marked, &mut cycle.worklist);
+        }
+        cycle
+    }
+
+    /// 增量回收标记结束时，把快照中设置了处置队列的类型的不可达对象放入队列
+    ///
+    /// 需要检查一遍快照，只在设置了处置队列时进行。放入队列和被推迟的对象重新放入工作列表，
+    /// 它们引用的对象扫描完后才继续清除
+    fn route_cycle_to_disposal(&mut self, cycle: &mut IncrementalCycle) {
+        if self.disposal_queues.is_empty() {
+            return;
+        }
+
+        let mut candidates: Vec<_> = cycle.snapshot
+            .iter()
+            .copied()
+            .filter(|obj| self.objects.contains(obj) && !cycle.marked.contains(obj))
+            .collect();
+        self.sort_for_finalization(&mut candidates);
+        let (kept, queued) = self.route_to_disposal(&candidates);
+        cycle.queued = queued;
+        cycle.deferred = kept.len() - queued;
+        for obj in kept {
+            self.mark_one(obj, &mut cycle.marked, &mut cycle.worklist);
+        }
+    }
+
+    /// 把不可达对象中设置了处置队列的类型的对象按顺序放入队列，队列已满的对象推迟到以后的回收
+    ///
+    /// 返回放入队列和被推迟的对象，以及其中放入队列的数量；它们本次都不清除
+    fn route_to_disposal(&mut self, unreachable: &[ObjRef]) -> (Vec<ObjRef>, usize) {
+        let mut kept = Vec::new();
+        let mut queued = 0;
+        for &obj in unreachable {
+            if let Some(queue) = self.object_tags
+                .get(&obj)
+                .and_then(|tag| self.disposal_queues.get_mut(tag))
+                .filter(|queue| queue.capacity > 0)
+            {
+                if queue.objs.len() < queue.capacity {
+                    queue.objs.push_back(obj);
+                    self.condemned.insert(obj);
+                    queued += 1;
+                }
+                kept.push(obj);
+            }
+        }
+        (kept, queued)
+    }
+
+    /// 增量回收进行中时把对象视为本轮存活，没有进行中的增量回收时不做任何事
+    fn shade(&mut self, obj: ObjRef) {
+        if let Some(mut cycle) = self.incremental.take() {
+            // 清除阶段同样只放入工作列表，下一步先扫描完工作列表再继续清除
+            self.mark_one(obj, &mut cycle.marked, &mut cycle.worklist);
+            self.incremental = Some(cycle);
+        }
+    }
+
+    /// 为被清除的对象记入释放回调，回调和用户数据在此时复制
+    ///
+    /// 设置了回收复用回调的类型的对象按标签分批先交给回收复用回调，其余对象直接交给释放回调
+    fn queue_free_callbacks(&mut self, swept: &[ObjRef]) {
+        let offers = std::mem::take(&mut self.recycle_offers);
+        let mut batches: Vec<(u32, Vec<ObjRef>)> = Vec::new();
+        let mut plain = Vec::new();
+        for &obj in swept {
+            match offers.get(&obj) {
+                Some(&tag) => match batches.iter_mut().find(|(batch_tag, _)| *batch_tag == tag) {
+                    Some((_, objs)) => objs.push(obj),
+                    None => batches.push((tag, vec![obj])),
+                },
+                None => plain.push(obj),
+            }
+        }
+
+        if let Some(callback) = self.free_callback
+            && !plain.is_empty()
+        {
+            self.pending_callbacks.push(PendingCall::Free {
+                callback,
+                user_data: self.free_user_data,
+                objs: plain,
+            });
+        }
+
+        batches.sort_by_key(|&(tag, _)| tag);
+        for (tag, objs) in batches {
+            // 交出的地址与注销的对象一样记入最近注销记录：复用前误用旧地址会被报告，重新注册时记录被清除
+            for &obj in &objs {
+                self.recent_unregisters.record(obj);
+            }
+            let (callback, user_data) = self.recyclers[&tag];
+            self.pending_callbacks.push(PendingCall::Recycle {
+                callback,
+                user_data,
+                objs,
+                free: self.free_callback.map(|free| (free, self.free_user_data)),
+            });
+        }
+    }
+
+    /// 清理标签之前记下被清除的对象中设置了回收复用回调的类型的对象
+    fn note_recycle_offers(&mut self, swept: &[ObjRef]) {
+        if self.recyclers.is_empty() {
+            return;
+        }
+        for &obj in swept {
+            if let Some(&tag) = self.object_tags.get(&obj)
+                && self.recyclers.contains_key(&tag)
+            {
+                self.recycle_offers.insert(obj, tag);
+            }
+        }
+    }
+
+    /// 为类型标签设置回收复用回调，传入None表示移除；标签0表示未设置类型，忽略
+    ///
+    /// 之后回收时，该类型被清除的对象按回收顺序成批交给回调，回调返回从数组开头起接受复用的数量：
+    /// 接受的对象由宿主保留内存，不再调用释放回调；其余对象照常交给释放回调。
+    /// 交给回调时对象已经注销，存活字节数已扣除它的大小；地址同时记入最近注销记录，
+    /// 复用前误用旧地址会被报告，重新注册同一地址时记录被清除，不会产生诊断。
+    /// 与其他回调一样在借用释放后派发；设置了处置队列的类型先进入处置队列，取出时不经过回调，
+    /// collect_into取出的对象也不经过回调
+    pub fn set_recycler(&mut self, tag: u32, callback: Option<SlimeGcRecyclerCallback>, user_data: *mut c_void) {
+        if tag == 0 {
+            return;
+        }
+        match callback {
+            Some(callback) => {
+                self.recyclers.insert(tag, (callback, user_data));
+            }
+            None => {
+                self.recyclers.remove(&tag);
+            }
+        }
+    }
+
+    /// 执行垃圾回收，把被回收的对象写入out，返回写入数量
+    ///
+    /// 上次回收的对象未取完时只继续取出，不会开始新的回收；
+    /// 返回值小于out长度说明已全部取完。这种方式不会调用释放回调
+    pub fn collect_into<T: From<ObjRef>>(&mut self, out: &mut [T]) -> usize {
+        if self.pending_garbage.is_empty() {
+            self.pending_garbage = self.sweep_unreachable();
+            // 取出的对象由调用方处理，不经过回收复用回调
+            self.recycle_offers.clear();
+        }
+
+        let count = out.len().min(self.pending_garbage.len());
+        for (dst, obj) in out.iter_mut().zip(self.pending_garbage.drain(..count)) {
+            *dst = obj.into();
+        }
+        count
+    }
+
+    /// 设置释放回调，传入None表示只移除追踪记录
+    pub fn set_free_callback(&mut self, callback: Option<SlimeGcFreeCallback>, user_data: *mut c_void) {
+        self.free_callback = callback;
+        self.free_user_data = user_data;
+    }
+
+    /// 执行垃圾回收并返回详细结果，释放回调的处理与collect_garbage相同
+    pub fn collect_garbage_detailed(&mut self) -> SlimeGcCollectResult {
+        let freed = self.collect_garbage();
+        SlimeGcCollectResult {
+            freed,
+            queued_for_disposal: self.last_queued_for_disposal,
+            deferred_by_backpressure: self.last_deferred_by_backpressure,
+        }
+    }
+
+    /// 为类型标签设置容量为capacity的处置队列，用于必须由特定线程释放的对象
+    ///
+    /// 之后回收时，该类型的不可达对象不再清除，而是按回收顺序放入队列并保持注册，直到宿主用pop_disposal取出；
+    /// 它们引用的对象也一起保留。放入队列的对象不会调用释放回调，重新变为可达也不会移出队列。
+    /// 队列已满时该类型新的不可达对象既不清除也不放入队列，推迟到队列有空位后的回收。
+    /// 已有队列时只修改容量，队列中的对象保持不变；capacity为0时不再放入新对象。标签0表示未设置类型，忽略
+    pub fn set_type_disposal_queue(&mut self, tag: u32, capacity: usize) {
+        if tag == 0 {
+            return;
+        }
+        self.disposal_queues
+            .entry(tag)
+            .or_insert_with(|| DisposalQueue { capacity, objs: VecDeque::new() })
+            .capacity = capacity;
+    }
+
+    /// 从类型标签的处置队列中按放入顺序取出对象写入out，返回写入数量
+    ///
+    /// 取出的对象此时才被注销：清理它的全部追踪状态，派发地址失效（SLIME_GC_INVALIDATE_SWEPT）和弱引用清除通知。
+    /// 不调用释放回调，对象由宿主释放
+    pub fn pop_disposal<T: From<ObjRef>>(&mut self, tag: u32, out: &mut [T]) -> usize {
+        let Some(queue) = self.disposal_queues.get_mut(&tag) else {
+            return 0;
+        };
+
+        let count = out.len().min(queue.objs.len());
+        let popped: Vec<_> = queue.objs.drain(..count).collect();
+        for &obj in &popped {
+            self.condemned.remove(&obj);
+            self.teardown_object(obj);
+        }
+        self.notify_invalidation(&popped, SLIME_GC_INVALIDATE_SWEPT);
+        self.notify_weak_cleared(SLIME_GC_WEAK_CLEARED_COLLECTED);
+
+        for (dst, obj) in out.iter_mut().zip(popped) {
+            *dst = obj.into();
+        }
+        count
+    }
+
+    /// 移除所有处置队列，队列中的对象恢复为普通对象，下次回收时按可达性处理
+    ///
+    /// 销毁回收器前调用，队列中的对象随最后一次回收交给释放回调
+    pub fn clear_disposal_queues(&mut self) {
+        self.disposal_queues.clear();
+        self.condemned.clear();
+    }
+
+    /// 标记并清除所有不可达对象，返回被清除的对象
+    fn sweep_unreachable(&mut self) -> Vec<ObjRef> {
+        // 完整回收会重新标记所有对象，直接放弃进行中的增量回收；它已清除的对象都已通知过
+        self.incremental = None;
+        self.last_mark_duration = Duration::ZERO;
+        self.last_sweep_duration = Duration::ZERO;
+        self.last_queued_for_disposal = 0;
+        self.last_deferred_by_backpressure = 0;
+        let swept = if self.objects.is_empty() {
+            Vec::new()
+        } else if self.mark_roots().next().is_none() && self.disposal_queues.is_empty() {
+            // 没有根对象时所有对象都不可达，无需标记直接全部清除
+            let sweep_start = Instant::now();
+            let mut swept: Vec<_> = self.objects.drain().collect();
+            self.sort_for_finalization(&mut swept);
+            self.note_recycle_offers(&swept);
+            self.references.clear();
+            self.referrers.clear();
+            self.owner_edges.clear();
+            self.weak_references.clear();
+            self.weak_referrers.clear();
+            self.object_sizes.clear();
+            self.external_bytes.clear();
+            self.object_tags.clear();
+            self.live_bytes = 0;
+            self.last_sweep_duration = sweep_start.elapsed();
+            self.notify_invalidation(&swept, SLIME_GC_INVALIDATE_SWEPT);
+            swept
+        } else {
+            self.mark_and_sweep()
+        };
+        self.last_collected = swept.len();
+
+        // 根据存活字节数调整下次自动回收的阈值
+        self.collection_threshold = self.initial_threshold.max(self.live_bytes.saturating_mul(2));
+
+        swept
+    }
+
+    /// 标记所有可达对象并清除其余对象，返回被清除的对象
+    fn mark_and_sweep(&mut self) -> Vec<ObjRef> {
+        // 步骤1: 标记所有可达对象
+        let mark_start = Instant::now();
+        let mut marked = self.mark_from_roots();
+        // 处置队列中的对象尚未被宿主取出，它们引用的对象一起保留
+        for &obj in &self.condemned {
+            self.mark(obj, &mut marked);
+        }
+        self.last_mark_duration = mark_start.elapsed();
+
+        // 步骤2: 清除所有未标记的对象
+        let sweep_start = Instant::now();
+        let mut to_remove = Vec::new();
+
+        for &obj in &self.objects {
+            if !marked.contains(&obj) {
+                // 注意：这里不直接释放对象，因为对象是在C++中用new创建的
+                // 对象的释放由C++的析构函数负责
+                to_remove.push(obj);
+            }
+        }
+
+        // 从集合中移除已释放的对象
+        self.sort_for_finalization(&mut to_remove);
+        if !self.disposal_queues.is_empty() {
+            let (kept, queued) = self.route_to_disposal(&to_remove);
+            self.last_queued_for_disposal = queued;
+            self.last_deferred_by_backpressure = kept.len() - queued;
+            for obj in kept {
+                self.mark(obj, &mut marked);
+            }
+            to_remove.retain(|obj| !marked.contains(obj));
+        }
+        self.note_recycle_offers(&to_remove);
+        for &obj in &to_remove {
+            self.teardown_object(obj);
+        }
+        self.last_sweep_duration = sweep_start.elapsed();
+
+        // 所有记录清理完毕后再统一通知
+        self.notify_invalidation(&to_remove, SLIME_GC_INVALIDATE_SWEPT);
+        self.notify_weak_cleared(SLIME_GC_WEAK_CLEARED_COLLECTED);
+
+        to_remove
+    }
+
+    /// 拆除回收器并报告仍残留的状态
+    ///
+    /// 只做统计，不执行回收，尚未派发的回调计入报告后丢弃
+    pub fn teardown_report(self) -> TeardownReport {
+        let weak_holders: HashSet<_> = self.weak_references.keys().copied().collect();
+        let mut by_tag: HashMap<u32, usize> = HashMap::new();
+        for obj in &self.objects {
+            *by_tag.entry(self.object_tags.get(obj).copied().unwrap_or(0)).or_insert(0) += 1;
+        }
+        let mut leftover_objects_by_tag: Vec<_> = by_tag.into_iter().collect();
+        leftover_objects_by_tag.sort();
+        let buffer_held: HashSet<_> = self.buffer_holds.values().flatten().copied().collect();
+        let leftover_roots_by_set = [
+            (LivenessAnchor::Root, self.roots.len()),
+            (LivenessAnchor::ScopedRoot, self.scope_root_counts.len()),
+            (LivenessAnchor::Pin, self.pins.len()),
+            (LivenessAnchor::ThreadBuffer, buffer_held.len()),
+        ].into_iter().filter(|&(_, count)| count > 0).collect();
+
+        TeardownReport {
+            leftover_objects: self.objects.len(),
+            leftover_object_examples: sorted_examples(&self.objects),
+            leftover_objects_by_tag,
+            leftover_roots_by_set,
+            leftover_roots: self.roots.len(),
+            leftover_root_examples: sorted_examples(&self.roots),
+            open_root_scopes: self.scope_starts.len(),
+            outstanding_reservations: self.reserved_objects,
+            untaken_garbage: self.pending_garbage.len(),
+            leftover_weak_references: self.weak_references.values().map(HashSet::len).sum(),
+            leftover_weak_holder_examples: sorted_examples(&weak_holders),
+            leftover_pins: self.pins.len(),
+            leftover_pin_examples: sorted_examples(&self.pins.keys().copied().collect()),
+            unflushed_thread_buffers: 0,
+            unflushed_buffer_ops: 0,
+            undispatched_callbacks: self.pending_callbacks.len(),
+            undrained_disposal_objects: self.disposal_queues.values().map(|queue| queue.objs.len()).sum(),
+        }
+    }
+
+    /// 批量查询对象状态，所有对象共用一次标记遍历，其余字段都是常数时间的查找
+    ///
+    /// 未注册的对象除out_degree外各字段均为0（liveness为SLIME_GC_LIVENESS_DEAD）。
+    /// 处置队列中的对象由回收器保持存活，reachable为1，liveness仍报告为CONDEMNED
+    pub fn query_many(&self, objs: &[impl IntoObjRef]) -> Vec<SlimeGcObjectQuery> {
+        let marked = self.mark_from_roots();
+
+        objs.iter()
+            .map(|obj| match obj.into_obj_ref() {
+                Some(obj) => {
+                    let registered = self.objects.contains(&obj);
+                    SlimeGcObjectQuery {
+                        registered: registered as c_int,
+                        root: self.roots.contains(&obj) as c_int,
+                        reachable: marked.contains(&obj) as c_int,
+                        out_degree: self.references.get(&obj).map_or(0, |refs| refs.len() as c_int),
+                        pinned: (registered && self.pins.contains_key(&obj)) as c_int,
+                        scoped_root: (registered && self.scope_root_counts.contains_key(&obj)) as c_int,
+                        in_degree: if registered {
+                            self.referrers.get(&obj)
+                                .map_or(0, |froms| froms.iter().filter(|from| self.objects.contains(*from)).count() as c_int)
+                        } else {
+                            0
+                        },
+                        size: if registered { self.object_sizes.get(&obj).copied().unwrap_or(0) } else { 0 },
+                        type_tag: if registered { self.object_tags.get(&obj).copied().unwrap_or(0) } else { 0 },
+                        liveness: self.liveness(obj).code(),
+                    }
+                }
+                None => SlimeGcObjectQuery::default(),
+            })
+            .collect()
+    }
+
+    /// 获取对象的存活状态，不做标记遍历
+    ///
+    /// 被回收证明不可达时对象被标记为待清除：放入处置队列的对象直到被pop_disposal取出前都是Condemned，
+    /// 重新变为可达也不会移出队列；增量回收进入清除阶段后，本轮未被标记的对象在被清除前都是Condemned，
+    /// 清除阶段中新添加引用或加入根而重新标记的对象恢复为Live。
+    /// 清除阶段中有重新标记的对象尚未扫描完时，未标记的对象可能被它们引用，这期间报告为Live。
+    /// 因处置队列已满而推迟的对象留到以后的回收重新检查，报告为Live；其余未被回收检查过的对象都报告为Live
+    pub fn liveness(&self, obj: impl IntoObjRef) -> Liveness {
+        let Some(obj) = obj.into_obj_ref().filter(|obj| self.objects.contains(obj)) else {
+            return Liveness::Dead;
+        };
+        let sweeping_unmarked = self.incremental.as_ref().is_some_and(|cycle| {
+            cycle.phase == IncrementalPhase::Sweep && cycle.worklist.is_empty() && !cycle.marked.contains(&obj)
+        });
+        if self.condemned.contains(&obj) || sweeping_unmarked {
+            Liveness::Condemned
+        } else {
+            Liveness::Live
+        }
+    }
+
+    /// 检查正向引用、反向索引、根、固定、作用域根、记录和计数之间是否一致
+    ///
+    /// 宿主误用（如在回调中绕过接口修改状态）可能让它们互相矛盾。
+    /// 开启自动修复时发现问题后立即修复，报告的repaired为1；否则只报告不修改
+    pub fn verify(&mut self) -> SlimeGcRepairReport {
+        let fix = self.auto_repair && !self.reconcile(false).is_clean();
+        self.reconcile(fix)
+    }
+
+    /// 设置一致性检查发现问题时是否自动修复，默认关闭
+    pub fn set_auto_repair(&mut self, enabled: bool) {
+        self.auto_repair = enabled;
+    }
+
+    /// 修复检查发现的所有问题，返回修复前发现的问题
+    ///
+    /// 按正向引用重建反向索引，删除引用方未注册的引用，删除指向未注册地址的根、固定、
+    /// 线程缓冲区持有、作用域根和记录，重新计算存活字节数和作用域根计数。
+    /// 指向尚未注册对象的引用是允许的，不会被删除。不清除或释放任何已注册对象，不派发回调，
+    /// 可以在任何两次调用之间执行
+    pub fn repair(&mut self) -> SlimeGcRepairReport {
+        self.reconcile(true)
+    }
+
+    /// 检查并在fix为true时修复内部状态，返回发现的问题
+    fn reconcile(&mut self, fix: bool) -> SlimeGcRepairReport {
+        let mut report = SlimeGcRepairReport {
+            dangling_edges: reconcile_sources(&self.objects, &mut self.references, fix),
+            dangling_weak_edges: reconcile_sources(&self.objects, &mut self.weak_references, fix),
+            ..Default::default()
+        };
+        for (forward, reverse) in [(&self.references, &mut self.referrers), (&self.weak_references, &mut self.weak_referrers)] {
+            let (missing, stale) = reconcile_index(&self.objects, forward, reverse, fix);
+            report.missing_reverse_edges += missing;
+            report.stale_reverse_edges += stale;
+        }
+
+        let objects = &self.objects;
+        report.dangling_roots = self.roots.iter().filter(|&obj| !objects.contains(obj)).count();
+        report.dangling_pins = self.pins.keys().filter(|&obj| !objects.contains(obj)).count()
+            + self.buffer_holds.values().flatten().filter(|&obj| !objects.contains(obj)).count();
+        report.counters_fixed += self.pins.iter().filter(|&(obj, &count)| objects.contains(obj) && count == 0).count();
+        report.dangling_scope_roots = self.scope_roots.iter().flatten().filter(|&obj| !objects.contains(obj)).count();
+        report.stale_records = self.object_sizes.keys().filter(|&obj| !objects.contains(obj)).count()
+            + self.external_bytes.keys().filter(|&obj| !objects.contains(obj)).count()
+            + self.object_tags.keys().filter(|&obj| !objects.contains(obj)).count();
+
+        let mut scope_root_counts: HashMap<ObjRef, usize> = HashMap::new();
+        for obj in self.scope_roots.iter().flatten().filter(|&obj| objects.contains(obj)) {
+            *scope_root_counts.entry(*obj).or_default() += 1;
+        }
+        report.counters_fixed += scope_root_counts.iter().filter(|&(obj, count)| self.scope_root_counts.get(obj) != Some(count)).count()
+            + self.scope_root_counts.keys().filter(|&obj| !scope_root_counts.contains_key(obj)).count();
+        let live_bytes: usize = self.object_sizes.iter().chain(&self.external_bytes)
+            .filter(|(obj, _)| objects.contains(*obj))
+            .map(|(_, bytes)| bytes)
+            .sum();
+        if live_bytes != self.live_bytes {
+            report.counters_fixed += 1;
+        }
+
+        if fix {
+            self.roots.retain(|obj| objects.contains(obj));
+            self.pins.retain(|obj, count| objects.contains(obj) && *count > 0);
+            for held in self.buffer_holds.values_mut() {
+                held.retain(|obj| objects.contains(obj));
+            }
+            self.buffer_holds.retain(|_, held| !held.is_empty());
+            for root in self.scope_roots.iter_mut().filter(|root| root.is_some_and(|obj| !objects.contains(&obj))) {
+                *root = None;
+            }
+            self.object_sizes.retain(|obj, _| objects.contains(obj));
+            self.external_bytes.retain(|obj, _| objects.contains(obj));
+            self.object_tags.retain(|obj, _| objects.contains(obj));
+            self.scope_root_counts = scope_root_counts;
+            self.live_bytes = live_bytes;
+            report.repaired = !report.is_clean() as c_int;
+        }
+        report
+    }
+
+    /// 获取回收器统计信息
+    pub fn get_stats(&self) -> GcStats {
+        GcStats {
+            total_objects: self.objects.len(),
+            root_count: self.roots.len(),
+            total_edges: self.references.iter()
+                .filter(|(from, _)| self.objects.contains(*from))
+                .map(|(_, tos)| tos.iter().filter(|to| self.objects.contains(*to)).count())
+                .sum(),
+            collected_last_cycle: self.last_collected,
+            last_mark_duration: self.last_mark_duration,
+            last_sweep_duration: self.last_sweep_duration,
+            max_objects: self.max_objects,
+            reserved_objects: self.reserved_objects,
+            used_object_slots: self.objects.len() + self.reserved_objects,
+        }
+    }
+
+    /// 以DOT格式导出对象图
+    ///
+    /// 根对象画成方框，固定的对象画成填充方框，作用域根画成虚线方框；强引用为实线，弱引用为虚线。
+    /// 只包含已注册对象之间的引用，节点和边按地址排序
+    pub fn dump_graph_dot(&self) -> String {
+        let mut out = String::from("digraph slime_gc {\n");
+        for obj in self.sorted_objects() {
+            let _ = if self.roots.contains(&obj) {
+                writeln!(out, "    \"{:p}\" [shape=box, style=bold];", obj)
+            } else if self.pins.contains_key(&obj) {
+                writeln!(out, "    \"{:p}\" [shape=box, style=filled];", obj)
+            } else if self.scope_root_counts.contains_key(&obj) {
+                writeln!(out, "    \"{:p}\" [shape=box, style=dashed];", obj)
+            } else {
+                writeln!(out, "    \"{:p}\";", obj)
+            };
+        }
+        for (from, to) in self.sorted_edges(&self.references) {
+            let _ = writeln!(out, "    \"{:p}\" -> \"{:p}\";", from, to);
+        }
+        for (from, to) in self.sorted_edges(&self.weak_references) {
+            let _ = writeln!(out, "    \"{:p}\" -> \"{:p}\" [style=dashed];", from, to);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// 以JSON格式导出对象图
+    ///
+    /// 格式版本为SLIME_GC_GRAPH_FORMAT_VERSION：{"format_version","nodes":[{"id","flags":{...},"pin_count","size","external_bytes","tag","liveness"}],
+    /// "edges":[{"from","to","kind"}]}。flags中的root只表示根对象，pinned、scoped_root、buffer_held（线程缓冲区持有）、
+    /// weakly_referenced和finalizer_registered（设置了释放回调）各自独立给出；liveness为live或condemned，与liveness相同；
+    /// kind为strong、owner（以add_owner添加的强引用）或weak。
+    /// 地址以十六进制字符串表示，size为对象自身的大小，external_bytes为归属于它的外部内存。
+    /// 只包含已注册对象之间的引用，节点和边按地址排序，内容与dump_graph_dot一致；可用validate_graph_json校验
+    pub fn dump_graph_json(&self) -> String {
+        let mut out = Vec::new();
+        // 写入内存不会失败，整个对象图作为一块写入
+        let _ = self.export_json_streaming(&mut out, usize::MAX);
+        String::from_utf8(out).unwrap_or_default()
+    }
+
+    /// 以JSON格式把对象图流式写入w，内容与dump_graph_json相同
+    ///
+    /// 先冻结一份按地址排序的对象和边的快照，再每次序列化chunk_objects个节点或边（至少为1）并写入一次，
+    /// 快照之外的额外内存只与块大小有关。写入失败时立即返回错误，已写入的内容不完整
+    pub fn export_json_streaming(&self, w: &mut impl std::io::Write, chunk_objects: usize) -> std::io::Result<()> {
+        let chunk_objects = chunk_objects.max(1);
+        let objs = self.sorted_objects();
+        let edges = self.graph_edges();
+
+        let mut chunk = format!("{{\"format_version\":{},\"nodes\":[", SLIME_GC_GRAPH_FORMAT_VERSION);
+        for (i, batch) in objs.chunks(chunk_objects).enumerate() {
+            for (j, &obj) in batch.iter().enumerate() {
+                let _ = write!(chunk, "{}{{\"id\":\"{:p}\",\"flags\":{{", if i == 0 && j == 0 { "" } else { "," }, obj);
+                for (k, (name, set)) in GRAPH_NODE_FLAGS.iter().zip(self.node_flags(obj)).enumerate() {
+                    let _ = write!(chunk, "{}\"{}\":{}", if k == 0 { "" } else { "," }, name, set);
+                }
+                let _ = write!(chunk, "}},\"pin_count\":{},\"size\":{},\"external_bytes\":{},\"tag\":{},\"liveness\":\"{}\"}}",
+                    self.pin_count(obj),
+                    self.object_sizes.get(&obj).copied().unwrap_or(0),
+                    self.external_bytes.get(&obj).copied().unwrap_or(0),
+                    self.object_tags.get(&obj).copied().unwrap_or(0),
+                    self.liveness(obj).name());
+            }
+            w.write_all(chunk.as_bytes())?;
+            chunk.clear();
+        }
+
+        chunk.push_str("],\"edges\":[");
+        for (i, batch) in edges.chunks(chunk_objects).enumerate() {
+            for (j, (from, to, kind)) in batch.iter().enumerate() {
+                let _ = write!(chunk, "{}{{\"from\":\"{:p}\",\"to\":\"{:p}\",\"kind\":\"{}\"}}",
+                    if i == 0 && j == 0 { "" } else { "," }, *from, *to, kind);
+            }
+            w.write_all(chunk.as_bytes())?;
+            chunk.clear();
+        }
+        chunk.push_str("]}\n");
+        w.write_all(chunk.as_bytes())
+    }
+
+    /// 导出的所有边：(引用方, 目标, 种类)，按引用方、目标和种类排序
+    fn graph_edges(&self) -> Vec<(ObjRef, ObjRef, &'static str)> {
+        let strong_kind = |from, to| if self.owner_edges.contains(&(from, to)) { "owner" } else { "strong" };
+        let mut edges: Vec<_> = self.sorted_edges(&self.references).into_iter().map(|(from, to)| (from, to, strong_kind(from, to)))
+            .chain(self.sorted_edges(&self.weak_references).into_iter().map(|(from, to)| (from, to, "weak")))
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    /// 对象的导出标志，顺序与GRAPH_NODE_FLAGS相同
+    fn node_flags(&self, obj: ObjRef) -> [bool; GRAPH_NODE_FLAGS.len()] {
+        [
+            self.roots.contains(&obj),
+            self.pins.contains_key(&obj),
+            self.scope_root_counts.contains_key(&obj),
+            self.buffer_holds.values().any(|held| held.contains(&obj)),
+            self.weak_referrers.get(&obj).is_some_and(|froms| froms.iter().any(|from| self.objects.contains(from))),
+            self.free_callback.is_some(),
+        ]
+    }
+
+    /// 对象自身作为存活起点的类型，同时属于多种时按根对象、作用域根、固定、线程缓冲区的顺序取第一种
+    fn anchor_of(&self, obj: ObjRef) -> Option<LivenessAnchor> {
+        if self.roots.contains(&obj) {
+            Some(LivenessAnchor::Root)
+        } else if self.scope_root_counts.contains_key(&obj) {
+            Some(LivenessAnchor::ScopedRoot)
+        } else if self.pins.contains_key(&obj) {
+            Some(LivenessAnchor::Pin)
+        } else if self.buffer_holds.values().any(|held| held.contains(&obj)) {
+            Some(LivenessAnchor::ThreadBuffer)
+        } else {
+            None
+        }
+    }
+
+    /// 查找一条从根对象到obj的强引用路径，用于回答“这个对象为什么还活着”
+    ///
+    /// 路径以根对象、作用域根、固定的对象或线程缓冲区持有的对象开头、以obj结尾，obj本身就是起点时只含obj；
+    /// 需要区分起点类型时使用find_retaining_path
+    pub fn find_path_to_root(&self, obj: impl IntoObjRef) -> Option<Vec<ObjRef>> {
+        self.find_retaining_path(obj).map(|(_, path)| path)
+    }
+
+    /// 查找让obj存活的最短强引用路径及其起点类型
+    ///
+    /// obj未注册或不可达时返回None。沿反向索引从obj出发广度优先搜索，返回最短路径；
+    /// 被固定的对象即使同时可以从根对象到达，也报告为因固定而存活
+    pub fn find_retaining_path(&self, obj: impl IntoObjRef) -> Option<(LivenessAnchor, Vec<ObjRef>)> {
+        let obj = obj.into_obj_ref().filter(|obj| self.objects.contains(obj))?;
+
+        // 每个访问过的对象记录它在路径上的下一个对象（它引用的对象），obj自身没有下一个
+        let mut next_hop: HashMap<ObjRef, Option<ObjRef>> = HashMap::new();
+        let mut queue = VecDeque::from([obj]);
+        next_hop.insert(obj, None);
+
+        while let Some(current) = queue.pop_front() {
+            if let Some(anchor) = self.anchor_of(current) {
+                let mut path = vec![current];
+                let mut hop = next_hop[&current];
+                while let Some(next) = hop {
+                    path.push(next);
+                    hop = next_hop[&next];
+                }
+                return Some((anchor, path));
+            }
+
+            if let Some(froms) = self.referrers.get(&current) {
+                for &from in froms {
+                    if self.objects.contains(&from) && !next_hop.contains_key(&from) {
+                        next_hop.insert(from, Some(current));
+                        queue.push_back(from);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// 以文本说明对象的大小、标志以及它为什么存活
+    ///
+    /// 第一行为大小和类型标签，第二行列出对象的所有标志，第三行给出存活原因：
+    /// 对象本身是根对象或被固定等时直接说明，否则给出从起点到它的引用路径
+    pub fn explain(&self, obj: impl IntoObjRef) -> String {
+        let Some(obj) = obj.into_obj_ref().filter(|obj| self.objects.contains(obj)) else {
+            return String::from("not registered");
+        };
+
+        let mut out = format!("{:p}: size {} bytes, {} external bytes, tag {}", obj,
+            self.object_sizes.get(&obj).copied().unwrap_or(0),
+            self.external_bytes.get(&obj).copied().unwrap_or(0),
+            self.object_tags.get(&obj).copied().unwrap_or(0));
+
+        let [root, pinned, scoped_root, buffer_held, weakly_referenced, finalizer_registered] = self.node_flags(obj);
+        let mut flags = Vec::new();
+        if root {
+            flags.push(String::from("root"));
+        }
+        if pinned {
+            flags.push(format!("pinned ({})", self.pin_count(obj)));
+        }
+        if scoped_root {
+            flags.push(String::from("scoped root"));
+        }
+        if buffer_held {
+            flags.push(String::from("held by a thread buffer"));
+        }
+        if weakly_referenced {
+            let holders = self.weak_referrers[&obj].iter().filter(|from| self.objects.contains(*from)).count();
+            flags.push(format!("weakly referenced by {}", holders));
+        }
+        if finalizer_registered {
+            flags.push(String::from("finalizer registered"));
+        }
+        if self.liveness(obj) == Liveness::Condemned {
+            flags.push(String::from("condemned"));
+        }
+        let _ = write!(out, "\nflags: {}", if flags.is_empty() { String::from("none") } else { flags.join(", ") });
+
+        let _ = match self.find_retaining_path(obj) {
+            Some((anchor, path)) if path.len() == 1 => write!(out, "\nalive because: it is {}", anchor.describe()),
+            Some((anchor, path)) => {
+                let hops: Vec<_> = path.iter().map(|obj| format!("{:p}", *obj)).collect();
+                write!(out, "\nalive because: reachable from {}: {}", anchor.describe(), hops.join(" -> "))
+            }
+            None => write!(out, "\nunreachable: will be collected by the next collection"),
+        };
+        out
+    }
+
+    /// 按地址排序的所有已注册对象
+    fn sorted_objects(&self) -> Vec<ObjRef> {
+        let mut objs: Vec<_> = self.objects.iter().copied().collect();
+        objs.sort();
+        objs
+    }
+
+    /// 按地址排序的已注册对象之间的引用
+    fn sorted_edges(&self, edges: &HashMap<ObjRef, HashSet<ObjRef>>) -> Vec<(ObjRef, ObjRef)> {
+        let mut pairs: Vec<_> = edges.iter()
+            .filter(|(from, _)| self.objects.contains(*from))
+            .flat_map(|(&from, tos)| tos.iter().map(move |&to| (from, to)))
+            .filter(|(_, to)| self.objects.contains(to))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    /// 标记的起点：根对象、作用域根对象、固定的对象和线程缓冲区持有的对象
+    fn mark_roots(&self) -> impl Iterator<Item = ObjRef> + '_ {
+        self.roots.iter()
+            .chain(self.scope_root_counts.keys())
+            .chain(self.pins.keys())
+            .chain(self.buffer_holds.values().flatten())
+            .copied()
+    }
+
+    /// 线程缓冲区持有对象，直到该缓冲区被刷新
+    fn hold_for_buffer(&mut self, buffer: u64, obj: ObjRef) {
+        self.buffer_holds.entry(buffer).or_default().insert(obj);
+    }
+
+    /// 释放线程缓冲区持有的所有对象
+    fn release_buffer_holds(&mut self, buffer: u64) {
+        self.buffer_holds.remove(&buffer);
+    }
+
+    /// 从所有根对象、作用域根对象、固定的对象和线程缓冲区持有的对象出发标记可达对象
+    fn mark_from_roots(&self) -> HashSet<ObjRef> {
+        self.mark_passes.fetch_add(1, Ordering::Relaxed);
+        let mut marked = HashSet::new();
+
+        for root in self.mark_roots() {
+            self.mark(root, &mut marked);
+        }
+
+        marked
+    }
+
+    /// 标记对象及其引用的对象
+    ///
+    /// 使用显式工作列表代替递归，很长的引用链也不会耗尽调用栈
+    fn mark(&self, obj: ObjRef, marked: &mut HashSet<ObjRef>) {
+        let mut worklist = Vec::new();
+        self.mark_one(obj, marked, &mut worklist);
+
+        while let Some(obj) = worklist.pop() {
+            // 标记所有引用的对象
+            if let Some(refs) = self.references.get(&obj) {
+                for &ref_obj in refs {
+                    self.mark_one(ref_obj, marked, &mut worklist);
+                }
+            }
+        }
+    }
+
+    /// 标记单个对象，首次标记时放入工作列表等待扫描其引用
+    fn mark_one(&self, obj: ObjRef, marked: &mut HashSet<ObjRef>, worklist: &mut Vec<ObjRef>) {
+        // 跳过未注册的对象；已标记的对象不会重复入列
+        if self.objects.contains(&obj) && marked.insert(obj) {
+            worklist.push(obj);
+        }
+    }
+}
+
+/// 在一次独占借用中执行操作，借用结束后再派发期间产生的回调
+///
+/// 回调中可以通过原始指针重入回收器（如在释放回调中注销其他对象），此时不存在其他借用
+///
+/// # Safety
+/// gc必须是非空且有效的回收器指针
+unsafe fn with_gc<R>(gc: *mut GarbageCollector, f: impl FnOnce(&mut GarbageCollector) -> R) -> R {
+    let (result, pending) = {
+        let gc = unsafe { &mut *gc };
+        let result = f(gc);
+        (result, gc.take_pending_callbacks())
+    };
+    pending.run();
+    result
+}
+
+/// 选中回调类别时取出pump要派发的回调，否则留在回收器中
+fn take_pumped_callbacks(gc: &mut GarbageCollector, priorities: u32) -> PendingCallbacks {
+    if priorities & SLIME_GC_WORK_CALLBACKS != 0 {
+        gc.take_pending_callbacks()
+    } else {
+        PendingCallbacks::default()
+    }
+}
+
+/// pump的最后一步：在剩余预算内派发回调，返回未派发的回调
+///
+/// 调用时不能持有回收器的借用或锁；本次pump没有推进其他工作时至少派发一批
+fn dispatch_pumped_callbacks(pending: PendingCallbacks, start: Instant, max_duration: Duration, result: &mut SlimeGcPumpResult) -> PendingCallbacks {
+    if pending.is_empty() {
+        return pending;
+    }
+    let idle = result.mark_done + result.sweep_done + result.garbage_done == 0;
+    let (done, rest) = pending.run_within(start, max_duration, idle);
+    result.callbacks_done = done;
+    result.callbacks_remaining = rest.len();
+    rest
+}
+
+/// 线程缓冲区中尚未应用的操作
+enum BufferedOp {
+    Register { obj: Option<ObjRef>, size_bytes: usize, tag: u32 },
+    AddReference { from: Option<ObjRef>, to: Option<ObjRef> },
+}
+
+/// 无锁操作栈的节点
+struct OpNode {
+    op: BufferedOp,
+    next: *mut OpNode,
+}
+
+/// 线程缓冲区与回收器共享的操作队列
+///
+/// 操作压入一个无锁栈：所属线程记录操作只做一次比较交换，
+/// 安全点一次取走整个栈再按记录顺序应用，双方都不加锁
+struct BufferQueue {
+    id: u64,
+    head: AtomicPtr<OpNode>,
+    len: AtomicUsize,
+    failed_registrations: AtomicUsize,
+}
+
+impl BufferQueue {
+    fn new(id: u64) -> Self {
+        BufferQueue {
+            id,
+            head: AtomicPtr::new(std::ptr::null_mut()),
+            len: AtomicUsize::new(0),
+            failed_registrations: AtomicUsize::new(0),
+        }
+    }
+
+    fn push(&self, op: BufferedOp) {
+        // 先计数再入栈，取走时减去的数量不会超过已计入的数量
+        self.len.fetch_add(1, Ordering::Relaxed);
+        let node = Box::into_raw(Box::new(OpNode { op, next: std::ptr::null_mut() }));
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            unsafe {
+                (*node).next = head;
+            }
+            match self.head.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    /// 取走所有尚未应用的操作，按记录顺序返回
+    fn take(&self) -> Vec<BufferedOp> {
+        let mut node = self.head.swap(std::ptr::null_mut(), Ordering::Acquire);
+        let mut ops = Vec::new();
+        while !node.is_null() {
+            let boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next;
+            ops.push(boxed.op);
+        }
+        self.len.fetch_sub(ops.len(), Ordering::Relaxed);
+        ops.reverse();
+        ops
+    }
+}
+
+impl Drop for BufferQueue {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+/// 线程注册缓冲区
+///
+/// 由一个变更线程使用，记录注册和添加引用操作时不获取任何锁；
+/// 操作在刷新或其他线程进入安全点时于一次加锁中统一应用。
+/// 其他线程的安全点注册的对象由缓冲区持有，在所属线程刷新之前一直视为存活。
+/// 丢弃缓冲区等同于调用end，只是不报告结果
+pub struct ThreadBuffer<'gc> {
+    gc: &'gc ConcurrentGarbageCollector,
+    queue: Arc<BufferQueue>,
+}
+
+impl ThreadBuffer<'_> {
+    /// 记录一次注册，应用时等同于register_object_with
+    pub fn register(&self, obj: impl IntoObjRef, size_bytes: usize, tag: u32) {
+        let obj = obj.into_obj_ref();
+        self.queue.push(BufferedOp::Register { obj, size_bytes, tag });
+    }
+
+    /// 记录一次添加引用，应用时等同于add_reference
+    pub fn add_reference(&self, from: impl IntoObjRef, to: impl IntoObjRef) {
+        let (from, to) = (from.into_obj_ref(), to.into_obj_ref());
+        self.queue.push(BufferedOp::AddReference { from, to });
+    }
+
+    /// 获取尚未应用的操作数量
+    pub fn len(&self) -> usize {
+        self.queue.len.load(Ordering::Relaxed)
+    }
+
+    /// 检查是否没有尚未应用的操作
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 应用缓冲区中的操作
+    ///
+    /// 在安全点执行，其他线程缓冲区中的操作也会一并应用。
+    /// 刷新后本缓冲区不再持有任何对象，未与根对象连通的对象此后可以被回收。
+    /// 自上次刷新以来本缓冲区有注册因数量上限失败时返回SLIME_GC_OBJECT_LIMIT
+    pub fn flush(&self) -> c_int {
+        self.gc.flush_thread_buffer(&self.queue)
+    }
+
+    /// 刷新并关闭缓冲区，返回值与flush相同
+    pub fn end(self) -> c_int {
+        self.gc.close_thread_buffer(&self.queue)
+    }
+}
+
+impl Drop for ThreadBuffer<'_> {
+    fn drop(&mut self) {
+        self.gc.close_thread_buffer(&self.queue);
+    }
+}
+
+/// 线程安全的垃圾回收器
+///
+/// 修改操作持有写锁，只读查询持有读锁，回收在写锁内一次完成。
+/// 所有回调都在写锁释放后才调用，因此回调中可以安全地重入同一个回收器。
+/// 注册频繁的线程可以改用线程缓冲区，把多次注册合并为一次加锁
+pub struct ConcurrentGarbageCollector {
+    inner: RwLock<GarbageCollector>,
+    /// 打开的线程缓冲区，只在缓冲区打开、关闭和安全点时加锁
+    buffers: Mutex<Vec<Arc<BufferQueue>>>,
+    next_buffer_id: AtomicU64,
+    write_locks: AtomicU64,
+    /// 是否记录操作耗时，默认关闭
+    op_latency_enabled: AtomicBool,
+    /// 各类操作的耗时直方图，下标为SLIME_GC_OP_*
+    op_latency: [LatencyHistogram; OP_KINDS],
+}
+
+// 回收器中的指针只作为不透明地址保存和比较，从不解引用；
+// 回调及其用户数据的线程安全由宿主负责
+unsafe impl Send for ConcurrentGarbageCollector {}
+unsafe impl Sync for ConcurrentGarbageCollector {}
+
+impl Default for ConcurrentGarbageCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConcurrentGarbageCollector {
+    /// 创建新的线程安全垃圾回收器
+    pub fn new() -> Self {
+        ConcurrentGarbageCollector {
+            inner: RwLock::new(GarbageCollector::new()),
+            buffers: Mutex::new(Vec::new()),
+            next_buffer_id: AtomicU64::new(1),
+            write_locks: AtomicU64::new(0),
+            op_latency_enabled: AtomicBool::new(false),
+            op_latency: [const { LatencyHistogram::new() }; OP_KINDS],
+        }
+    }
+
+    /// 持有读锁执行只读操作
+    pub fn with_read<R>(&self, f: impl FnOnce(&GarbageCollector) -> R) -> R {
+        f(&self.read_lock())
+    }
+
+    /// 持有写锁执行修改操作，写锁释放后再派发期间产生的回调
+    pub fn with_write<R>(&self, f: impl FnOnce(&mut GarbageCollector) -> R) -> R {
+        let mut gc = self.write_lock();
+        let result = f(&mut gc);
+        let pending = gc.take_pending_callbacks();
+        drop(gc);
+
+        pending.run();
+        result
+    }
+
+    /// 执行一次操作，开启耗时记录时把包括等锁和派发回调在内的耗时记入op类别；未开启时只多一次分支
+    fn timed<R>(&self, op: c_int, f: impl FnOnce() -> R) -> R {
+        if !self.op_latency_enabled.load(Ordering::Relaxed) {
+            return f();
+        }
+        let start = Instant::now();
+        let result = f();
+        self.op_latency[op as usize].record(start.elapsed().as_nanos().min(u64::MAX as u128) as u64);
+        result
+    }
+
+    /// 开启或关闭操作耗时记录，关闭时保留已记录的直方图
+    ///
+    /// 记录注册和注销、添加和移除强引用、根对象、固定和作用域根的增减、添加和移除弱引用这些直接调用的耗时，
+    /// 线程缓冲区中的操作不记录
+    pub fn set_op_latency_enabled(&self, enabled: bool) {
+        self.op_latency_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// 获取一类操作（SLIME_GC_OP_*）的耗时直方图，类别无效时返回None
+    pub fn op_latency(&self, op: c_int) -> Option<SlimeGcHistogram> {
+        let op = usize::try_from(op).ok()?;
+        self.op_latency.get(op).map(LatencyHistogram::snapshot)
+    }
+
+    /// 以Prometheus文本格式导出统计信息和操作耗时直方图
+    pub fn metrics_text(&self) -> String {
+        let stats = self.get_stats();
+        let mut out = String::new();
+        for (name, help, value) in [
+            ("slime_gc_objects", "Registered objects.", stats.total_objects),
+            ("slime_gc_roots", "Root objects, excluding scoped roots.", stats.root_count),
+            ("slime_gc_edges", "Strong references between registered objects.", stats.total_edges),
+            ("slime_gc_collected_last_cycle", "Objects swept by the last collection.", stats.collected_last_cycle),
+        ] {
+            let _ = write!(out, "# HELP {name} {help}\n# TYPE {name} gauge\n{name} {value}\n");
+        }
+
+        out.push_str("# HELP slime_gc_op_latency_seconds Latency of mutator-visible operations while recording is enabled.\n");
+        out.push_str("# TYPE slime_gc_op_latency_seconds histogram\n");
+        for (op, histogram) in OP_KIND_NAMES.iter().zip(&self.op_latency) {
+            let histogram = histogram.snapshot();
+            let mut cumulative = 0;
+            // 桶i的上界为2^i - 1纳秒，最后一个桶没有上界
+            for (i, count) in histogram.buckets[..SLIME_GC_LATENCY_BUCKETS - 1].iter().enumerate() {
+                cumulative += count;
+                let le = ((1u64 << i) - 1) as f64 / 1e9;
+                let _ = writeln!(out, "slime_gc_op_latency_seconds_bucket{{op=\"{op}\",le=\"{le}\"}} {cumulative}");
+            }
+            let _ = writeln!(out, "slime_gc_op_latency_seconds_bucket{{op=\"{op}\",le=\"+Inf\"}} {}", histogram.count);
+            let _ = writeln!(out, "slime_gc_op_latency_seconds_sum{{op=\"{op}\"}} {}", histogram.total_nanos as f64 / 1e9);
+            let _ = writeln!(out, "slime_gc_op_latency_seconds_count{{op=\"{op}\"}} {}", histogram.count);
+        }
+        out
+    }
+
+    fn read_lock(&self) -> RwLockReadGuard<'_, GarbageCollector> {
+        // 回调是C函数不会panic，锁中毒时内部状态仍然一致
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_lock(&self) -> RwLockWriteGuard<'_, GarbageCollector> {
+        self.write_locks.fetch_add(1, Ordering::Relaxed);
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_buffers(&self) -> MutexGuard<'_, Vec<Arc<BufferQueue>>> {
+        self.buffers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 在安全点执行可能触发回收的修改操作
+    ///
+    /// 先在写锁内应用所有线程缓冲区中的操作，再执行f。
+    /// 从缓冲区注册的对象由该缓冲区持有，直到所属线程刷新：
+    /// 所属线程可能还没来得及缓冲指向它的引用，期间的回收都不会清除它
+    fn with_safepoint<R>(&self, f: impl FnOnce(&mut GarbageCollector) -> R) -> R {
+        self.with_safepoint_of(None, f)
+    }
+
+    /// 以owner所属线程的身份进入安全点，执行f后释放owner持有的所有对象
+    fn with_safepoint_of<R>(&self, owner: Option<&BufferQueue>, f: impl FnOnce(&mut GarbageCollector) -> R) -> R {
+        let (result, pending) = self.at_safepoint(owner, |gc| (f(gc), gc.take_pending_callbacks()));
+        pending.run();
+        result
+    }
+
+    /// 在安全点执行f，期间产生的回调留在回收器中，需要派发的由f自行取出
+    fn at_safepoint<R>(&self, owner: Option<&BufferQueue>, f: impl FnOnce(&mut GarbageCollector) -> R) -> R {
+        // 加锁顺序固定为：缓冲区列表、回收器
+        let buffers = self.lock_buffers();
+        let mut gc = self.write_lock();
+
+        for queue in buffers.iter() {
+            for op in queue.take() {
+                match op {
+                    BufferedOp::Register { obj, size_bytes, tag } => {
+                        // 逐个持有，批量注册中途的紧急回收也不会清除同批的对象
+                        if gc.register_object_with(obj, size_bytes, tag) != SLIME_GC_OK {
+                            queue.failed_registrations.fetch_add(1, Ordering::Relaxed);
+                        } else if let Some(obj) = obj {
+                            gc.hold_for_buffer(queue.id, obj);
+                        }
+                    }
+                    BufferedOp::AddReference { from, to } => gc.add_reference(from, to),
+                }
+            }
+        }
+
+        let result = f(&mut gc);
+        // 所属线程此前缓冲的引用都已应用，它的对象不再需要额外保护
+        if let Some(owner) = owner {
+            gc.release_buffer_holds(owner.id);
+        }
+        drop(gc);
+        drop(buffers);
+        result
+    }
+
+    /// 刷新并关闭线程缓冲区，缓冲区已关闭时不做任何事
+    fn close_thread_buffer(&self, queue: &Arc<BufferQueue>) -> c_int {
+        if !self.lock_buffers().iter().any(|b| Arc::ptr_eq(b, queue)) {
+            return SLIME_GC_OK;
+        }
+        // 只有所属线程会关闭缓冲区，刷新之后不会再有新的操作和持有
+        let status = self.flush_thread_buffer(queue);
+        self.lock_buffers().retain(|b| !Arc::ptr_eq(b, queue));
+        status
+    }
+
+    /// 以所属线程的身份刷新线程缓冲区，报告自上次刷新以来是否有注册失败
+    fn flush_thread_buffer(&self, queue: &BufferQueue) -> c_int {
+        self.with_safepoint_of(Some(queue), |_| ());
+        if queue.failed_registrations.swap(0, Ordering::Relaxed) > 0 {
+            SLIME_GC_OBJECT_LIMIT
+        } else {
+            SLIME_GC_OK
+        }
+    }
+
+    /// 创建一个线程注册缓冲区，所有可能触发回收的操作都会先应用其中的操作
+    pub fn thread_buffer_begin(&self) -> ThreadBuffer<'_> {
+        let queue = Arc::new(BufferQueue::new(self.next_buffer_id.fetch_add(1, Ordering::Relaxed)));
+        self.lock_buffers().push(Arc::clone(&queue));
+        ThreadBuffer { gc: self, queue }
+    }
+
+    /// 获取创建以来获取写锁的次数，用于衡量锁的使用频率
+    pub fn get_write_lock_count(&self) -> u64 {
+        self.write_locks.load(Ordering::Relaxed)
+    }
+
+    /// 注册新对象
+    pub fn register_object(&self, obj: impl IntoObjRef) -> c_int {
+        self.timed(SLIME_GC_OP_REGISTER, || self.with_safepoint(|gc| gc.register_object(obj)))
+    }
+
+    /// 注册指定大小（字节）的新对象
+    pub fn register_object_sized(&self, obj: impl IntoObjRef, size_bytes: usize) -> c_int {
+        self.timed(SLIME_GC_OP_REGISTER, || self.with_safepoint(|gc| gc.register_object_sized(obj, size_bytes)))
+    }
+
+    /// 注册带类型标签的新对象
+    pub fn register_object_tagged(&self, obj: impl IntoObjRef, tag: u32) -> c_int {
+        self.timed(SLIME_GC_OP_REGISTER, || self.with_safepoint(|gc| gc.register_object_tagged(obj, tag)))
+    }
+
+    /// 注册指定大小和类型标签的新对象
+    pub fn register_object_with(&self, obj: impl IntoObjRef, size_bytes: usize, tag: u32) -> c_int {
+        self.timed(SLIME_GC_OP_REGISTER, || self.with_safepoint(|gc| gc.register_object_with(obj, size_bytes, tag)))
+    }
+
+    /// 注销对象
+    pub fn unregister_object(&self, obj: impl IntoObjRef) {
+        self.timed(SLIME_GC_OP_REGISTER, || self.with_write(|gc| gc.unregister_object(obj)))
+    }
+
+    /// 添加对象引用
+    pub fn add_reference(&self, from: impl IntoObjRef, to: impl IntoObjRef) {
+        self.timed(SLIME_GC_OP_ADD_REFERENCE, || self.with_write(|gc| gc.add_reference(from, to)))
+    }
+
+    /// 移除对象引用
+    pub fn remove_reference(&self, from: impl IntoObjRef, to: impl IntoObjRef) {
+        self.timed(SLIME_GC_OP_REMOVE_REFERENCE, || self.with_write(|gc| gc.remove_reference(from, to)))
+    }
+
+    /// 批量添加引用
+    pub fn add_references(&self, from: impl IntoObjRef, to_list: &[impl IntoObjRef]) {
+        self.timed(SLIME_GC_OP_ADD_REFERENCE, || self.with_write(|gc| gc.add_references(from, to_list)))
+    }
+
+    /// 批量移除引用
+    pub fn remove_references(&self, from: impl IntoObjRef, to_list: &[impl IntoObjRef]) {
+        self.timed(SLIME_GC_OP_REMOVE_REFERENCE, || self.with_write(|gc| gc.remove_references(from, to_list)))
+    }
+
+    /// 移除对象的所有引用
+    pub fn clear_references(&self, obj: impl IntoObjRef) {
+        self.timed(SLIME_GC_OP_REMOVE_REFERENCE, || self.with_write(|gc| gc.clear_references(obj)))
+    }
+
+    /// 添加弱引用
+    pub fn add_weak_reference(&self, from: impl IntoObjRef, to: impl IntoObjRef) {
+        self.timed(SLIME_GC_OP_WEAK, || self.with_write(|gc| gc.add_weak_reference(from, to)))
+    }
+
+    /// 移除弱引用
+    pub fn remove_weak_reference(&self, from: impl IntoObjRef, to: impl IntoObjRef) {
+        self.timed(SLIME_GC_OP_WEAK, || self.with_write(|gc| gc.remove_weak_reference(from, to)))
+    }
+
+    /// 将对象标记为根对象
+    pub fn mark_root(&self, obj: impl IntoObjRef) {
+        self.timed(SLIME_GC_OP_ROOT, || self.with_write(|gc| gc.mark_root(obj)))
+    }
+
+    /// 将对象标记为非根对象
+    pub fn unmark_root(&self, obj: impl IntoObjRef) {
+        self.timed(SLIME_GC_OP_ROOT, || self.with_write(|gc| gc.unmark_root(obj)))
+    }
+
+    /// 清除所有根对象标记
+    pub fn clear_roots(&self) {
+        self.with_write(|gc| gc.clear_roots())
+    }
+
+    /// 设置释放回调
+    pub fn set_free_callback(&self, callback: Option<SlimeGcFreeCallback>, user_data: *mut c_void) {
+        self.with_write(|gc| gc.set_free_callback(callback, user_data))
+    }
+
+    /// 执行垃圾回收
+    pub fn collect_garbage(&self) -> usize {
+        self.with_safepoint(|gc| gc.collect_garbage())
+    }
+
+    /// 执行垃圾回收并返回详细结果
+    pub fn collect_garbage_detailed(&self) -> SlimeGcCollectResult {
+        self.with_safepoint(|gc| gc.collect_garbage_detailed())
+    }
+
+    /// 为类型标签设置处置队列
+    pub fn set_type_disposal_queue(&self, tag: u32, capacity: usize) {
+        self.with_write(|gc| gc.set_type_disposal_queue(tag, capacity))
+    }
+
+    /// 从类型标签的处置队列中取出对象写入out，先应用所有线程缓冲区中的操作
+    pub fn pop_disposal<T: From<ObjRef>>(&self, tag: u32, out: &mut [T]) -> usize {
+        self.with_safepoint(|gc| gc.pop_disposal(tag, out))
+    }
+
+    /// 移除所有处置队列
+    pub fn clear_disposal_queues(&self) {
+        self.with_write(|gc| gc.clear_disposal_queues())
+    }
+
+    /// 为类型标签设置回收复用回调
+    pub fn set_recycler(&self, tag: u32, callback: Option<SlimeGcRecyclerCallback>, user_data: *mut c_void) {
+        self.with_write(|gc| gc.set_recycler(tag, callback, user_data))
+    }
+
+    /// 执行一步增量回收
+    pub fn collect_step(&self, budget_objects: usize) -> CollectStepResult {
+        self.with_safepoint(|gc| gc.collect_step(budget_objects))
+    }
+
+    /// 获取当前有待推进的延迟工作类别
+    pub fn pending_work(&self) -> u32 {
+        self.with_read(|gc| gc.pending_work())
+    }
+
+    /// 在时间预算内推进选中的延迟工作，写锁释放后用剩余预算派发回调
+    ///
+    /// 未选中回调类别时，期间产生的回调留到下一次修改操作结束时派发
+    pub fn pump(&self, max_duration: Duration, priorities: u32) -> SlimeGcPumpResult {
+        let start = Instant::now();
+        let (mut result, pending) = self.at_safepoint(None, |gc| {
+            let result = gc.pump(max_duration, priorities);
+            (result, take_pumped_callbacks(gc, priorities))
+        });
+
+        let rest = dispatch_pumped_callbacks(pending, start, max_duration, &mut result);
+        if !rest.is_empty() {
+            self.write_lock().requeue_callbacks(rest);
+        }
+        result
+    }
+
+    /// 固定对象
+    pub fn pin(&self, obj: impl IntoObjRef) -> c_int {
+        self.timed(SLIME_GC_OP_ROOT, || self.with_write(|gc| gc.pin(obj)))
+    }
+
+    /// 取消一次固定
+    pub fn unpin(&self, obj: impl IntoObjRef) {
+        self.timed(SLIME_GC_OP_ROOT, || self.with_write(|gc| gc.unpin(obj)))
+    }
+
+    /// 检查对象是否被固定
+    pub fn is_pinned(&self, obj: impl IntoObjRef) -> bool {
+        self.with_read(|gc| gc.is_pinned(obj))
+    }
+
+    /// 获取对象的固定次数
+    pub fn pin_count(&self, obj: impl IntoObjRef) -> usize {
+        self.with_read(|gc| gc.pin_count(obj))
+    }
+
+    /// 存活字节数超过阈值时执行回收
+    pub fn maybe_collect(&self) -> usize {
+        self.with_safepoint(|gc| gc.maybe_collect())
+    }
+
+    /// 获取对象的引用数量
+    pub fn get_reference_count(&self, obj: impl IntoObjRef) -> usize {
+        self.with_read(|gc| gc.get_references(obj).map_or(0, |refs| refs.len()))
+    }
+
+    /// 获取当前根对象数量
+    pub fn get_root_count(&self) -> usize {
+        self.with_read(|gc| gc.get_root_count())
+    }
+
+    /// 获取当前注册对象数量
+    pub fn get_object_count(&self) -> usize {
+        self.with_read(|gc| gc.get_object_count())
+    }
+
+    /// 获取所有已注册对象的总字节数，包括归属于它们的外部内存
+    pub fn get_live_bytes(&self) -> usize {
+        self.with_read(|gc| gc.get_live_bytes())
+    }
+
+    /// 把外部内存归属到对象
+    pub fn add_external_bytes(&self, obj: impl IntoObjRef, bytes: usize) -> c_int {
+        self.with_write(|gc| gc.add_external_bytes(obj, bytes))
+    }
+
+    /// 从对象归属的外部内存中扣除，最多扣到0
+    pub fn remove_external_bytes(&self, obj: impl IntoObjRef, bytes: usize) {
+        self.with_write(|gc| gc.remove_external_bytes(obj, bytes))
+    }
+
+    /// 获取归属于对象的外部内存字节数
+    pub fn get_external_bytes(&self, obj: impl IntoObjRef) -> usize {
+        self.with_read(|gc| gc.get_external_bytes(obj))
+    }
+
+    /// 检查对象是否仍被回收器追踪
+    pub fn is_alive(&self, obj: impl IntoObjRef) -> bool {
+        self.with_read(|gc| gc.is_alive(obj))
+    }
+
+    /// 设置未指定大小的对象按多少字节计入
+    pub fn set_default_object_size(&self, size_bytes: usize) {
+        self.with_write(|gc| gc.set_default_object_size(size_bytes))
+    }
+
+    /// 设置自动回收阈值（字节）
+    pub fn set_collection_threshold(&self, bytes: usize) {
+        self.with_write(|gc| gc.set_collection_threshold(bytes))
+    }
+
+    /// 设置弱引用被清除时的回调
+    pub fn set_weak_clear_callback(&self, callback: Option<SlimeGcWeakClearCallback>, user_data: *mut c_void) {
+        self.with_write(|gc| gc.set_weak_clear_callback(callback, user_data))
+    }
+
+    /// 设置带原因的弱引用清除回调
+    pub fn set_weak_clear_reason_callback(&self, callback: Option<SlimeGcWeakClearReasonCallback>, user_data: *mut c_void) {
+        self.with_write(|gc| gc.set_weak_clear_reason_callback(callback, user_data))
+    }
+
+    /// 作用域栈由所有线程共享，多个线程同时使用时须由宿主保证按栈顺序开闭
+    pub fn push_root_scope(&self) {
+        self.with_write(|gc| gc.push_root_scope())
+    }
+
+    /// 在最内层作用域中加入临时根对象
+    pub fn add_scoped_root(&self, obj: impl IntoObjRef) -> c_int {
+        self.timed(SLIME_GC_OP_ROOT, || self.with_write(|gc| gc.add_scoped_root(obj)))
+    }
+
+    /// 关闭最内层作用域
+    pub fn pop_root_scope(&self) -> c_int {
+        self.with_write(|gc| gc.pop_root_scope())
+    }
+
+    /// 执行垃圾回收并把被回收的对象写入out
+    pub fn collect_into<T: From<ObjRef>>(&self, out: &mut [T]) -> usize {
+        self.with_safepoint(|gc| gc.collect_into(out))
+    }
+
+    /// 将所有指向from_obj的引用改为指向to_obj
+    pub fn redirect(&self, from_obj: impl IntoObjRef, to_obj: impl IntoObjRef, flags: c_int) -> usize {
+        self.with_write(|gc| gc.redirect(from_obj, to_obj, flags))
+    }
+
+    /// 设置最近注销检测的窗口大小，0表示关闭
+    pub fn set_recent_unregister_window(&self, window: usize) {
+        self.with_write(|gc| gc.set_recent_unregister_window(window))
+    }
+
+    /// 设置使用最近注销对象时的回调
+    pub fn set_recently_unregistered_callback(&self, callback: Option<SlimeGcRecentlyUnregisteredCallback>, user_data: *mut c_void) {
+        self.with_write(|gc| gc.set_recently_unregistered_callback(callback, user_data))
+    }
+
+    /// 获取使用最近注销对象的次数
+    pub fn get_recently_unregistered_count(&self) -> usize {
+        self.with_read(|gc| gc.get_recently_unregistered_count())
+    }
+
+    /// 订阅地址失效通知，返回订阅编号
+    pub fn subscribe_invalidation(&self, callback: SlimeGcInvalidationCallback, user_data: *mut c_void) -> u64 {
+        self.with_write(|gc| gc.subscribe_invalidation(callback, user_data))
+    }
+
+    /// 取消地址失效通知订阅
+    pub fn unsubscribe_invalidation(&self, id: u64) {
+        self.with_write(|gc| gc.unsubscribe_invalidation(id))
+    }
+
+    /// 记录child被owner拥有
+    pub fn add_owner(&self, child: impl IntoObjRef, owner: impl IntoObjRef) {
+        self.with_write(|gc| gc.add_owner(child, owner))
+    }
+
+    /// 移除child的拥有者
+    pub fn remove_owner(&self, child: impl IntoObjRef, owner: impl IntoObjRef) {
+        self.with_write(|gc| gc.remove_owner(child, owner))
+    }
+
+    /// 批量记录child的拥有者
+    pub fn add_owners(&self, child: impl IntoObjRef, owner_list: &[impl IntoObjRef]) {
+        self.with_write(|gc| gc.add_owners(child, owner_list))
+    }
+
+    /// 批量移除child的拥有者
+    pub fn remove_owners(&self, child: impl IntoObjRef, owner_list: &[impl IntoObjRef]) {
+        self.with_write(|gc| gc.remove_owners(child, owner_list))
+    }
+
+    /// 批量查询对象状态
+    pub fn query_many(&self, objs: &[impl IntoObjRef]) -> Vec<SlimeGcObjectQuery> {
+        self.with_read(|gc| gc.query_many(objs))
+    }
+
+    /// 拆除回收器并报告仍残留的状态
+    ///
+    /// 不进入安全点：仍打开的线程缓冲区中尚未应用的操作单独报告后丢弃，不会应用到回收器上，
+    /// 其余各项只反映已经应用的操作
+    pub fn teardown_report(self) -> TeardownReport {
+        let buffers = self.buffers.into_inner().unwrap_or_else(PoisonError::into_inner);
+        let pending: Vec<_> = buffers.iter()
+            .map(|queue| queue.len.load(Ordering::Relaxed))
+            .filter(|&len| len > 0)
+            .collect();
+        let mut report = self.inner.into_inner().unwrap_or_else(PoisonError::into_inner).teardown_report();
+        report.unflushed_thread_buffers = pending.len();
+        report.unflushed_buffer_ops = pending.iter().sum();
+        report
+    }
+
+    /// 设置注册对象数量上限，0表示不限制
+    pub fn set_max_objects(&self, max: usize) {
+        self.with_write(|gc| gc.set_max_objects(max))
+    }
+
+    /// 为接下来的n次注册预留空位
+    pub fn reserve_objects(&self, n: usize) -> c_int {
+        self.with_safepoint(|gc| gc.reserve_objects(n))
+    }
+
+    /// 释放最多n个尚未使用的预留空位
+    pub fn release_reservation(&self, n: usize) {
+        self.with_write(|gc| gc.release_reservation(n))
+    }
+
+    /// 获取已预留但尚未使用的对象数量
+    pub fn get_reserved_objects(&self) -> usize {
+        self.with_read(|gc| gc.get_reserved_objects())
+    }
+
+    /// 获取回收器统计信息
+    pub fn get_stats(&self) -> GcStats {
+        self.with_read(|gc| gc.get_stats())
+    }
+
+    /// 以DOT格式导出对象图
+    pub fn dump_graph_dot(&self) -> String {
+        self.with_read(|gc| gc.dump_graph_dot())
+    }
+
+    /// 以JSON格式导出对象图
+    pub fn dump_graph_json(&self) -> String {
+        self.with_read(|gc| gc.dump_graph_json())
+    }
+
+    /// 以JSON格式把对象图流式写入w，写入期间持有读锁
+    pub fn export_json_streaming(&self, w: &mut impl std::io::Write, chunk_objects: usize) -> std::io::Result<()> {
+        self.with_read(|gc| gc.export_json_streaming(w, chunk_objects))
+    }
+
+    /// 查找一条从根对象到obj的引用路径
+    pub fn find_path_to_root(&self, obj: impl IntoObjRef) -> Option<Vec<ObjRef>> {
+        self.with_read(|gc| gc.find_path_to_root(obj))
+    }
+
+    /// 查找让obj存活的最短引用路径及其起点类型
+    pub fn find_retaining_path(&self, obj: impl IntoObjRef) -> Option<(LivenessAnchor, Vec<ObjRef>)> {
+        self.with_read(|gc| gc.find_retaining_path(obj))
+    }
+
+    /// 获取对象的存活状态，线程缓冲区中尚未应用的注册不计入
+    pub fn liveness(&self, obj: impl IntoObjRef) -> Liveness {
+        self.with_read(|gc| gc.liveness(obj))
+    }
+
+    /// 以文本说明对象的大小、标志以及它为什么存活
+    pub fn explain(&self, obj: impl IntoObjRef) -> String {
+        self.with_read(|gc| gc.explain(obj))
+    }
+
+    /// 获取对象的类型标签
+    pub fn get_type_tag(&self, obj: impl IntoObjRef) -> Option<u32> {
+        self.with_read(|gc| gc.get_type_tag(obj))
+    }
+
+    /// 设置回收时报告被清除对象的标签顺序
+    pub fn set_finalization_order(&self, tags_in_order: &[u32]) {
+        self.with_write(|gc| gc.set_finalization_order(tags_in_order))
+    }
+
+    /// 统计带有指定类型标签的对象数量
+    pub fn count_objects_by_tag(&self, tag: u32) -> usize {
+        self.with_read(|gc| gc.count_objects_by_tag(tag))
+    }
+
+    /// 查找所有直接引用指定类型对象的已注册对象
+    pub fn referrers_of_type(&self, tag: u32) -> Vec<(ObjRef, ObjRef)> {
+        self.with_read(|gc| gc.referrers_of_type(tag))
+    }
+
+    /// 按新配置重建回收器内部状态，先应用所有线程缓冲区中的操作，迁移规则同单线程版本
+    pub fn swap_in(&self, config: &SlimeGcConfig) -> c_int {
+        self.with_safepoint(|gc| gc.swap_in(config))
+    }
+
+    /// 检查内部状态是否一致，开启自动修复时发现问题立即修复
+    pub fn verify(&self) -> SlimeGcRepairReport {
+        self.with_write(|gc| gc.verify())
+    }
+
+    /// 设置一致性检查发现问题时是否自动修复
+    pub fn set_auto_repair(&self, enabled: bool) {
+        self.with_write(|gc| gc.set_auto_repair(enabled))
+    }
+
+    /// 修复检查发现的所有问题
+    pub fn repair(&self) -> SlimeGcRepairReport {
+        self.with_write(|gc| gc.repair())
+    }
+}
+
+/// C接口函数，用于创建垃圾回收器
+#[unsafe(no_mangle)]
+pub extern "C" fn slime_gc_new() -> *mut GarbageCollector {
+    Box::into_raw(Box::new(GarbageCollector::new()))
+}
+
+/// C接口函数，用于销毁垃圾回收器
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_destroy(gc: *mut GarbageCollector) {
+    if !gc.is_null() {
+        unsafe {
+            // 销毁GC之前，先释放所有对象，处置队列中尚未取出的对象也一起交给释放回调
+            with_gc(gc, |gc| {
+                gc.clear_disposal_queues();
+                gc.collect_garbage()
+            });
+            drop(Box::from_raw(gc));
+        }
+    }
+}
+
+/// C接口函数，用于注册对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_register_object(gc: *mut GarbageCollector, obj: *mut c_void) {
+    slime_gc_register_object_checked(gc, obj);
+}
+
+/// C接口函数，用于注册对象并返回状态码
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_register_object_checked(gc: *mut GarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.register_object(obj))
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于注册指定大小（字节）的对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_register_object_sized(gc: *mut GarbageCollector, obj: *mut c_void, size_bytes: usize) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.register_object_sized(obj, size_bytes))
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于设置未指定大小的对象按多少字节计入
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_default_object_size(gc: *mut GarbageCollector, size_bytes: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_default_object_size(size_bytes);
+        }
+    }
+}
+
+/// C接口函数，用于获取所有已注册对象的总字节数，包括外部内存
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_get_live_bytes(gc: *const GarbageCollector) -> usize {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).get_live_bytes()
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于设置自动回收阈值（字节）
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_collection_threshold(gc: *mut GarbageCollector, bytes: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_collection_threshold(bytes);
+        }
+    }
+}
+
+/// C接口函数，用于在存活字节数超过阈值时执行回收
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_maybe_collect(gc: *mut GarbageCollector) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.maybe_collect()) as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于注销对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_unregister_object(gc: *mut GarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.unregister_object(obj));
+        }
+    }
+}
+
+/// C接口函数，用于添加对象引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_add_reference(gc: *mut GarbageCollector, from: *mut c_void, to: *mut c_void) {
+    if !gc.is_null() && !from.is_null() && !to.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.add_reference(from, to));
+        }
+    }
+}
+
+/// C接口函数，用于移除对象引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_remove_reference(gc: *mut GarbageCollector, from: *mut c_void, to: *mut c_void) {
+    if !gc.is_null() && !from.is_null() && !to.is_null() {
+        unsafe {
+            (*gc).remove_reference(from, to);
+        }
+    }
+}
+
+/// C接口函数，用于添加弱引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_add_weak_reference(gc: *mut GarbageCollector, from: *mut c_void, to: *mut c_void) {
+    if !gc.is_null() && !from.is_null() && !to.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.add_weak_reference(from, to));
+        }
+    }
+}
+
+/// C接口函数，用于移除弱引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_remove_weak_reference(gc: *mut GarbageCollector, from: *mut c_void, to: *mut c_void) {
+    if !gc.is_null() && !from.is_null() && !to.is_null() {
+        unsafe {
+            (*gc).remove_weak_reference(from, to);
+        }
+    }
+}
+
+/// C接口函数，用于设置弱引用被清除时的回调
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_weak_clear_callback(gc: *mut GarbageCollector, callback: Option<SlimeGcWeakClearCallback>, user_data: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_weak_clear_callback(callback, user_data);
+        }
+    }
+}
+
+/// C接口函数，用于检查对象是否仍被回收器追踪
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_is_alive(gc: *const GarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).is_alive(obj) as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于移除对象的所有引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_clear_references(gc: *mut GarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).clear_references(obj);
+        }
+    }
+}
+
+/// C接口函数，用于获取对象的引用集合大小
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_get_reference_count(gc: *const GarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            if let Some(refs) = (*gc).get_references(obj) {
+                return refs.len() as c_int;
+            }
+        }
+    }
+    0
+}
+
+/// C接口函数，用于批量添加引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_add_references(gc: *mut GarbageCollector, from: *mut c_void, to_list: *const *mut c_void, count: c_int) {
+    if !gc.is_null() && !from.is_null() && !to_list.is_null() && count > 0 {
+        unsafe {
+            let to_slice = std::slice::from_raw_parts(to_list, count as usize);
+            with_gc(gc, |gc| gc.add_references(from, to_slice));
+        }
+    }
+}
+
+/// C接口函数，用于批量移除引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_remove_references(gc: *mut GarbageCollector, from: *mut c_void, to_list: *const *mut c_void, count: c_int) {
+    if !gc.is_null() && !from.is_null() && !to_list.is_null() && count > 0 {
+        unsafe {
+            let to_slice = std::slice::from_raw_parts(to_list, count as usize);
+            (*gc).remove_references(from, to_slice);
+        }
+    }
+}
+
+/// C接口函数，用于标记根对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_mark_root(gc: *mut GarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.mark_root(obj));
+        }
+    }
+}
+
+/// C接口函数，用于取消标记根对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_unmark_root(gc: *mut GarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).unmark_root(obj);
+        }
+    }
+}
+
+/// C接口函数，用于清除所有根对象标记
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_clear_roots(gc: *mut GarbageCollector) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).clear_roots();
+        }
+    }
+}
+
+/// C接口函数，用于打开一个新的根作用域
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_push_scope(gc: *mut GarbageCollector) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).push_root_scope();
+        }
+    }
+}
+
+/// C接口函数，用于在最内层作用域中加入临时根对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_add_scoped_root(gc: *mut GarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.add_scoped_root(obj))
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于关闭最内层作用域
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_pop_scope(gc: *mut GarbageCollector) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).pop_root_scope()
+        }
+    } else {
+        SLIME_GC_NO_SCOPE
+    }
+}
+
+/// C接口函数，用于执行垃圾回收
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_collect(gc: *mut GarbageCollector) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.collect_garbage()) as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于执行垃圾回收并取出被回收的对象
+///
+/// 上次回收的对象未取完时只继续取出；返回值小于capacity说明已全部取完
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_collect_into(gc: *mut GarbageCollector, out_buf: *mut *mut c_void, capacity: c_int) -> c_int {
+    if !gc.is_null() && !out_buf.is_null() && capacity > 0 {
+        unsafe {
+            let out_slice = std::slice::from_raw_parts_mut(out_buf, capacity as usize);
+            with_gc(gc, |gc| gc.collect_into(out_slice)) as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于设置释放回调，传入空回调表示只移除追踪记录
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_free_callback(gc: *mut GarbageCollector, callback: Option<SlimeGcFreeCallback>, user_data: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_free_callback(callback, user_data);
+        }
+    }
+}
+
+/// C接口函数，用于将指向某对象的引用重定向到另一对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_redirect(gc: *mut GarbageCollector, from_obj: *mut c_void, to_obj: *mut c_void, flags: c_int) -> usize {
+    if !gc.is_null() && !from_obj.is_null() && !to_obj.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.redirect(from_obj, to_obj, flags))
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于设置最近注销检测的窗口大小
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_recent_unregister_window(gc: *mut GarbageCollector, window: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_recent_unregister_window(window);
+        }
+    }
+}
+
+/// C接口函数，用于设置使用最近注销对象时的回调
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_recently_unregistered_callback(gc: *mut GarbageCollector, callback: Option<SlimeGcRecentlyUnregisteredCallback>, user_data: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_recently_unregistered_callback(callback, user_data);
+        }
+    }
+}
+
+/// C接口函数，用于获取使用最近注销对象的次数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_get_recently_unregistered_count(gc: *const GarbageCollector) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).get_recently_unregistered_count() as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于订阅地址失效通知
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_subscribe_invalidation(gc: *mut GarbageCollector, callback: Option<SlimeGcInvalidationCallback>, user_data: *mut c_void) -> u64 {
+    match callback {
+        Some(callback) if !gc.is_null() => unsafe {
+            (*gc).subscribe_invalidation(callback, user_data)
+        },
+        _ => 0,
+    }
+}
+
+/// C接口函数，用于取消地址失效通知订阅
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_unsubscribe_invalidation(gc: *mut GarbageCollector, id: u64) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).unsubscribe_invalidation(id);
+        }
+    }
+}
+
+/// C接口函数，用于以“被拥有”的方式添加引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_add_owner(gc: *mut GarbageCollector, child: *mut c_void, owner: *mut c_void) {
+    if !gc.is_null() && !child.is_null() && !owner.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.add_owner(child, owner));
+        }
+    }
+}
+
+/// C接口函数，用于以“被拥有”的方式移除引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_remove_owner(gc: *mut GarbageCollector, child: *mut c_void, owner: *mut c_void) {
+    if !gc.is_null() && !child.is_null() && !owner.is_null() {
+        unsafe {
+            (*gc).remove_owner(child, owner);
+        }
+    }
+}
+
+/// C接口函数，用于批量添加拥有者
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_add_owners(gc: *mut GarbageCollector, child: *mut c_void, owner_list: *const *mut c_void, count: c_int) {
+    if !gc.is_null() && !child.is_null() && !owner_list.is_null() && count > 0 {
+        unsafe {
+            let owner_slice = std::slice::from_raw_parts(owner_list, count as usize);
+            with_gc(gc, |gc| gc.add_owners(child, owner_slice));
+        }
+    }
+}
+
+/// C接口函数，用于批量移除拥有者
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_remove_owners(gc: *mut GarbageCollector, child: *mut c_void, owner_list: *const *mut c_void, count: c_int) {
+    if !gc.is_null() && !child.is_null() && !owner_list.is_null() && count > 0 {
+        unsafe {
+            let owner_slice = std::slice::from_raw_parts(owner_list, count as usize);
+            (*gc).remove_owners(child, owner_slice);
+        }
+    }
+}
+
+/// C接口函数，用于批量查询对象状态
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_query_many(gc: *const GarbageCollector, objs: *const *mut c_void, count: usize, out: *mut SlimeGcObjectQuery) {
+    if !gc.is_null() && !objs.is_null() && !out.is_null() && count > 0 {
+        unsafe {
+            let obj_slice = std::slice::from_raw_parts(objs, count);
+            let out_slice = std::slice::from_raw_parts_mut(out, count);
+            for (dst, result) in out_slice.iter_mut().zip((*gc).query_many(obj_slice)) {
+                *dst = result;
+            }
+        }
+    }
+}
+
+/// C接口函数，用于拆除回收器并将残留状态报告写入缓冲区
+///
+/// 调用后回收器即被销毁；报告以'\0'结尾，超出容量时截断，返回完整报告的字节数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_teardown_report(gc: *mut GarbageCollector, out_buf: *mut c_char, capacity: c_int) -> c_int {
+    if gc.is_null() {
+        return 0;
+    }
+
+    let report = unsafe { Box::from_raw(gc) }.teardown_report().to_string();
+    copy_report(&report, out_buf, capacity)
+}
+
+/// 把报告以'\0'结尾写入缓冲区，超出容量时截断，返回完整报告的字节数
+fn copy_report(report: &str, out_buf: *mut c_char, capacity: c_int) -> c_int {
+    if !out_buf.is_null() && capacity > 0 {
+        let len = report.len().min(capacity as usize - 1);
+        unsafe {
+            std::ptr::copy_nonoverlapping(report.as_ptr(), out_buf as *mut u8, len);
+            *out_buf.add(len) = 0;
+        }
+    }
+    report.len() as c_int
+}
+
+/// C接口函数，用于设置注册对象数量上限
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_max_objects(gc: *mut GarbageCollector, max: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_max_objects(max);
+        }
+    }
+}
+
+/// C接口函数，用于获取当前注册对象数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_get_object_count(gc: *const GarbageCollector) -> usize {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).get_object_count()
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于为接下来的注册预留空位
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_reserve_objects(gc: *mut GarbageCollector, n: usize) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.reserve_objects(n))
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于释放尚未使用的预留空位
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_release_reservation(gc: *mut GarbageCollector, n: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).release_reservation(n);
+        }
+    }
+}
+
+/// C接口函数，用于获取已预留但尚未使用的对象数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_get_reserved_objects(gc: *const GarbageCollector) -> usize {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).get_reserved_objects()
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于创建线程安全的垃圾回收器
+#[unsafe(no_mangle)]
+pub extern "C" fn slime_gc_new_threadsafe() -> *mut ConcurrentGarbageCollector {
+    Box::into_raw(Box::new(ConcurrentGarbageCollector::new()))
+}
+
+/// C接口函数，用于销毁线程安全的垃圾回收器，调用时不能有其他线程仍在使用它
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_destroy(gc: *mut ConcurrentGarbageCollector) {
+    if !gc.is_null() {
+        unsafe {
+            // 销毁GC之前，先释放所有对象，处置队列中尚未取出的对象也一起交给释放回调
+            (*gc).clear_disposal_queues();
+            (*gc).collect_garbage();
+            drop(Box::from_raw(gc));
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中注册对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_register_object(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).register_object(obj)
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中注册指定大小的对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_register_object_sized(gc: *const ConcurrentGarbageCollector, obj: *mut c_void, size_bytes: usize) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).register_object_sized(obj, size_bytes)
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中注销对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_unregister_object(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).unregister_object(obj);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中添加对象引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_add_reference(gc: *const ConcurrentGarbageCollector, from: *mut c_void, to: *mut c_void) {
+    if !gc.is_null() && !from.is_null() && !to.is_null() {
+        unsafe {
+            (*gc).add_reference(from, to);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中移除对象引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_remove_reference(gc: *const ConcurrentGarbageCollector, from: *mut c_void, to: *mut c_void) {
+    if !gc.is_null() && !from.is_null() && !to.is_null() {
+        unsafe {
+            (*gc).remove_reference(from, to);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中批量添加引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_add_references(gc: *const ConcurrentGarbageCollector, from: *mut c_void, to_list: *const *mut c_void, count: c_int) {
+    if !gc.is_null() && !from.is_null() && !to_list.is_null() && count > 0 {
+        unsafe {
+            let to_slice = std::slice::from_raw_parts(to_list, count as usize);
+            (*gc).add_references(from, to_slice);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中批量移除引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_remove_references(gc: *const ConcurrentGarbageCollector, from: *mut c_void, to_list: *const *mut c_void, count: c_int) {
+    if !gc.is_null() && !from.is_null() && !to_list.is_null() && count > 0 {
+        unsafe {
+            let to_slice = std::slice::from_raw_parts(to_list, count as usize);
+            (*gc).remove_references(from, to_slice);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中移除对象的所有引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_clear_references(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).clear_references(obj);
+        }
+    }
+}
+
+/// C接口函数，用于获取线程安全回收器中对象的引用数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_get_reference_count(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).get_reference_count(obj) as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中添加弱引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_add_weak_reference(gc: *const ConcurrentGarbageCollector, from: *mut c_void, to: *mut c_void) {
+    if !gc.is_null() && !from.is_null() && !to.is_null() {
+        unsafe {
+            (*gc).add_weak_reference(from, to);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中移除弱引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_remove_weak_reference(gc: *const ConcurrentGarbageCollector, from: *mut c_void, to: *mut c_void) {
+    if !gc.is_null() && !from.is_null() && !to.is_null() {
+        unsafe {
+            (*gc).remove_weak_reference(from, to);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中标记根对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_mark_root(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).mark_root(obj);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中取消标记根对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_unmark_root(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).unmark_root(obj);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中清除所有根对象标记
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_clear_roots(gc: *const ConcurrentGarbageCollector) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).clear_roots();
+        }
+    }
+}
+
+/// C接口函数，用于设置线程安全回收器的释放回调，回调在写锁释放后调用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_free_callback(gc: *const ConcurrentGarbageCollector, callback: Option<SlimeGcFreeCallback>, user_data: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_free_callback(callback, user_data);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中执行垃圾回收
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_collect(gc: *const ConcurrentGarbageCollector) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).collect_garbage() as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中按分配压力执行回收
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_maybe_collect(gc: *const ConcurrentGarbageCollector) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).maybe_collect() as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于获取线程安全回收器中的注册对象数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_get_object_count(gc: *const ConcurrentGarbageCollector) -> usize {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).get_object_count()
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于获取线程安全回收器中所有对象的总字节数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_get_live_bytes(gc: *const ConcurrentGarbageCollector) -> usize {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).get_live_bytes()
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于检查线程安全回收器中的对象是否仍被追踪
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_is_alive(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).is_alive(obj) as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中设置未指定大小的对象按多少字节计入
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_default_object_size(gc: *const ConcurrentGarbageCollector, size_bytes: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_default_object_size(size_bytes);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中设置自动回收阈值（字节）
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_collection_threshold(gc: *const ConcurrentGarbageCollector, bytes: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_collection_threshold(bytes);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中设置弱引用被清除时的回调
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_weak_clear_callback(gc: *const ConcurrentGarbageCollector, callback: Option<SlimeGcWeakClearCallback>, user_data: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_weak_clear_callback(callback, user_data);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中打开一个新的根作用域
+///
+/// 作用域栈由所有线程共享，多个线程同时使用时须由宿主保证按栈顺序开闭
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_push_scope(gc: *const ConcurrentGarbageCollector) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).push_root_scope();
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器的最内层作用域中加入临时根对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_add_scoped_root(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).add_scoped_root(obj)
+        }
+    } else {
+        SLIME_GC_NO_SCOPE
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中关闭最内层作用域
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_pop_scope(gc: *const ConcurrentGarbageCollector) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).pop_root_scope()
+        }
+    } else {
+        SLIME_GC_NO_SCOPE
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中执行回收并把被回收的对象写入out_buf
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_collect_into(gc: *const ConcurrentGarbageCollector, out_buf: *mut *mut c_void, capacity: c_int) -> c_int {
+    if !gc.is_null() && !out_buf.is_null() && capacity > 0 {
+        unsafe {
+            let out_slice = std::slice::from_raw_parts_mut(out_buf, capacity as usize);
+            (*gc).collect_into(out_slice) as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中将指向from_obj的引用改为指向to_obj
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_redirect(gc: *const ConcurrentGarbageCollector, from_obj: *mut c_void, to_obj: *mut c_void, flags: c_int) -> usize {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).redirect(from_obj, to_obj, flags)
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中设置最近注销检测的窗口大小
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_recent_unregister_window(gc: *const ConcurrentGarbageCollector, window: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_recent_unregister_window(window);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中设置使用最近注销对象时的回调
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_recently_unregistered_callback(gc: *const ConcurrentGarbageCollector, callback: Option<SlimeGcRecentlyUnregisteredCallback>, user_data: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_recently_unregistered_callback(callback, user_data);
+        }
+    }
+}
+
+/// C接口函数，用于获取线程安全的回收器中使用最近注销对象的次数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_get_recently_unregistered_count(gc: *const ConcurrentGarbageCollector) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).get_recently_unregistered_count() as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于订阅线程安全的回收器的地址失效通知，返回订阅编号
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_subscribe_invalidation(gc: *const ConcurrentGarbageCollector, callback: Option<SlimeGcInvalidationCallback>, user_data: *mut c_void) -> u64 {
+    match callback {
+        Some(callback) if !gc.is_null() => unsafe {
+            (*gc).subscribe_invalidation(callback, user_data)
+        },
+        _ => 0,
+    }
+}
+
+/// C接口函数，用于取消线程安全的回收器的地址失效通知订阅
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_unsubscribe_invalidation(gc: *const ConcurrentGarbageCollector, id: u64) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).unsubscribe_invalidation(id);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中以“被拥有”的方式添加引用
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_add_owner(gc: *const ConcurrentGarbageCollector, child: *mut c_void, owner: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).add_owner(child, owner);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中移除child的拥有者
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_remove_owner(gc: *const ConcurrentGarbageCollector, child: *mut c_void, owner: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).remove_owner(child, owner);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中批量添加拥有者
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_add_owners(gc: *const ConcurrentGarbageCollector, child: *mut c_void, owner_list: *const *mut c_void, count: c_int) {
+    if !gc.is_null() && !child.is_null() && !owner_list.is_null() && count > 0 {
+        unsafe {
+            let owner_slice = std::slice::from_raw_parts(owner_list, count as usize);
+            (*gc).add_owners(child, owner_slice);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中批量移除拥有者
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_remove_owners(gc: *const ConcurrentGarbageCollector, child: *mut c_void, owner_list: *const *mut c_void, count: c_int) {
+    if !gc.is_null() && !child.is_null() && !owner_list.is_null() && count > 0 {
+        unsafe {
+            let owner_slice = std::slice::from_raw_parts(owner_list, count as usize);
+            (*gc).remove_owners(child, owner_slice);
+        }
+    }
+}
+
+/// C接口函数，用于批量查询线程安全的回收器中的对象状态
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_query_many(gc: *const ConcurrentGarbageCollector, objs: *const *mut c_void, count: usize, out: *mut SlimeGcObjectQuery) {
+    if !gc.is_null() && !objs.is_null() && !out.is_null() && count > 0 {
+        unsafe {
+            let obj_slice = std::slice::from_raw_parts(objs, count);
+            let out_slice = std::slice::from_raw_parts_mut(out, count);
+            for (dst, result) in out_slice.iter_mut().zip((*gc).query_many(obj_slice)) {
+                *dst = result;
+            }
+        }
+    }
+}
+
+/// C接口函数，用于拆除线程安全的回收器并将残留状态报告写入缓冲区
+///
+/// 调用后回收器即被销毁，调用时不能有其他线程仍在使用它
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_teardown_report(gc: *mut ConcurrentGarbageCollector, out_buf: *mut c_char, capacity: c_int) -> c_int {
+    if gc.is_null() {
+        return 0;
+    }
+
+    let report = unsafe { Box::from_raw(gc) }.teardown_report().to_string();
+    copy_report(&report, out_buf, capacity)
+}
+
+/// C接口函数，用于设置线程安全的回收器的注册对象数量上限
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_max_objects(gc: *const ConcurrentGarbageCollector, max: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_max_objects(max);
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中为接下来的n次注册预留空位
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_reserve_objects(gc: *const ConcurrentGarbageCollector, n: usize) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).reserve_objects(n)
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中释放尚未使用的预留空位
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_release_reservation(gc: *const ConcurrentGarbageCollector, n: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).release_reservation(n);
+        }
+    }
+}
+
+/// C接口函数，用于获取线程安全的回收器中已预留但尚未使用的对象数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_get_reserved_objects(gc: *const ConcurrentGarbageCollector) -> usize {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).get_reserved_objects()
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于获取回收器统计信息
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_get_stats(gc: *const GarbageCollector, out: *mut SlimeGcStats) {
+    if !gc.is_null() && !out.is_null() {
+        unsafe {
+            *out = (*gc).get_stats().into();
+        }
+    }
+}
+
+/// 把导出的对象图写入C字符串路径指定的文件
+fn write_graph_file(path: *const c_char, graph: &str) -> c_int {
+    if path.is_null() {
+        return SLIME_GC_IO_ERROR;
+    }
+    let path = unsafe { CStr::from_ptr(path) };
+    match path.to_str().map(|path| std::fs::write(path, graph)) {
+        Ok(Ok(())) => SLIME_GC_OK,
+        _ => SLIME_GC_IO_ERROR,
+    }
+}
+
+/// C接口流式导出每次写入的节点或边数量
+const EXPORT_CHUNK_OBJECTS: usize = 1024;
+
+/// 以JSON格式把对象图流式写入C字符串路径指定的文件
+fn stream_graph_file(path: *const c_char, export: impl FnOnce(&mut std::io::BufWriter<std::fs::File>) -> std::io::Result<()>) -> c_int {
+    if path.is_null() {
+        return SLIME_GC_IO_ERROR;
+    }
+    let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str() else {
+        return SLIME_GC_IO_ERROR;
+    };
+    let written = std::fs::File::create(path).and_then(|file| {
+        let mut w = std::io::BufWriter::new(file);
+        export(&mut w)?;
+        std::io::Write::flush(&mut w)
+    });
+    if written.is_ok() { SLIME_GC_OK } else { SLIME_GC_IO_ERROR }
+}
+
+/// 把写入转发给C回调的写入器，回调返回非0时写入失败
+struct CallbackWriter {
+    callback: SlimeGcWriteCallback,
+    ctx: *mut c_void,
+}
+
+impl std::io::Write for CallbackWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if (self.callback)(buf.as_ptr(), buf.len(), self.ctx) == 0 {
+            Ok(buf.len())
+        } else {
+            Err(std::io::Error::other("export aborted by the write callback"))
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// C接口函数，用于把对象图以DOT格式写入文件
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_dump_dot(gc: *const GarbageCollector, path: *const c_char) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            write_graph_file(path, &(*gc).dump_graph_dot())
+        }
+    } else {
+        SLIME_GC_IO_ERROR
+    }
+}
+
+/// C接口函数，用于把对象图以JSON格式写入文件
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_dump_json(gc: *const GarbageCollector, path: *const c_char) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            stream_graph_file(path, |w| (*gc).export_json_streaming(w, EXPORT_CHUNK_OBJECTS))
+        }
+    } else {
+        SLIME_GC_IO_ERROR
+    }
+}
+
+/// C接口函数，用于把一条从根对象到obj的引用路径写入缓冲区
+///
+/// 超出容量时只写入前capacity个对象，返回完整路径的长度；obj不可达时返回0
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_retaining_path(gc: *const GarbageCollector, obj: *mut c_void, out_buf: *mut *mut c_void, capacity: c_int) -> c_int {
+    if gc.is_null() || obj.is_null() {
+        return 0;
+    }
+
+    let path = unsafe { (*gc).find_path_to_root(obj) }.unwrap_or_default();
+    copy_path(&path, out_buf, capacity)
+}
+
+/// 把引用路径写入缓冲区，超出容量时只写入前capacity个对象，返回完整路径的长度
+fn copy_path(path: &[ObjRef], out_buf: *mut *mut c_void, capacity: c_int) -> c_int {
+    if !out_buf.is_null() && capacity > 0 {
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out_buf, path.len().min(capacity as usize)) };
+        for (dst, &obj) in out_slice.iter_mut().zip(path) {
+            *dst = obj.as_ptr();
+        }
+    }
+    path.len() as c_int
+}
+
+/// C接口函数，用于获取obj存活的起点类型，返回SLIME_GC_ANCHOR_*；obj未注册或不可达时返回0
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_retaining_anchor(gc: *const GarbageCollector, obj: *mut c_void) -> c_int {
+    if gc.is_null() {
+        return 0;
+    }
+    unsafe { (*gc).find_retaining_path(obj) }.map_or(0, |(anchor, _)| anchor.code())
+}
+
+/// C接口函数，用于获取对象的存活状态，返回SLIME_GC_LIVENESS_*
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_liveness(gc: *const GarbageCollector, obj: *mut c_void) -> c_int {
+    if gc.is_null() {
+        return SLIME_GC_LIVENESS_DEAD;
+    }
+    unsafe { (*gc).liveness(obj) }.code()
+}
+
+/// C接口函数，用于把对象的说明文本写入缓冲区，返回完整文本的字节数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_explain(gc: *const GarbageCollector, obj: *mut c_void, out_buf: *mut c_char, capacity: c_int) -> c_int {
+    if gc.is_null() {
+        return 0;
+    }
+    copy_report(&unsafe { (*gc).explain(obj) }, out_buf, capacity)
+}
+
+/// C接口函数，用于获取线程安全的回收器中obj存活的起点类型
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_retaining_anchor(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) -> c_int {
+    if gc.is_null() {
+        return 0;
+    }
+    unsafe { (*gc).find_retaining_path(obj) }.map_or(0, |(anchor, _)| anchor.code())
+}
+
+/// C接口函数，用于获取线程安全的回收器中对象的存活状态
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_liveness(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) -> c_int {
+    if gc.is_null() {
+        return SLIME_GC_LIVENESS_DEAD;
+    }
+    unsafe { (*gc).liveness(obj) }.code()
+}
+
+/// C接口函数，用于把线程安全的回收器中对象的说明文本写入缓冲区
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_explain(gc: *const ConcurrentGarbageCollector, obj: *mut c_void, out_buf: *mut c_char, capacity: c_int) -> c_int {
+    if gc.is_null() {
+        return 0;
+    }
+    copy_report(&unsafe { (*gc).explain(obj) }, out_buf, capacity)
+}
+
+/// C接口函数，用于检查以'\0'结尾的文本是否符合当前版本的对象图JSON导出格式
+///
+/// 符合时返回SLIME_GC_OK，版本不同或缺少版本时返回SLIME_GC_VERSION_MISMATCH，其他问题返回SLIME_GC_INVALID_FORMAT
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_validate_graph_json(text: *const c_char) -> c_int {
+    if text.is_null() {
+        return SLIME_GC_INVALID_FORMAT;
+    }
+    let Ok(text) = unsafe { CStr::from_ptr(text) }.to_str() else {
+        return SLIME_GC_INVALID_FORMAT;
+    };
+    match validate_graph_json(text) {
+        Ok(()) => SLIME_GC_OK,
+        Err(GraphSchemaError::Version(_)) => SLIME_GC_VERSION_MISMATCH,
+        Err(_) => SLIME_GC_INVALID_FORMAT,
+    }
+}
+
+/// C接口函数，用于以JSON格式把对象图分块交给write_cb
+///
+/// 每块最多包含EXPORT_CHUNK_OBJECTS个节点或边；回调返回非0时立即中止并返回SLIME_GC_IO_ERROR，
+/// 已交出的内容不完整。回调期间不能调用修改回收器的函数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_export_json_cb(gc: *const GarbageCollector, write_cb: Option<SlimeGcWriteCallback>, ctx: *mut c_void) -> c_int {
+    let (false, Some(callback)) = (gc.is_null(), write_cb) else {
+        return SLIME_GC_IO_ERROR;
+    };
+    let mut w = CallbackWriter { callback, ctx };
+    match unsafe { (*gc).export_json_streaming(&mut w, EXPORT_CHUNK_OBJECTS) } {
+        Ok(()) => SLIME_GC_OK,
+        Err(_) => SLIME_GC_IO_ERROR,
+    }
+}
+
+/// C接口函数，用于以JSON格式把线程安全的回收器的对象图分块交给write_cb
+///
+/// 回调期间持有读锁，不能调用修改回收器的函数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_export_json_cb(gc: *const ConcurrentGarbageCollector, write_cb: Option<SlimeGcWriteCallback>, ctx: *mut c_void) -> c_int {
+    let (false, Some(callback)) = (gc.is_null(), write_cb) else {
+        return SLIME_GC_IO_ERROR;
+    };
+    let mut w = CallbackWriter { callback, ctx };
+    match unsafe { (*gc).export_json_streaming(&mut w, EXPORT_CHUNK_OBJECTS) } {
+        Ok(()) => SLIME_GC_OK,
+        Err(_) => SLIME_GC_IO_ERROR,
+    }
+}
+
+/// C接口函数，用于为类型标签设置回收复用回调，传入空回调表示移除
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_recycler(gc: *mut GarbageCollector, type_id: u32, callback: Option<SlimeGcRecyclerCallback>, ctx: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.set_recycler(type_id, callback, ctx));
+        }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中为类型标签设置回收复用回调
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_recycler(gc: *const ConcurrentGarbageCollector, type_id: u32, callback: Option<SlimeGcRecyclerCallback>, ctx: *mut c_void) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_recycler(type_id, callback, ctx);
+        }
+    }
+}
+
+/// C接口函数，用于开启或关闭线程安全的回收器的操作耗时记录
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_op_latency(gc: *const ConcurrentGarbageCollector, enabled: c_int) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_op_latency_enabled(enabled != 0);
+        }
+    }
+}
+
+/// C接口函数，用于获取一类操作的耗时直方图，写入out并返回1；类别无效时返回0
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_op_latency(gc: *const ConcurrentGarbageCollector, op_kind: c_int, out: *mut SlimeGcHistogram) -> c_int {
+    if gc.is_null() || out.is_null() {
+        return 0;
+    }
+    match unsafe { (*gc).op_latency(op_kind) } {
+        Some(histogram) => {
+            unsafe {
+                *out = histogram;
+            }
+            1
+        }
+        None => 0,
+    }
+}
+
+/// C接口函数，用于把Prometheus文本格式的指标写入缓冲区，返回完整文本的字节数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_metrics_text(gc: *const ConcurrentGarbageCollector, out_buf: *mut c_char, capacity: c_int) -> c_int {
+    if gc.is_null() {
+        return 0;
+    }
+    copy_report(&unsafe { (*gc).metrics_text() }, out_buf, capacity)
+}
+
+/// C接口函数，用于执行垃圾回收并返回详细结果
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_collect_detailed(gc: *mut GarbageCollector) -> SlimeGcCollectResult {
+    if gc.is_null() {
+        return SlimeGcCollectResult::default();
+    }
+    unsafe { with_gc(gc, |gc| gc.collect_garbage_detailed()) }
+}
+
+/// C接口函数，用于为类型标签设置容量为capacity的处置队列
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_type_disposal_queue(gc: *mut GarbageCollector, type_id: u32, capacity: usize) {
+    if !gc.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.set_type_disposal_queue(type_id, capacity));
+        }
+    }
+}
+
+/// C接口函数，用于从类型标签的处置队列中按放入顺序取出对象，返回写入数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_pop_disposal(gc: *mut GarbageCollector, type_id: u32, out: *mut *mut c_void, capacity: usize) -> usize {
+    if !gc.is_null() && !out.is_null() && capacity > 0 {
+        unsafe {
+            let out_slice = std::slice::from_raw_parts_mut(out, capacity);
+            with_gc(gc, |gc| gc.pop_disposal(type_id, out_slice))
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中执行垃圾回收并返回详细结果
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_collect_detailed(gc: *const ConcurrentGarbageCollector) -> SlimeGcCollectResult {
+    if gc.is_null() {
+        return SlimeGcCollectResult::default();
+    }
+    unsafe { (*gc).collect_garbage_detailed() }
+}
+
+/// C接口函数，用于在线程安全的回收器中为类型标签设置处置队列
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_type_disposal_queue(gc: *const ConcurrentGarbageCollector, type_id: u32, capacity: usize) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_type_disposal_queue(type_id, capacity);
+        }
+    }
+}
+
+/// C接口函数，用于从线程安全的回收器的处置队列中取出对象，返回写入数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_pop_disposal(gc: *const ConcurrentGarbageCollector, type_id: u32, out: *mut *mut c_void, capacity: usize) -> usize {
+    if !gc.is_null() && !out.is_null() && capacity > 0 {
+        unsafe {
+            let out_slice = std::slice::from_raw_parts_mut(out, capacity);
+            (*gc).pop_disposal(type_id, out_slice)
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于获取线程安全的回收器的统计信息
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_get_stats(gc: *const ConcurrentGarbageCollector, out: *mut SlimeGcStats) {
+    if !gc.is_null() && !out.is_null() {
+        unsafe {
+            *out = (*gc).get_stats().into();
+        }
+    }
+}
+
+/// C接口函数，用于把线程安全的回收器的对象图以DOT格式写入文件
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_dump_dot(gc: *const ConcurrentGarbageCollector, path: *const c_char) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            write_graph_file(path, &(*gc).dump_graph_dot())
+        }
+    } else {
+        SLIME_GC_IO_ERROR
+    }
+}
+
+/// C接口函数，用于把线程安全的回收器的对象图以JSON格式写入文件
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_dump_json(gc: *const ConcurrentGarbageCollector, path: *const c_char) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            stream_graph_file(path, |w| (*gc).export_json_streaming(w, EXPORT_CHUNK_OBJECTS))
+        }
+    } else {
+        SLIME_GC_IO_ERROR
+    }
+}
+
+/// C接口函数，用于把线程安全的回收器中一条从根对象到obj的引用路径写入缓冲区
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_retaining_path(gc: *const ConcurrentGarbageCollector, obj: *mut c_void, out_buf: *mut *mut c_void, capacity: c_int) -> c_int {
+    if gc.is_null() || obj.is_null() {
+        return 0;
+    }
+
+    let path = unsafe { (*gc).find_path_to_root(obj) }.unwrap_or_default();
+    copy_path(&path, out_buf, capacity)
+}
+
+/// C接口函数，用于注册带类型标签的对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_register_object_tagged(gc: *mut GarbageCollector, obj: *mut c_void, tag: u32) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.register_object_tagged(obj, tag))
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于注册指定大小和类型标签的对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_register_object_sized_tagged(gc: *mut GarbageCollector, obj: *mut c_void, size_bytes: usize, tag: u32) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            with_gc(gc, |gc| gc.register_object_with(obj, size_bytes, tag))
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于获取对象的类型标签
+///
+/// 对象已注册时把标签写入out_tag并返回1，否则返回0
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_get_type_tag(gc: *const GarbageCollector, obj: *mut c_void, out_tag: *mut u32) -> c_int {
+    if gc.is_null() || obj.is_null() {
+        return 0;
+    }
+
+    write_type_tag(unsafe { (*gc).get_type_tag(obj) }, out_tag)
+}
+
+/// 标签存在时写入out_tag并返回1，否则返回0
+fn write_type_tag(tag: Option<u32>, out_tag: *mut u32) -> c_int {
+    match tag {
+        Some(tag) => {
+            if !out_tag.is_null() {
+                unsafe {
+                    *out_tag = tag;
+                }
+            }
+            1
+        }
+        None => 0,
+    }
+}
+
+/// C接口函数，用于设置回收时报告被清除对象的标签顺序，count为0表示取消排序
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_finalization_order(gc: *mut GarbageCollector, tags: *const u32, count: c_int) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_finalization_order(tag_slice(tags, count));
+        }
+    }
+}
+
+/// 把C传入的标签数组转为切片，空指针或count不为正时返回空切片
+fn tag_slice<'a>(tags: *const u32, count: c_int) -> &'a [u32] {
+    if !tags.is_null() && count > 0 {
+        unsafe { std::slice::from_raw_parts(tags, count as usize) }
+    } else {
+        &[]
+    }
+}
+
+/// C接口函数，用于统计带有指定类型标签的对象数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_count_by_tag(gc: *const GarbageCollector, tag: u32) -> usize {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).count_objects_by_tag(tag)
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中注册带类型标签的对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_register_object_tagged(gc: *const ConcurrentGarbageCollector, obj: *mut c_void, tag: u32) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).register_object_tagged(obj, tag)
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中注册指定大小和类型标签的对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_register_object_sized_tagged(gc: *const ConcurrentGarbageCollector, obj: *mut c_void, size_bytes: usize, tag: u32) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).register_object_with(obj, size_bytes, tag)
+        }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于获取线程安全的回收器中对象的类型标签
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_get_type_tag(gc: *const ConcurrentGarbageCollector, obj: *mut c_void, out_tag: *mut u32) -> c_int {
+    if gc.is_null() || obj.is_null() {
+        return 0;
+    }
+
+    write_type_tag(unsafe { (*gc).get_type_tag(obj) }, out_tag)
+}
+
+/// C接口函数，用于设置线程安全的回收器报告被清除对象的标签顺序
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_finalization_order(gc: *const ConcurrentGarbageCollector, tags: *const u32, count: c_int) {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).set_finalization_order(tag_slice(tags, count));
+        }
+    }
+}
+
+/// C接口函数，用于统计线程安全的回收器中带有指定类型标签的对象数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_count_by_tag(gc: *const ConcurrentGarbageCollector, tag: u32) -> usize {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).count_objects_by_tag(tag)
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于执行一步增量回收
+///
+/// 每步最多处理budget个对象；本轮未完成时返回-1，完成时返回本轮清除的对象数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_collect_step(gc: *mut GarbageCollector, budget: c_int) -> c_int {
+    if !gc.is_null() {
+        let result = unsafe { with_gc(gc, |gc| gc.collect_step(budget.max(0) as usize)) };
+        if result.finished {
+            result.freed as c_int
+        } else {
+            -1
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中执行一步增量回收
+///
+/// 本轮未完成时返回-1，完成时返回本轮清除的对象数量
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_collect_step(gc: *const ConcurrentGarbageCollector, budget: c_int) -> c_int {
+    if !gc.is_null() {
+        let result = unsafe { (*gc).collect_step(budget.max(0) as usize) };
+        if result.finished {
+            result.freed as c_int
+        } else {
+            -1
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于固定对象，对象未注册时返回SLIME_GC_NOT_REGISTERED
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_pin(gc: *mut GarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).pin(obj)
+        }
+    } else {
+        SLIME_GC_NOT_REGISTERED
+    }
+}
+
+/// C接口函数，用于取消一次固定
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_unpin(gc: *mut GarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).unpin(obj);
+        }
+    }
+}
+
+/// C接口函数，用于获取对象的固定次数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_pin_count(gc: *const GarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).pin_count(obj) as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// 把按类型查找的结果写入缓冲区，超出容量时只写入前capacity条，返回结果总数
+fn copy_referrer_records(pairs: &[(ObjRef, ObjRef)], out: *mut SlimeGcReferrerRecord, capacity: usize) -> usize {
+    if !out.is_null() && capacity > 0 {
+        let out_slice = unsafe { std::slice::from_raw_parts_mut(out, capacity.min(pairs.len())) };
+        for (dst, &(from, to)) in out_slice.iter_mut().zip(pairs) {
+            *dst = SlimeGcReferrerRecord {
+                from: from.as_ptr(),
+                to: to.as_ptr(),
+            };
+        }
+    }
+    pairs.len()
+}
+
+/// C接口函数，用于查找所有直接引用指定类型对象的已注册对象
+///
+/// 结果按地址排序，超出容量时只写入前capacity条，返回结果总数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_find_referrers_of_type(gc: *const GarbageCollector, tag: u32, out: *mut SlimeGcReferrerRecord, capacity: usize) -> usize {
+    if !gc.is_null() {
+        let pairs = unsafe { (*gc).referrers_of_type(tag) };
+        copy_referrer_records(&pairs, out, capacity)
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中查找所有直接引用指定类型对象的已注册对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_find_referrers_of_type(gc: *const ConcurrentGarbageCollector, tag: u32, out: *mut SlimeGcReferrerRecord, capacity: usize) -> usize {
+    if !gc.is_null() {
+        let pairs = unsafe { (*gc).referrers_of_type(tag) };
+        copy_referrer_records(&pairs, out, capacity)
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于创建线程注册缓冲区，用完后须调用slime_gc_thread_buffer_end关闭
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_thread_buffer_begin(gc: *const ConcurrentGarbageCollector) -> *mut ThreadBuffer<'static> {
+    if !gc.is_null() {
+        // 缓冲区借用回收器，调用方须在销毁回收器前关闭所有缓冲区
+        let gc: &'static ConcurrentGarbageCollector = unsafe { &*gc };
+        Box::into_raw(Box::new(gc.thread_buffer_begin()))
+    } else {
+        std::ptr::null_mut()
+    }
+}
+
+/// C接口函数，用于在线程缓冲区中记录一次注册，不获取任何锁
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_buffered_register(buf: *mut ThreadBuffer<'static>, obj: *mut c_void, size_bytes: usize, type_id: u32) {
+    if !buf.is_null() {
+        unsafe {
+            (*buf).register(obj, size_bytes, type_id);
+        }
+    }
+}
+
+/// C接口函数，用于在线程缓冲区中记录一次添加引用，不获取任何锁
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_buffered_add_reference(buf: *mut ThreadBuffer<'static>, from: *mut c_void, to: *mut c_void) {
+    if !buf.is_null() {
+        unsafe {
+            (*buf).add_reference(from, to);
+        }
+    }
+}
+
+/// C接口函数，用于应用线程缓冲区中的操作
+///
+/// gc须为创建该缓冲区的回收器；自上次刷新以来有注册因数量上限失败时返回SLIME_GC_OBJECT_LIMIT
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_thread_buffer_flush(gc: *const ConcurrentGarbageCollector, buf: *mut ThreadBuffer<'static>) -> c_int {
+    if !gc.is_null() && !buf.is_null() {
+        unsafe { (*buf).flush() }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于刷新并关闭线程缓冲区，之后不能再使用该缓冲区
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_thread_buffer_end(gc: *const ConcurrentGarbageCollector, buf: *mut ThreadBuffer<'static>) -> c_int {
+    if !gc.is_null() && !buf.is_null() {
+        unsafe { Box::from_raw(buf).end() }
+    } else {
+        SLIME_GC_OK
+    }
+}
+
+/// C接口函数，用于获取线程安全的回收器获取写锁的次数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_get_write_lock_count(gc: *const ConcurrentGarbageCollector) -> u64 {
+    if !gc.is_null() {
+        unsafe { (*gc).get_write_lock_count() }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于获取当前有待推进的延迟工作类别
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_pending_work(gc: *const GarbageCollector) -> u32 {
+    if !gc.is_null() {
+        unsafe { (*gc).pending_work() }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在max_micros微秒的预算内推进priorities选中的延迟工作
+///
+/// 选中回调类别时用剩余预算派发回调，未派发完的留到下一次pump；
+/// 未选中时回调留到下一次调用其他C接口函数时派发
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_pump(gc: *mut GarbageCollector, max_micros: u64, priorities: u32) -> SlimeGcPumpResult {
+    if gc.is_null() {
+        return SlimeGcPumpResult::default();
+    }
+
+    let start = Instant::now();
+    let max_duration = Duration::from_micros(max_micros);
+    let (mut result, pending) = {
+        let gc = unsafe { &mut *gc };
+        let result = gc.pump(max_duration, priorities);
+        (result, take_pumped_callbacks(gc, priorities))
+    };
+
+    let rest = dispatch_pumped_callbacks(pending, start, max_duration, &mut result);
+    if !rest.is_empty() {
+        unsafe { (*gc).requeue_callbacks(rest) };
+    }
+    result
+}
+
+/// C接口函数，用于获取线程安全的回收器中有待推进的延迟工作类别
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_pending_work(gc: *const ConcurrentGarbageCollector) -> u32 {
+    if !gc.is_null() {
+        unsafe { (*gc).pending_work() }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中推进选中的延迟工作
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_pump(gc: *const ConcurrentGarbageCollector, max_micros: u64, priorities: u32) -> SlimeGcPumpResult {
+    if !gc.is_null() {
+        unsafe { (*gc).pump(Duration::from_micros(max_micros), priorities) }
+    } else {
+        SlimeGcPumpResult::default()
+    }
+}
+
+/// C接口函数，用于按新配置重建回收器内部状态，句柄保持不变；new_cfg为空时使用默认配置
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_swap_in(old: *mut GarbageCollector, new_cfg: *const SlimeGcConfig) -> c_int {
+    if old.is_null() {
+        return SLIME_GC_OK;
+    }
+    let config = if new_cfg.is_null() { SlimeGcConfig::default() } else { unsafe { *new_cfg } };
+    unsafe { with_gc(old, |gc| gc.swap_in(&config)) }
+}
+
+/// C接口函数，用于按新配置重建线程安全的回收器内部状态
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_swap_in(gc: *const ConcurrentGarbageCollector, new_cfg: *const SlimeGcConfig) -> c_int {
+    if gc.is_null() {
+        return SLIME_GC_OK;
+    }
+    let config = if new_cfg.is_null() { SlimeGcConfig::default() } else { unsafe { *new_cfg } };
+    unsafe { (*gc).swap_in(&config) }
+}
+
+/// C接口函数，用于把外部内存归属到对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_add_external_bytes(gc: *mut GarbageCollector, obj: *mut c_void, bytes: usize) -> c_int {
+    if !gc.is_null() {
+        unsafe { (*gc).add_external_bytes(obj, bytes) }
+    } else {
+        SLIME_GC_NOT_REGISTERED
+    }
+}
+
+/// C接口函数，用于从对象归属的外部内存中扣除
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_remove_external_bytes(gc: *mut GarbageCollector, obj: *mut c_void, bytes: usize) {
+    if !gc.is_null() {
+        unsafe { (*gc).remove_external_bytes(obj, bytes) }
+    }
+}
+
+/// C接口函数，用于获取归属于对象的外部内存字节数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_get_external_bytes(gc: *const GarbageCollector, obj: *mut c_void) -> usize {
+    if !gc.is_null() {
+        unsafe { (*gc).get_external_bytes(obj) }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中把外部内存归属到对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_add_external_bytes(gc: *const ConcurrentGarbageCollector, obj: *mut c_void, bytes: usize) -> c_int {
+    if !gc.is_null() {
+        unsafe { (*gc).add_external_bytes(obj, bytes) }
+    } else {
+        SLIME_GC_NOT_REGISTERED
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中从对象归属的外部内存中扣除
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_remove_external_bytes(gc: *const ConcurrentGarbageCollector, obj: *mut c_void, bytes: usize) {
+    if !gc.is_null() {
+        unsafe { (*gc).remove_external_bytes(obj, bytes) }
+    }
+}
+
+/// C接口函数，用于获取线程安全的回收器中归属于对象的外部内存字节数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_get_external_bytes(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) -> usize {
+    if !gc.is_null() {
+        unsafe { (*gc).get_external_bytes(obj) }
+    } else {
+        0
+    }
+}
+
+/// C接口函数，用于设置带原因的弱引用清除回调
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_weak_clear_reason_callback(gc: *mut GarbageCollector, callback: Option<SlimeGcWeakClearReasonCallback>, user_data: *mut c_void) {
+    if !gc.is_null() {
+        unsafe { (*gc).set_weak_clear_reason_callback(callback, user_data) }
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中设置带原因的弱引用清除回调
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_weak_clear_reason_callback(gc: *const ConcurrentGarbageCollector, callback: Option<SlimeGcWeakClearReasonCallback>, user_data: *mut c_void) {
+    if !gc.is_null() {
+        unsafe { (*gc).set_weak_clear_reason_callback(callback, user_data) }
+    }
+}
+
+/// 把报告写入调用方提供的位置，位置为空时不写
+///
+/// # Safety
+/// out_report为空或指向可写的SlimeGcRepairReport
+unsafe fn write_repair_report(report: SlimeGcRepairReport, out_report: *mut SlimeGcRepairReport) {
+    if !out_report.is_null() {
+        unsafe { *out_report = report };
+    }
+}
+
+/// 一致性检查的C接口返回值
+fn verify_status(report: &SlimeGcRepairReport) -> c_int {
+    if report.is_clean() { SLIME_GC_OK } else { SLIME_GC_INCONSISTENT }
+}
+
+/// C接口函数，用于检查内部状态是否一致
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_verify(gc: *mut GarbageCollector, out_report: *mut SlimeGcRepairReport) -> c_int {
+    if gc.is_null() {
+        return SLIME_GC_OK;
+    }
+    let report = unsafe { (*gc).verify() };
+    unsafe { write_repair_report(report, out_report) };
+    verify_status(&report)
+}
+
+/// C接口函数，用于设置一致性检查发现问题时是否自动修复
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_set_auto_repair(gc: *mut GarbageCollector, enabled: c_int) {
+    if !gc.is_null() {
+        unsafe { (*gc).set_auto_repair(enabled != 0) }
+    }
+}
+
+/// C接口函数，用于修复内部状态中的不一致
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_repair(gc: *mut GarbageCollector, out_report: *mut SlimeGcRepairReport) -> c_int {
+    if !gc.is_null() {
+        unsafe { write_repair_report((*gc).repair(), out_report) };
+    }
+    SLIME_GC_OK
+}
+
+/// C接口函数，用于检查线程安全的回收器的内部状态是否一致
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_verify(gc: *const ConcurrentGarbageCollector, out_report: *mut SlimeGcRepairReport) -> c_int {
+    if gc.is_null() {
+        return SLIME_GC_OK;
+    }
+    let report = unsafe { (*gc).verify() };
+    unsafe { write_repair_report(report, out_report) };
+    verify_status(&report)
+}
+
+/// C接口函数，用于设置线程安全的回收器在一致性检查发现问题时是否自动修复
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_set_auto_repair(gc: *const ConcurrentGarbageCollector, enabled: c_int) {
+    if !gc.is_null() {
+        unsafe { (*gc).set_auto_repair(enabled != 0) }
+    }
+}
+
+/// C接口函数，用于修复线程安全的回收器内部状态中的不一致
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_repair(gc: *const ConcurrentGarbageCollector, out_report: *mut SlimeGcRepairReport) -> c_int {
+    if !gc.is_null() {
+        unsafe { write_repair_report((*gc).repair(), out_report) };
+    }
+    SLIME_GC_OK
+}
+
+/// C接口函数，用于在线程安全的回收器中固定对象
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_pin(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() {
+        unsafe {
+            (*gc).pin(obj)
+        }
+    } else {
+        SLIME_GC_NOT_REGISTERED
+    }
+}
+
+/// C接口函数，用于在线程安全的回收器中取消一次固定
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_unpin(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).unpin(obj);
+        }
+    }
+}
+
+/// C接口函数，用于获取线程安全的回收器中对象的固定次数
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn slime_gc_ts_pin_count(gc: *const ConcurrentGarbageCollector, obj: *mut c_void) -> c_int {
+    if !gc.is_null() && !obj.is_null() {
+        unsafe {
+            (*gc).pin_count(obj) as c_int
+        }
+    } else {
+        0
+    }
+}
+
+/// C接口函数表，新函数只追加在末尾，旧版本的表是新版本的前缀
+#[repr(C)]
+pub struct SlimeGcVTable {
+    /// 本表的有效字节数，调用方据此检查字段是否存在
+    pub size: usize,
+    /// 本表对应的ABI版本
+    pub version: u32,
+
+    // 版本1
+    pub new: extern "C" fn() -> *mut GarbageCollector,
+    pub destroy: extern "C" fn(*mut GarbageCollector),
+    pub register_object: extern "C" fn(*mut GarbageCollector, *mut c_void),
+    pub unregister_object: extern "C" fn(*mut GarbageCollector, *mut c_void),
+    pub add_reference: extern "C" fn(*mut GarbageCollector, *mut c_void, *mut c_void),
+    pub remove_reference: extern "C" fn(*mut GarbageCollector, *mut c_void, *mut c_void),
+    pub clear_references: extern "C" fn(*mut GarbageCollector, *mut c_void),
+    pub get_reference_count: extern "C" fn(*const GarbageCollector, *mut c_void) -> c_int,
+    pub add_references: extern "C" fn(*mut GarbageCollector, *mut c_void, *const *mut c_void, c_int),
+    pub remove_references: extern "C" fn(*mut GarbageCollector, *mut c_void, *const *mut c_void, c_int),
+    pub mark_root: extern "C" fn(*mut GarbageCollector, *mut c_void),
+    pub unmark_root: extern "C" fn(*mut GarbageCollector, *mut c_void),
+    pub clear_roots: extern "C" fn(*mut GarbageCollector),
+    pub collect: extern "C" fn(*mut GarbageCollector) -> c_int,
+
+    // 版本2
+    pub redirect: extern "C" fn(*mut GarbageCollector, *mut c_void, *mut c_void, c_int) -> usize,
+    pub set_recent_unregister_window: extern "C" fn(*mut GarbageCollector, usize),
+    pub set_recently_unregistered_callback: extern "C" fn(*mut GarbageCollector, Option<SlimeGcRecentlyUnregisteredCallback>, *mut c_void),
+    pub get_recently_unregistered_count: extern "C" fn(*const GarbageCollector) -> c_int,
+    pub subscribe_invalidation: extern "C" fn(*mut GarbageCollector, Option<SlimeGcInvalidationCallback>, *mut c_void) -> u64,
+    pub unsubscribe_invalidation: extern "C" fn(*mut GarbageCollector, u64),
+    pub add_owner: extern "C" fn(*mut GarbageCollector, *mut c_void, *mut c_void),
+    pub remove_owner: extern "C" fn(*mut GarbageCollector, *mut c_void, *mut c_void),
+    pub add_owners: extern "C" fn(*mut GarbageCollector, *mut c_void, *const *mut c_void, c_int),
+    pub remove_owners: extern "C" fn(*mut GarbageCollector, *mut c_void, *const *mut c_void, c_int),
+    pub query_many: extern "C" fn(*const GarbageCollector, *const *mut c_void, usize, *mut SlimeGcObjectQuery),
+
+    // 版本3
+    pub teardown_report: extern "C" fn(*mut GarbageCollector, *mut c_char, c_int) -> c_int,
+
+    // 版本4
+    pub set_max_objects: extern "C" fn(*mut GarbageCollector, usize),
+    pub get_object_count: extern "C" fn(*const GarbageCollector) -> usize,
+    pub reserve_objects: extern "C" fn(*mut GarbageCollector, usize) -> c_int,
+    pub release_reservation: extern "C" fn(*mut GarbageCollector, usize),
+    pub get_reserved_objects: extern "C" fn(*const GarbageCollector) -> usize,
+    pub register_object_checked: extern "C" fn(*mut GarbageCollector, *mut c_void) -> c_int,
+
+    // 版本5
+    pub collect_into: extern "C" fn(*mut GarbageCollector, *mut *mut c_void, c_int) -> c_int,
+    pub set_free_callback: extern "C" fn(*mut GarbageCollector, Option<SlimeGcFreeCallback>, *mut c_void),
+
+    // 版本6
+    pub push_scope: extern "C" fn(*mut GarbageCollector),
+    pub add_scoped_root: extern "C" fn(*mut GarbageCollector, *mut c_void) -> c_int,
+    pub pop_scope: extern "C" fn(*mut GarbageCollector) -> c_int,
+
+    // 版本7
+    pub register_object_sized: extern "C" fn(*mut GarbageCollector, *mut c_void, usize) -> c_int,
+    pub set_default_object_size: extern "C" fn(*mut GarbageCollector, usize),
+    pub get_live_bytes: extern "C" fn(*const GarbageCollector) -> usize,
+    pub set_collection_threshold: extern "C" fn(*mut GarbageCollector, usize),
+    pub maybe_collect: extern "C" fn(*mut GarbageCollector) -> c_int,
+
+    // 版本8
+    pub add_weak_reference: extern "C" fn(*mut GarbageCollector, *mut c_void, *mut c_void),
+    pub remove_weak_reference: extern "C" fn(*mut GarbageCollector, *mut c_void, *mut c_void),
+    pub set_weak_clear_callback: extern "C" fn(*mut GarbageCollector, Option<SlimeGcWeakClearCallback>, *mut c_void),
+    pub is_alive: extern "C" fn(*const GarbageCollector, *mut c_void) -> c_int,
+
+    // 版本9
+    pub new_threadsafe: extern "C" fn() -> *mut ConcurrentGarbageCollector,
+    pub ts_destroy: extern "C" fn(*mut ConcurrentGarbageCollector),
+    pub ts_register_object: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void) -> c_int,
+    pub ts_register_object_sized: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, usize) -> c_int,
+    pub ts_unregister_object: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void),
+    pub ts_add_reference: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut c_void),
+    pub ts_remove_reference: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut c_void),
+    pub ts_add_references: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *const *mut c_void, c_int),
+    pub ts_remove_references: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *const *mut c_void, c_int),
+    pub ts_clear_references: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void),
+    pub ts_get_reference_count: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void) -> c_int,
+    pub ts_add_weak_reference: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut c_void),
+    pub ts_remove_weak_reference: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut c_void),
+    pub ts_mark_root: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void),
+    pub ts_unmark_root: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void),
+    pub ts_clear_roots: extern "C" fn(*const ConcurrentGarbageCollector),
+    pub ts_set_free_callback: extern "C" fn(*const ConcurrentGarbageCollector, Option<SlimeGcFreeCallback>, *mut c_void),
+    pub ts_collect: extern "C" fn(*const ConcurrentGarbageCollector) -> c_int,
+    pub ts_maybe_collect: extern "C" fn(*const ConcurrentGarbageCollector) -> c_int,
+    pub ts_get_object_count: extern "C" fn(*const ConcurrentGarbageCollector) -> usize,
+    pub ts_get_live_bytes: extern "C" fn(*const ConcurrentGarbageCollector) -> usize,
+    pub ts_is_alive: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void) -> c_int,
+    pub ts_set_default_object_size: extern "C" fn(*const ConcurrentGarbageCollector, usize),
+    pub ts_set_collection_threshold: extern "C" fn(*const ConcurrentGarbageCollector, usize),
+    pub ts_set_weak_clear_callback: extern "C" fn(*const ConcurrentGarbageCollector, Option<SlimeGcWeakClearCallback>, *mut c_void),
+    pub ts_push_scope: extern "C" fn(*const ConcurrentGarbageCollector),
+    pub ts_add_scoped_root: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void) -> c_int,
+    pub ts_pop_scope: extern "C" fn(*const ConcurrentGarbageCollector) -> c_int,
+    pub ts_collect_into: extern "C" fn(*const ConcurrentGarbageCollector, *mut *mut c_void, c_int) -> c_int,
+    pub ts_redirect: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut c_void, c_int) -> usize,
+    pub ts_set_recent_unregister_window: extern "C" fn(*const ConcurrentGarbageCollector, usize),
+    pub ts_set_recently_unregistered_callback: extern "C" fn(*const ConcurrentGarbageCollector, Option<SlimeGcRecentlyUnregisteredCallback>, *mut c_void),
+    pub ts_get_recently_unregistered_count: extern "C" fn(*const ConcurrentGarbageCollector) -> c_int,
+    pub ts_subscribe_invalidation: extern "C" fn(*const ConcurrentGarbageCollector, Option<SlimeGcInvalidationCallback>, *mut c_void) -> u64,
+    pub ts_unsubscribe_invalidation: extern "C" fn(*const ConcurrentGarbageCollector, u64),
+    pub ts_add_owner: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut c_void),
+    pub ts_remove_owner: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut c_void),
+    pub ts_add_owners: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *const *mut c_void, c_int),
+    pub ts_remove_owners: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *const *mut c_void, c_int),
+    pub ts_query_many: extern "C" fn(*const ConcurrentGarbageCollector, *const *mut c_void, usize, *mut SlimeGcObjectQuery),
+    pub ts_teardown_report: extern "C" fn(*mut ConcurrentGarbageCollector, *mut c_char, c_int) -> c_int,
+    pub ts_set_max_objects: extern "C" fn(*const ConcurrentGarbageCollector, usize),
+    pub ts_reserve_objects: extern "C" fn(*const ConcurrentGarbageCollector, usize) -> c_int,
+    pub ts_release_reservation: extern "C" fn(*const ConcurrentGarbageCollector, usize),
+    pub ts_get_reserved_objects: extern "C" fn(*const ConcurrentGarbageCollector) -> usize,
+
+    // 版本10
+    pub get_stats: extern "C" fn(*const GarbageCollector, *mut SlimeGcStats),
+    pub dump_dot: extern "C" fn(*const GarbageCollector, *const c_char) -> c_int,
+    pub dump_json: extern "C" fn(*const GarbageCollector, *const c_char) -> c_int,
+    pub retaining_path: extern "C" fn(*const GarbageCollector, *mut c_void, *mut *mut c_void, c_int) -> c_int,
+    pub ts_get_stats: extern "C" fn(*const ConcurrentGarbageCollector, *mut SlimeGcStats),
+    pub ts_dump_dot: extern "C" fn(*const ConcurrentGarbageCollector, *const c_char) -> c_int,
+    pub ts_dump_json: extern "C" fn(*const ConcurrentGarbageCollector, *const c_char) -> c_int,
+    pub ts_retaining_path: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut *mut c_void, c_int) -> c_int,
+
+    // 版本11
+    pub register_object_tagged: extern "C" fn(*mut GarbageCollector, *mut c_void, u32) -> c_int,
+    pub register_object_sized_tagged: extern "C" fn(*mut GarbageCollector, *mut c_void, usize, u32) -> c_int,
+    pub get_type_tag: extern "C" fn(*const GarbageCollector, *mut c_void, *mut u32) -> c_int,
+    pub set_finalization_order: extern "C" fn(*mut GarbageCollector, *const u32, c_int),
+    pub count_by_tag: extern "C" fn(*const GarbageCollector, u32) -> usize,
+    pub ts_register_object_tagged: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, u32) -> c_int,
+    pub ts_register_object_sized_tagged: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, usize, u32) -> c_int,
+    pub ts_get_type_tag: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut u32) -> c_int,
+    pub ts_set_finalization_order: extern "C" fn(*const ConcurrentGarbageCollector, *const u32, c_int),
+    pub ts_count_by_tag: extern "C" fn(*const ConcurrentGarbageCollector, u32) -> usize,
+
+    // 版本12
+    pub collect_step: extern "C" fn(*mut GarbageCollector, c_int) -> c_int,
+    pub ts_collect_step: extern "C" fn(*const ConcurrentGarbageCollector, c_int) -> c_int,
+
+    // 版本13
+    pub pin: extern "C" fn(*mut GarbageCollector, *mut c_void) -> c_int,
+    pub unpin: extern "C" fn(*mut GarbageCollector, *mut c_void),
+    pub pin_count: extern "C" fn(*const GarbageCollector, *mut c_void) -> c_int,
+    pub ts_pin: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void) -> c_int,
+    pub ts_unpin: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void),
+    pub ts_pin_count: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void) -> c_int,
+
+    // 版本14
+    pub find_referrers_of_type: extern "C" fn(*const GarbageCollector, u32, *mut SlimeGcReferrerRecord, usize) -> usize,
+    pub ts_find_referrers_of_type: extern "C" fn(*const ConcurrentGarbageCollector, u32, *mut SlimeGcReferrerRecord, usize) -> usize,
+
+    // 版本15
+    pub thread_buffer_begin: extern "C" fn(*const ConcurrentGarbageCollector) -> *mut ThreadBuffer<'static>,
+    pub buffered_register: extern "C" fn(*mut ThreadBuffer<'static>, *mut c_void, usize, u32),
+    pub buffered_add_reference: extern "C" fn(*mut ThreadBuffer<'static>, *mut c_void, *mut c_void),
+    pub thread_buffer_flush: extern "C" fn(*const ConcurrentGarbageCollector, *mut ThreadBuffer<'static>) -> c_int,
+    pub thread_buffer_end: extern "C" fn(*const ConcurrentGarbageCollector, *mut ThreadBuffer<'static>) -> c_int,
+    pub ts_get_write_lock_count: extern "C" fn(*const ConcurrentGarbageCollector) -> u64,
+
+    // 版本16
+    pub pending_work: extern "C" fn(*const GarbageCollector) -> u32,
+    pub pump: extern "C" fn(*mut GarbageCollector, u64, u32) -> SlimeGcPumpResult,
+    pub ts_pending_work: extern "C" fn(*const ConcurrentGarbageCollector) -> u32,
+    pub ts_pump: extern "C" fn(*const ConcurrentGarbageCollector, u64, u32) -> SlimeGcPumpResult,
+
+    // 版本17
+    pub swap_in: extern "C" fn(*mut GarbageCollector, *const SlimeGcConfig) -> c_int,
+    pub ts_swap_in: extern "C" fn(*const ConcurrentGarbageCollector, *const SlimeGcConfig) -> c_int,
+
+    // 版本18
+    pub add_external_bytes: extern "C" fn(*mut GarbageCollector, *mut c_void, usize) -> c_int,
+    pub remove_external_bytes: extern "C" fn(*mut GarbageCollector, *mut c_void, usize),
+    pub get_external_bytes: extern "C" fn(*const GarbageCollector, *mut c_void) -> usize,
+    pub ts_add_external_bytes: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, usize) -> c_int,
+    pub ts_remove_external_bytes: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, usize),
+    pub ts_get_external_bytes: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void) -> usize,
+
+    // 版本19
+    pub set_weak_clear_reason_callback: extern "C" fn(*mut GarbageCollector, Option<SlimeGcWeakClearReasonCallback>, *mut c_void),
+    pub ts_set_weak_clear_reason_callback: extern "C" fn(*const ConcurrentGarbageCollector, Option<SlimeGcWeakClearReasonCallback>, *mut c_void),
+
+    // 版本20
+    pub verify: extern "C" fn(*mut GarbageCollector, *mut SlimeGcRepairReport) -> c_int,
+    pub set_auto_repair: extern "C" fn(*mut GarbageCollector, c_int),
+    pub repair: extern "C" fn(*mut GarbageCollector, *mut SlimeGcRepairReport) -> c_int,
+    pub ts_verify: extern "C" fn(*const ConcurrentGarbageCollector, *mut SlimeGcRepairReport) -> c_int,
+    pub ts_set_auto_repair: extern "C" fn(*const ConcurrentGarbageCollector, c_int),
+    pub ts_repair: extern "C" fn(*const ConcurrentGarbageCollector, *mut SlimeGcRepairReport) -> c_int,
+
+    // 版本21
+    pub retaining_anchor: extern "C" fn(*const GarbageCollector, *mut c_void) -> c_int,
+    pub explain: extern "C" fn(*const GarbageCollector, *mut c_void, *mut c_char, c_int) -> c_int,
+    pub ts_retaining_anchor: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void) -> c_int,
+    pub ts_explain: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void, *mut c_char, c_int) -> c_int,
+    pub validate_graph_json: extern "C" fn(*const c_char) -> c_int,
+
+    // 版本22
+    pub collect_detailed: extern "C" fn(*mut GarbageCollector) -> SlimeGcCollectResult,
+    pub set_type_disposal_queue: extern "C" fn(*mut GarbageCollector, u32, usize),
+    pub pop_disposal: extern "C" fn(*mut GarbageCollector, u32, *mut *mut c_void, usize) -> usize,
+    pub ts_collect_detailed: extern "C" fn(*const ConcurrentGarbageCollector) -> SlimeGcCollectResult,
+    pub ts_set_type_disposal_queue: extern "C" fn(*const ConcurrentGarbageCollector, u32, usize),
+    pub ts_pop_disposal: extern "C" fn(*const ConcurrentGarbageCollector, u32, *mut *mut c_void, usize) -> usize,
+
+    // 版本23
+    pub export_json_cb: extern "C" fn(*const GarbageCollector, Option<SlimeGcWriteCallback>, *mut c_void) -> c_int,
+    pub ts_export_json_cb: extern "C" fn(*const ConcurrentGarbageCollector, Option<SlimeGcWriteCallback>, *mut c_void) -> c_int,
+
+    // 版本24
+    pub ts_set_op_latency: extern "C" fn(*const ConcurrentGarbageCollector, c_int),
+    pub ts_op_latency: extern "C" fn(*const ConcurrentGarbageCollector, c_int, *mut SlimeGcHistogram) -> c_int,
+    pub ts_metrics_text: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_char, c_int) -> c_int,
+
+    // 版本25
+    pub set_recycler: extern "C" fn(*mut GarbageCollector, u32, Option<SlimeGcRecyclerCallback>, *mut c_void),
+    pub ts_set_recycler: extern "C" fn(*const ConcurrentGarbageCollector, u32, Option<SlimeGcRecyclerCallback>, *mut c_void),
+
+    // 版本26
+    pub liveness: extern "C" fn(*const GarbageCollector, *mut c_void) -> c_int,
+    pub ts_liveness: extern "C" fn(*const ConcurrentGarbageCollector, *mut c_void) -> c_int,
+}
+
+/// 当前函数表的ABI版本
+pub const SLIME_GC_VTABLE_VERSION: u32 = 26;
+
+/// 各版本函数表的有效字节数，下标为版本号减1
+const VTABLE_SIZES: [usize; SLIME_GC_VTABLE_VERSION as usize] = [
+    std::mem::offset_of!(SlimeGcVTable, redirect),
+    std::mem::offset_of!(SlimeGcVTable, teardown_report),
+    std::mem::offset_of!(SlimeGcVTable, set_max_objects),
+    std::mem::offset_of!(SlimeGcVTable, collect_into),
+    std::mem::offset_of!(SlimeGcVTable, push_scope),
+    std::mem::offset_of!(SlimeGcVTable, register_object_sized),
+    std::mem::offset_of!(SlimeGcVTable, add_weak_reference),
+    std::mem::offset_of!(SlimeGcVTable, new_threadsafe),
+    std::mem::offset_of!(SlimeGcVTable, get_stats),
+    std::mem::offset_of!(SlimeGcVTable, register_object_tagged),
+    std::mem::offset_of!(SlimeGcVTable, collect_step),
+    std::mem::offset_of!(SlimeGcVTable, pin),
+    std::mem::offset_of!(SlimeGcVTable, find_referrers_of_type),
+    std::mem::offset_of!(SlimeGcVTable, thread_buffer_begin),
+    std::mem::offset_of!(SlimeGcVTable, pending_work),
+    std::mem::offset_of!(SlimeGcVTable, swap_in),
+    std::mem::offset_of!(SlimeGcVTable, add_external_bytes),
+    std::mem::offset_of!(SlimeGcVTable, set_weak_clear_reason_callback),
+    std::mem::offset_of!(SlimeGcVTable, verify),
+    std::mem::offset_of!(SlimeGcVTable, retaining_anchor),
+    std::mem::offset_of!(SlimeGcVTable, collect_detailed),
+    std::mem::offset_of!(SlimeGcVTable, export_json_cb),
+    std::mem::offset_of!(SlimeGcVTable, ts_set_op_latency),
+    std::mem::offset_of!(SlimeGcVTable, set_recycler),
+    std::mem::offset_of!(SlimeGcVTable, liveness),
+    std::mem::size_of::<SlimeGcVTable>(),
+];
+
+/// 构造指定版本的函数表，所有版本共用同一组实现
+const fn vtable(version: u32) -> SlimeGcVTable {
+    SlimeGcVTable {
+        size: VTABLE_SIZES[version as usize - 1],
+        version,
+        new: slime_gc_new,
+        destroy: slime_gc_destroy,
+        register_object: slime_gc_register_object,
+        unregister_object: slime_gc_unregister_object,
+        add_reference: slime_gc_add_reference,
+        remove_reference: slime_gc_remove_reference,
+        clear_references: slime_gc_clear_references,
+        get_reference_count: slime_gc_get_reference_count,
+        add_references: slime_gc_add_references,
+        remove_references: slime_gc_remove_references,
+        mark_root: slime_gc_mark_root,
+        unmark_root: slime_gc_unmark_root,
+        clear_roots: slime_gc_clear_roots,
+        collect: slime_gc_collect,
+        redirect: slime_gc_redirect,
+        set_recent_unregister_window: slime_gc_set_recent_unregister_window,
+        set_recently_unregistered_callback: slime_gc_set_recently_unregistered_callback,
+        get_recently_unregistered_count: slime_gc_get_recently_unregistered_count,
+        subscribe_invalidation: slime_gc_subscribe_invalidation,
+        unsubscribe_invalidation: slime_gc_unsubscribe_invalidation,
+        add_owner: slime_gc_add_owner,
+        remove_owner: slime_gc_remove_owner,
+        add_owners: slime_gc_add_owners,
+        remove_owners: slime_gc_remove_owners,
+        query_many: slime_gc_query_many,
+        teardown_report: slime_gc_teardown_report,
+        set_max_objects: slime_gc_set_max_objects,
+        get_object_count: slime_gc_get_object_count,
+        reserve_objects: slime_gc_reserve_objects,
+        release_reservation: slime_gc_release_reservation,
+        get_reserved_objects: slime_gc_get_reserved_objects,
+        register_object_checked: slime_gc_register_object_checked,
+        collect_into: slime_gc_collect_into,
+        set_free_callback: slime_gc_set_free_callback,
+        push_scope: slime_gc_push_scope,
+        add_scoped_root: slime_gc_add_scoped_root,
+        pop_scope: slime_gc_pop_scope,
+        register_object_sized: slime_gc_register_object_sized,
+        set_default_object_size: slime_gc_set_default_object_size,
+        get_live_bytes: slime_gc_get_live_bytes,
+        set_collection_threshold: slime_gc_set_collection_threshold,
+        maybe_collect: slime_gc_maybe_collect,
+        add_weak_reference: slime_gc_add_weak_reference,
+        remove_weak_reference: slime_gc_remove_weak_reference,
+        set_weak_clear_callback: slime_gc_set_weak_clear_callback,
+        is_alive: slime_gc_is_alive,
+        new_threadsafe: slime_gc_new_threadsafe,
+        ts_destroy: slime_gc_ts_destroy,
+        ts_register_object: slime_gc_ts_register_object,
+        ts_register_object_sized: slime_gc_ts_register_object_sized,
+        ts_unregister_object: slime_gc_ts_unregister_object,
+        ts_add_reference: slime_gc_ts_add_reference,
+        ts_remove_reference: slime_gc_ts_remove_reference,
+        ts_add_references: slime_gc_ts_add_references,
+        ts_remove_references: slime_gc_ts_remove_references,
+        ts_clear_references: slime_gc_ts_clear_references,
+        ts_get_reference_count: slime_gc_ts_get_reference_count,
+        ts_add_weak_reference: slime_gc_ts_add_weak_reference,
+        ts_remove_weak_reference: slime_gc_ts_remove_weak_reference,
+        ts_mark_root: slime_gc_ts_mark_root,
+        ts_unmark_root: slime_gc_ts_unmark_root,
+        ts_clear_roots: slime_gc_ts_clear_roots,
+        ts_set_free_callback: slime_gc_ts_set_free_callback,
+        ts_collect: slime_gc_ts_collect,
+        ts_maybe_collect: slime_gc_ts_maybe_collect,
+        ts_get_object_count: slime_gc_ts_get_object_count,
+        ts_get_live_bytes: slime_gc_ts_get_live_bytes,
+        ts_is_alive: slime_gc_ts_is_alive,
+        ts_set_default_object_size: slime_gc_ts_set_default_object_size,
+        ts_set_collection_threshold: slime_gc_ts_set_collection_threshold,
+        ts_set_weak_clear_callback: slime_gc_ts_set_weak_clear_callback,
+        ts_push_scope: slime_gc_ts_push_scope,
+        ts_add_scoped_root: slime_gc_ts_add_scoped_root,
+        ts_pop_scope: slime_gc_ts_pop_scope,
+        ts_collect_into: slime_gc_ts_collect_into,
+        ts_redirect: slime_gc_ts_redirect,
+        ts_set_recent_unregister_window: slime_gc_ts_set_recent_unregister_window,
+        ts_set_recently_unregistered_callback: slime_gc_ts_set_recently_unregistered_callback,
+        ts_get_recently_unregistered_count: slime_gc_ts_get_recently_unregistered_count,
+        ts_subscribe_invalidation: slime_gc_ts_subscribe_invalidation,
+        ts_unsubscribe_invalidation: slime_gc_ts_unsubscribe_invalidation,
+        ts_add_owner: slime_gc_ts_add_owner,
+        ts_remove_owner: slime_gc_ts_remove_owner,
+        ts_add_owners: slime_gc_ts_add_owners,
+        ts_remove_owners: slime_gc_ts_remove_owners,
+        ts_query_many: slime_gc_ts_query_many,
+        ts_teardown_report: slime_gc_ts_teardown_report,
+        ts_set_max_objects: slime_gc_ts_set_max_objects,
+        ts_reserve_objects: slime_gc_ts_reserve_objects,
+        ts_release_reservation: slime_gc_ts_release_reservation,
+        ts_get_reserved_objects: slime_gc_ts_get_reserved_objects,
+        get_stats: slime_gc_get_stats,
+        dump_dot: slime_gc_dump_dot,
+        dump_json: slime_gc_dump_json,
+        retaining_path: slime_gc_retaining_path,
+        ts_get_stats: slime_gc_ts_get_stats,
+        ts_dump_dot: slime_gc_ts_dump_dot,
+        ts_dump_json: slime_gc_ts_dump_json,
+        ts_retaining_path: slime_gc_ts_retaining_path,
+        register_object_tagged: slime_gc_register_object_tagged,
+        register_object_sized_tagged: slime_gc_register_object_sized_tagged,
+        get_type_tag: slime_gc_get_type_tag,
+        set_finalization_order: slime_gc_set_finalization_order,
+        count_by_tag: slime_gc_count_by_tag,
+        ts_register_object_tagged: slime_gc_ts_register_object_tagged,
+        ts_register_object_sized_tagged: slime_gc_ts_register_object_sized_tagged,
+        ts_get_type_tag: slime_gc_ts_get_type_tag,
+        ts_set_finalization_order: slime_gc_ts_set_finalization_order,
+        ts_count_by_tag: slime_gc_ts_count_by_tag,
+        collect_step: slime_gc_collect_step,
+        ts_collect_step: slime_gc_ts_collect_step,
+        pin: slime_gc_pin,
+        unpin: slime_gc_unpin,
+        pin_count: slime_gc_pin_count,
+        ts_pin: slime_gc_ts_pin,
+        ts_unpin: slime_gc_ts_unpin,
+        ts_pin_count: slime_gc_ts_pin_count,
+        find_referrers_of_type: slime_gc_find_referrers_of_type,
+        ts_find_referrers_of_type: slime_gc_ts_find_referrers_of_type,
+        thread_buffer_begin: slime_gc_thread_buffer_begin,
+        buffered_register: slime_gc_buffered_register,
+        buffered_add_reference: slime_gc_buffered_add_reference,
+        thread_buffer_flush: slime_gc_thread_buffer_flush,
+        thread_buffer_end: slime_gc_thread_buffer_end,
+        ts_get_write_lock_count: slime_gc_ts_get_write_lock_count,
+        pending_work: slime_gc_pending_work,
+        pump: slime_gc_pump,
+        ts_pending_work: slime_gc_ts_pending_work,
+        ts_pump: slime_gc_ts_pump,
+        swap_in: slime_gc_swap_in,
+        ts_swap_in: slime_gc_ts_swap_in,
+        add_external_bytes: slime_gc_add_external_bytes,
+        remove_external_bytes: slime_gc_remove_external_bytes,
+        get_external_bytes: slime_gc_get_external_bytes,
+        ts_add_external_bytes: slime_gc_ts_add_external_bytes,
+        ts_remove_external_bytes: slime_gc_ts_remove_external_bytes,
+        ts_get_external_bytes: slime_gc_ts_get_external_bytes,
+        set_weak_clear_reason_callback: slime_gc_set_weak_clear_reason_callback,
+        ts_set_weak_clear_reason_callback: slime_gc_ts_set_weak_clear_reason_callback,
+        verify: slime_gc_verify,
+        set_auto_repair: slime_gc_set_auto_repair,
+        repair: slime_gc_repair,
+        ts_verify: slime_gc_ts_verify,
+        ts_set_auto_repair: slime_gc_ts_set_auto_repair,
+        ts_repair: slime_gc_ts_repair,
+        retaining_anchor: slime_gc_retaining_anchor,
+        explain: slime_gc_explain,
+        ts_retaining_anchor: slime_gc_ts_retaining_anchor,
+        ts_explain: slime_gc_ts_explain,
+        validate_graph_json: slime_gc_validate_graph_json,
+        collect_detailed: slime_gc_collect_detailed,
+        set_type_disposal_queue: slime_gc_set_type_disposal_queue,
+        pop_disposal: slime_gc_pop_disposal,
+        ts_collect_detailed: slime_gc_ts_collect_detailed,
+        ts_set_type_disposal_queue: slime_gc_ts_set_type_disposal_queue,
+        ts_pop_disposal: slime_gc_ts_pop_disposal,
+        export_json_cb: slime_gc_export_json_cb,
+        ts_export_json_cb: slime_gc_ts_export_json_cb,
+        ts_set_op_latency: slime_gc_ts_set_op_latency,
+        ts_op_latency: slime_gc_ts_op_latency,
+        ts_metrics_text: slime_gc_ts_metrics_text,
+        set_recycler: slime_gc_set_recycler,
+        ts_set_recycler: slime_gc_ts_set_recycler,
+        liveness: slime_gc_liveness,
+        ts_liveness: slime_gc_ts_liveness,
+    }
+}
+
+/// 各版本的函数表，下标为版本号减1
+static VTABLES: [SlimeGcVTable; SLIME_GC_VTABLE_VERSION as usize] = [
+    vtable(1),
+    vtable(2),
+    vtable(3),
+    vtable(4),
+    vtable(5),
+    vtable(6),
+    vtable(7),
+    vtable(8),
+    vtable(9),
+    vtable(10),
+    vtable(11),
+    vtable(12),
+    vtable(13),
+    vtable(14),
+    vtable(15),
+    vtable(16),
+    vtable(17),
+    vtable(18),
+    vtable(19),
+    vtable(20),
+    vtable(21),
+    vtable(22),
+    vtable(23),
+    vtable(24),
+    vtable(25),
+    vtable(26),
+];
+
+/// C接口函数，用于获取指定ABI版本的函数表，版本不受支持时返回空指针
+#[unsafe(no_mangle)]
+pub extern "C" fn slime_gc_get_vtable(version: u32) -> *const SlimeGcVTable {
+    if version == 0 || version > SLIME_GC_VTABLE_VERSION {
+        return std::ptr::null();
+    }
+    &VTABLES[version as usize - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试用的假对象地址，回收器从不解引用对象指针
+    fn obj(n: usize) -> *mut c_void {
+        (n * 16) as *mut c_void
+    }
+
+    fn oref(n: usize) -> ObjRef {
+        ObjRef::new(obj(n)).unwrap()
+    }
+
+    // ---- synth-203：空堆与无根回收 ----
+
+    /// 构造一个包含环、链和孤立对象的小对象图
+    fn build_mixed_graph(gc: &mut GarbageCollector) {
+        for n in 1..=6 {
+            gc.register_object(obj(n));
+        }
+        gc.add_reference(obj(1), obj(2));
+        gc.add_reference(obj(2), obj(3));
+        gc.add_reference(obj(3), obj(1));
+        gc.add_reference(obj(4), obj(5));
+    }
+
+    #[test]
+    fn empty_heap_collection_frees_nothing() {
+        let mut gc = GarbageCollector::new();
+        assert_eq!(gc.collect_garbage(), 0);
+
+        gc.register_object(obj(1));
+        assert_eq!(gc.collect_garbage(), 1);
+        assert_eq!(gc.collect_garbage(), 0);
+        assert!(gc.objects.is_empty());
+    }
+
+    #[test]
+    fn empty_roots_collection_clears_every_table() {
+        let mut gc = GarbageCollector::new();
+        build_mixed_graph(&mut gc);
+
+        assert_eq!(gc.collect_garbage(), 6);
+        assert!(gc.objects.is_empty());
+        assert!(gc.references.is_empty());
+    }
+
+    #[test]
+    fn empty_roots_fast_path_matches_general_path() {
+        let mut fast = GarbageCollector::new();
+        build_mixed_graph(&mut fast);
+        let mut general = GarbageCollector::new();
+        build_mixed_graph(&mut general);
+
+        // 直接调用mark_and_sweep强制走标记路径
+        assert_eq!(fast.collect_garbage(), 6);
+        assert_eq!(general.mark_and_sweep().len(), 6);
+        assert_eq!(fast.objects, general.objects);
+        assert_eq!(fast.references, general.references);
+    }
+
+    // ---- synth-212：批量查询 ----
+
+    #[test]
+    fn query_many_matches_single_queries() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=8 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(1));
+        gc.add_reference(obj(1), obj(2));
+        gc.add_reference(obj(1), obj(3));
+        gc.add_reference(obj(4), obj(3));
+
+        let objs: Vec<_> = (1..=9).map(obj).collect();
+        let batch = gc.query_many(&objs);
+        for (&o, result) in objs.iter().zip(&batch) {
+            assert_eq!(gc.query_many(&[o])[0], *result);
+            assert_eq!(result.registered != 0, gc.objects.contains(&o));
+            assert_eq!(result.out_degree as usize, gc.references.get(&o).map_or(0, |r| r.len()));
+        }
+
+        let roots: Vec<_> = batch.iter().map(|r| r.root).collect();
+        assert_eq!(roots, vec![1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let reachable: Vec<_> = batch.iter().map(|r| r.reachable).collect();
+        assert_eq!(reachable, vec![1, 1, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(batch[0].out_degree, 2);
+        assert_eq!(batch[8], SlimeGcObjectQuery::default());
+    }
+
+    #[test]
+    fn query_many_reports_roots_pins_and_scoped_roots_separately() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object_with(obj(1), 64, 7);
+        for n in 2..=4 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(1));
+        gc.pin(obj(2));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(3));
+        gc.add_reference(obj(1), obj(4));
+        gc.add_reference(obj(2), obj(4));
+        gc.add_reference(obj(5), obj(4));
+
+        let batch = gc.query_many(&[obj(1), obj(2), obj(3), obj(4)]);
+        let flags: Vec<_> = batch.iter().map(|r| (r.root, r.pinned, r.scoped_root, r.reachable)).collect();
+        assert_eq!(flags, vec![(1, 0, 0, 1), (0, 1, 0, 1), (0, 0, 1, 1), (0, 0, 0, 1)]);
+        // obj(5)未注册，它的引用不计入入度
+        assert_eq!(batch[3].in_degree, 2);
+        assert_eq!((batch[0].size, batch[0].type_tag), (64, 7));
+        assert_eq!((batch[3].size, batch[3].type_tag), (0, 0));
+    }
+
+    #[test]
+    fn query_many_shares_one_mark_pass() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=5000 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(1));
+        for n in 1..5000 {
+            gc.add_reference(obj(n), obj(n + 1));
+        }
+
+        let objs: Vec<_> = (1..=5000).map(obj).collect();
+        let before = gc.mark_passes.load(Ordering::Relaxed);
+        let batch = gc.query_many(&objs);
+        assert_eq!(gc.mark_passes.load(Ordering::Relaxed) - before, 1);
+        assert!(batch.iter().all(|r| r.reachable == 1));
+
+        let mut out = vec![SlimeGcObjectQuery::default(); objs.len()];
+        let gc = Box::into_raw(Box::new(gc));
+        slime_gc_query_many(gc, objs.as_ptr(), objs.len(), out.as_mut_ptr());
+        assert_eq!(unsafe { &*gc }.mark_passes.load(Ordering::Relaxed) - before, 2);
+        assert_eq!(out, batch);
+        slime_gc_destroy(gc);
+    }
+
+    #[test]
+    fn query_many_through_ffi() {
+        let gc = slime_gc_new();
+        slime_gc_register_object(gc, obj(1));
+        slime_gc_register_object(gc, obj(2));
+        slime_gc_mark_root(gc, obj(1));
+        slime_gc_add_reference(gc, obj(1), obj(2));
+
+        let objs = [obj(1), obj(2), obj(3)];
+        let mut out = [SlimeGcObjectQuery::default(); 3];
+        slime_gc_query_many(gc, objs.as_ptr(), objs.len(), out.as_mut_ptr());
+        assert_eq!(out.to_vec(), unsafe { &*gc }.query_many(&objs));
+        assert_eq!(out[1].reachable, 1);
+        assert_eq!(out[2].registered, 0);
+        slime_gc_destroy(gc);
+    }
+
+    // ---- synth-217：重定向 ----
+
+    #[test]
+    fn redirect_rewrites_incoming_edges() {
+        let mut gc = GarbageCollector::new();
+        let (root, holder, stub, real) = (obj(1), obj(2), obj(3), obj(4));
+        for o in [root, holder, stub, real] {
+            gc.register_object(o);
+        }
+        gc.mark_root(root);
+        gc.add_reference(root, stub);
+        gc.add_reference(root, holder);
+        gc.add_reference(holder, stub);
+
+        assert_eq!(gc.redirect(stub, real, 0), 2);
+        assert!(gc.get_references(root).unwrap().contains(&real));
+        assert!(!gc.get_references(root).unwrap().contains(&stub));
+        assert!(gc.get_references(holder).unwrap().contains(&real));
+        assert!(!gc.get_references(holder).unwrap().contains(&stub));
+
+        // 原对象仍注册但不再可达，目标对象经改写后的引用存活
+        assert_eq!(gc.collect_garbage(), 1);
+        assert!(!gc.objects.contains(&stub));
+        assert!(gc.objects.contains(&real));
+    }
+
+    #[test]
+    fn redirect_rejects_self_and_unregistered_targets() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.register_object(obj(2));
+        gc.add_reference(obj(1), obj(2));
+
+        assert_eq!(gc.redirect(obj(2), obj(2), SLIME_GC_REDIRECT_UNREGISTER), 0);
+        assert_eq!(gc.redirect(obj(2), obj(9), SLIME_GC_REDIRECT_UNREGISTER), 0);
+        assert_eq!(gc.redirect(std::ptr::null_mut(), obj(2), 0), 0);
+        assert!(gc.objects.contains(&obj(2)));
+        assert!(gc.get_references(obj(1)).unwrap().contains(&obj(2)));
+    }
+
+    #[test]
+    fn redirect_chain_and_unregister() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=4 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(2));
+        gc.add_reference(obj(1), obj(2));
+
+        assert_eq!(gc.redirect(obj(2), obj(3), SLIME_GC_REDIRECT_MIGRATE_ROOT | SLIME_GC_REDIRECT_UNREGISTER), 1);
+        assert!(!gc.objects.contains(&obj(2)));
+        assert!(gc.roots.contains(&obj(3)));
+        assert_eq!(gc.redirect(obj(3), obj(4), SLIME_GC_REDIRECT_UNREGISTER), 1);
+        assert!(!gc.objects.contains(&obj(3)));
+        assert!(gc.roots.is_empty());
+        assert_eq!(gc.get_references(obj(1)).unwrap().iter().copied().collect::<Vec<_>>(), vec![obj(4)]);
+    }
+
+    #[test]
+    fn redirect_rewrites_strong_and_weak_edges() {
+        for flags in [0, SLIME_GC_REDIRECT_WEAK] {
+            let mut cleared: Vec<(*mut c_void, *mut c_void)> = Vec::new();
+            let mut gc = GarbageCollector::new();
+            gc.set_weak_clear_callback(Some(record_weak_clear), &mut cleared as *mut _ as *mut c_void);
+            let (root, holder, cache, stub, real) = (obj(1), obj(2), obj(3), obj(4), obj(5));
+            for o in [root, holder, cache, stub, real] {
+                gc.register_object(o);
+            }
+            gc.mark_root(root);
+            gc.add_references(root, &[holder, cache, stub]);
+            gc.add_reference(holder, stub);
+            gc.add_reference(holder, real);
+            gc.add_weak_reference(cache, stub);
+            gc.add_weak_reference(holder, real);
+            gc.add_weak_reference(holder, stub);
+
+            // holder已强引用和弱引用real，它的两条引用都合并而不计入；只有root的强引用和cache的弱引用实际移动
+            let weak = flags & SLIME_GC_REDIRECT_WEAK != 0;
+            let rewritten = gc.redirect(stub, real, flags | SLIME_GC_REDIRECT_UNREGISTER);
+            assert_eq!(rewritten, if weak { 2 } else { 1 });
+            assert!(gc.get_references(holder).unwrap().contains(&real));
+            assert_eq!(gc.get_weak_references(cache).is_some_and(|refs| refs.contains(&real)), weak);
+            assert_eq!(gc.get_weak_references(holder).unwrap().iter().copied().collect::<Vec<_>>(), vec![real]);
+            gc.take_pending_callbacks().run();
+            if weak {
+                assert!(cleared.is_empty());
+            } else {
+                cleared.sort();
+                assert_eq!(cleared, vec![(holder, stub), (cache, stub)]);
+            }
+            assert_indexes_consistent(&gc);
+
+            // 改写后的弱引用不让目标存活，目标被回收时照常清除
+            gc.unregister_object(holder);
+            gc.remove_reference(root, real);
+            assert_eq!(gc.collect_garbage(), 1);
+            gc.take_pending_callbacks().run();
+            assert_eq!(cleared.iter().any(|&(from, to)| from == cache && to == real), weak);
+        }
+    }
+
+    // ---- synth-218：最近注销诊断 ----
+
+    extern "C" fn record_recent_hit(from: *mut c_void, to: *mut c_void, age: u64, user_data: *mut c_void) {
+        let hits = unsafe { &mut *(user_data as *mut Vec<(*mut c_void, *mut c_void, u64)>) };
+        hits.push((from, to, age));
+    }
+
+    #[test]
+    fn recently_unregistered_edge_is_reported_with_age() {
+        let mut hits: Vec<(*mut c_void, *mut c_void, u64)> = Vec::new();
+        let mut gc = GarbageCollector::new();
+        gc.set_recent_unregister_window(4);
+        gc.set_recently_unregistered_callback(Some(record_recent_hit), &mut hits as *mut _ as *mut c_void);
+        for n in 1..=3 {
+            gc.register_object(obj(n));
+        }
+
+        gc.unregister_object(obj(1));
+        gc.unregister_object(obj(2));
+        gc.add_reference(obj(1), obj(3));
+        gc.mark_root(obj(2));
+        gc.take_pending_callbacks().run();
+
+        assert_eq!(gc.get_recently_unregistered_count(), 2);
+        assert_eq!(hits, vec![(obj(1), obj(3), 2), (obj(2), std::ptr::null_mut(), 1)]);
+
+        // 重新注册后不再视为已注销
+        gc.register_object(obj(1));
+        gc.add_reference(obj(1), obj(3));
+        assert_eq!(gc.get_recently_unregistered_count(), 2);
+    }
+
+    #[test]
+    fn recently_unregistered_entries_are_evicted() {
+        let mut gc = GarbageCollector::new();
+        gc.set_recent_unregister_window(2);
+        for n in [1, 2, 3, 4, 6, 9] {
+            gc.register_object(obj(n));
+        }
+        gc.unregister_object(obj(1));
+        gc.unregister_object(obj(2));
+        gc.add_reference(obj(9), obj(1));
+        assert_eq!(gc.get_recently_unregistered_count(), 1);
+
+        // 再注销两个对象后obj(1)的槽位被覆盖
+        gc.unregister_object(obj(3));
+        gc.unregister_object(obj(4));
+        gc.add_reference(obj(9), obj(1));
+        gc.add_reference(obj(9), obj(2));
+        assert_eq!(gc.get_recently_unregistered_count(), 1);
+        assert_eq!(gc.recent_unregisters.index.len(), 2);
+
+        // 同一对象重新注册后再次注销时，旧槽位被覆盖不会移除较新的记录
+        gc.register_object(obj(5));
+        gc.unregister_object(obj(5));
+        gc.register_object(obj(5));
+        gc.unregister_object(obj(5));
+        gc.unregister_object(obj(6));
+        assert_eq!(gc.recent_unregisters.age(Some(oref(5))), Some(2));
+        assert_eq!(gc.recent_unregisters.age(Some(oref(4))), None);
+
+        // 注销未注册的地址不记录，不会挤掉窗口中的记录
+        gc.unregister_object(obj(6));
+        gc.unregister_object(obj(42));
+        assert_eq!(gc.recent_unregisters.age(Some(oref(5))), Some(2));
+        assert_eq!(gc.recent_unregisters.age(Some(oref(42))), None);
+    }
+
+    #[test]
+    fn redirect_unregister_is_recorded() {
+        let mut gc = GarbageCollector::new();
+        gc.set_recent_unregister_window(4);
+        gc.register_object(obj(1));
+        gc.register_object(obj(2));
+        gc.register_object(obj(3));
+        gc.redirect(obj(1), obj(2), SLIME_GC_REDIRECT_UNREGISTER);
+
+        gc.add_reference(obj(3), obj(1));
+        assert_eq!(gc.get_recently_unregistered_count(), 1);
+    }
+
+    // ---- synth-231：地址失效通知 ----
+
+    #[derive(Default)]
+    struct InvalidationLog {
+        gc: Option<*mut GarbageCollector>,
+        unsubscribe: Vec<u64>,
+        batches: Vec<(Vec<*mut c_void>, c_int)>,
+    }
+
+    extern "C" fn record_invalidation(addrs: *const *mut c_void, count: usize, reason: c_int, user_data: *mut c_void) {
+        let log = unsafe { &mut *(user_data as *mut InvalidationLog) };
+        let mut batch = unsafe { std::slice::from_raw_parts(addrs, count) }.to_vec();
+        batch.sort();
+        log.batches.push((batch, reason));
+        if let Some(gc) = log.gc {
+            for id in std::mem::take(&mut log.unsubscribe) {
+                slime_gc_unsubscribe_invalidation(gc, id);
+            }
+        }
+    }
+
+    #[test]
+    fn invalidation_reports_each_reason_once_per_batch() {
+        let mut log = InvalidationLog::default();
+        let mut other = InvalidationLog::default();
+        let mut gc = GarbageCollector::new();
+        gc.subscribe_invalidation(record_invalidation, &mut log as *mut _ as *mut c_void);
+        gc.subscribe_invalidation(record_invalidation, &mut other as *mut _ as *mut c_void);
+        for n in 1..=6 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(5));
+        gc.mark_root(obj(6));
+
+        gc.collect_garbage();
+        gc.unregister_object(obj(5));
+        // 注销未注册的地址不产生通知
+        gc.unregister_object(obj(42));
+        gc.redirect(obj(6), obj(5), SLIME_GC_REDIRECT_UNREGISTER);
+        gc.register_object(obj(5));
+        gc.redirect(obj(6), obj(5), SLIME_GC_REDIRECT_UNREGISTER);
+        gc.take_pending_callbacks().run();
+
+        let expected = vec![
+            ((1..=4).map(obj).collect::<Vec<_>>(), SLIME_GC_INVALIDATE_SWEPT),
+            (vec![obj(5)], SLIME_GC_INVALIDATE_UNREGISTERED),
+            (vec![obj(6)], SLIME_GC_INVALIDATE_REDIRECTED),
+        ];
+        assert_eq!(log.batches, expected);
+        assert_eq!(other.batches, expected);
+    }
+
+    #[test]
+    fn unsubscribe_during_callback() {
+        let gc = slime_gc_new();
+        let mut first = InvalidationLog { gc: Some(gc), ..Default::default() };
+        let mut second = InvalidationLog::default();
+        slime_gc_subscribe_invalidation(gc, Some(record_invalidation), &mut first as *mut _ as *mut c_void);
+        let second_id = slime_gc_subscribe_invalidation(gc, Some(record_invalidation), &mut second as *mut _ as *mut c_void);
+        first.unsubscribe.push(second_id);
+
+        slime_gc_register_object(gc, obj(1));
+        slime_gc_collect(gc);
+        // 本次派发已复制订阅者列表，第二个订阅者仍收到这次通知
+        assert_eq!(first.batches.len(), 1);
+        assert_eq!(second.batches.len(), 1);
+
+        slime_gc_register_object(gc, obj(2));
+        slime_gc_collect(gc);
+        assert_eq!(first.batches.len(), 2);
+        assert_eq!(second.batches.len(), 1);
+        slime_gc_destroy(gc);
+    }
+
+    // ---- synth-236：拥有者方向的引用 ----
+
+    #[test]
+    fn owner_edges_match_canonical_references() {
+        let mut owned = GarbageCollector::new();
+        let mut canonical = GarbageCollector::new();
+        for gc in [&mut owned, &mut canonical] {
+            for n in 1..=5 {
+                gc.register_object(obj(n));
+            }
+            gc.mark_root(obj(1));
+        }
+        owned.add_owner(obj(2), obj(1));
+        owned.add_owners(obj(3), &[obj(2), obj(4)]);
+        owned.add_owner(obj(5), obj(4));
+        canonical.add_reference(obj(1), obj(2));
+        canonical.add_references(obj(2), &[obj(3)]);
+        canonical.add_reference(obj(4), obj(3));
+        canonical.add_reference(obj(4), obj(5));
+
+        let objs: Vec<_> = (1..=5).map(obj).collect();
+        assert_eq!(owned.query_many(&objs), canonical.query_many(&objs));
+
+        owned.remove_owners(obj(3), &[obj(2)]);
+        canonical.remove_reference(obj(2), obj(3));
+        assert_eq!(owned.query_many(&objs), canonical.query_many(&objs));
+        assert_eq!(owned.collect_garbage(), 3);
+        assert_eq!(canonical.collect_garbage(), 3);
+    }
+
+    #[test]
+    fn mixing_owner_and_reference_styles_shares_one_edge() {
+        let gc = slime_gc_new();
+        slime_gc_register_object(gc, obj(1));
+        slime_gc_register_object(gc, obj(2));
+        slime_gc_mark_root(gc, obj(1));
+
+        // 两种写法描述同一条边，引用按集合记录，只保留一条
+        slime_gc_add_reference(gc, obj(1), obj(2));
+        slime_gc_add_owner(gc, obj(2), obj(1));
+        assert_eq!(unsafe { &*gc }.get_references(obj(1)).unwrap().len(), 1);
+
+        slime_gc_remove_owner(gc, obj(2), obj(1));
+        assert!(unsafe { &*gc }.get_references(obj(1)).is_none_or(|refs| refs.is_empty()));
+        assert_eq!(slime_gc_collect(gc), 1);
+        assert!(!unsafe { &*gc }.objects.contains(&obj(2)));
+        slime_gc_destroy(gc);
+    }
+
+    // ---- synth-241：函数表 ----
+
+    #[test]
+    fn vtable_versions_are_prefix_compatible() {
+        assert!(slime_gc_get_vtable(0).is_null());
+        assert!(slime_gc_get_vtable(SLIME_GC_VTABLE_VERSION + 1).is_null());
+
+        let latest = unsafe { &*slime_gc_get_vtable(SLIME_GC_VTABLE_VERSION) };
+        assert_eq!(latest.size, std::mem::size_of::<SlimeGcVTable>());
+        let mut previous = 0;
+        for version in 1..=SLIME_GC_VTABLE_VERSION {
+            let table = unsafe { &*slime_gc_get_vtable(version) };
+            assert_eq!(table.version, version);
+            assert!(table.size > previous);
+            previous = table.size;
+            assert_eq!(table.new as usize, latest.new as usize);
+            assert_eq!(table.collect as usize, slime_gc_collect as extern "C" fn(*mut GarbageCollector) -> c_int as usize);
+        }
+    }
+
+    #[test]
+    fn vtable_drives_full_scenario() {
+        let vt = unsafe { &*slime_gc_get_vtable(SLIME_GC_VTABLE_VERSION) };
+        let gc = (vt.new)();
+        for n in 1..=3 {
+            (vt.register_object)(gc, obj(n));
+        }
+        (vt.mark_root)(gc, obj(1));
+        (vt.add_reference)(gc, obj(1), obj(2));
+        assert_eq!((vt.collect)(gc), 1);
+        assert_eq!((vt.get_object_count)(gc), 2);
+        (vt.destroy)(gc);
+    }
+
+    // ---- synth-242：拆除报告 ----
+
+    #[test]
+    fn clean_collector_produces_empty_report() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.register_object(obj(2));
+        gc.mark_root(obj(1));
+        gc.unmark_root(obj(1));
+        gc.collect_garbage();
+
+        let report = gc.teardown_report();
+        assert!(report.is_empty());
+        assert_eq!(report.to_string(), "teardown report: clean");
+    }
+
+    #[test]
+    fn each_leftover_category_lands_in_its_section() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=3 {
+            gc.register_object(obj(n));
+        }
+        let report = gc.teardown_report();
+        assert_eq!(report.leftover_objects, 3);
+        assert_eq!(report.leftover_object_examples, vec![obj(1), obj(2), obj(3)]);
+        assert_eq!(report.leftover_roots, 0);
+        assert!(report.to_string().contains("objects still registered (3)"));
+
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.mark_root(obj(1));
+        let report = gc.teardown_report();
+        assert_eq!((report.leftover_roots, report.leftover_root_examples.clone()), (1, vec![oref(1)]));
+        assert!(report.to_string().contains("roots never unrooted (1)"));
+
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.register_object_with(obj(2), 0, 7);
+        gc.register_object_with(obj(3), 0, 7);
+        let report = gc.teardown_report();
+        assert_eq!(report.leftover_objects_by_tag, vec![(0, 1), (7, 2)]);
+        assert!(report.to_string().contains("objects by type tag: 0=1 7=2"));
+        assert!(report.leftover_roots_by_set.is_empty());
+
+        let mut gc = GarbageCollector::new();
+        for n in 1..=4 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(1));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(2));
+        gc.add_scoped_root(obj(3));
+        gc.pin(obj(1));
+        gc.hold_for_buffer(1, oref(4));
+        let report = gc.teardown_report();
+        assert_eq!(report.leftover_roots_by_set, vec![(LivenessAnchor::Root, 1), (LivenessAnchor::ScopedRoot, 2),
+                                                      (LivenessAnchor::Pin, 1), (LivenessAnchor::ThreadBuffer, 1)]);
+        assert!(report.to_string().contains("roots by root set: root=1 scoped_root=2 pin=1 thread_buffer=1"));
+
+        // 尚未派发的回调和处置队列中的对象各自单独报告
+        let mut gc = GarbageCollector::new();
+        gc.set_free_callback(Some(ignore_free), std::ptr::null_mut());
+        gc.set_type_disposal_queue(GPU_TAG, 4);
+        gc.register_object(obj(1));
+        gc.register_object_with(obj(2), 0, GPU_TAG);
+        gc.collect_garbage();
+        let report = gc.teardown_report();
+        assert_eq!((report.undispatched_callbacks, report.undrained_disposal_objects, report.leftover_objects), (1, 1, 1));
+        let text = report.to_string();
+        assert!(text.contains("callback batches never dispatched: 1"));
+        assert!(text.contains("disposal queue objects never popped: 1"));
+    }
+
+    #[test]
+    fn threadsafe_teardown_reports_unflushed_buffers_without_applying_them() {
+        let gc = ConcurrentGarbageCollector::new();
+        gc.register_object(obj(1));
+        let buffer = gc.thread_buffer_begin();
+        buffer.register(obj(2), 0, 0);
+        buffer.register(obj(3), 0, 0);
+        buffer.add_reference(obj(2), obj(3));
+        let idle = gc.thread_buffer_begin();
+        // 模拟宿主忘记结束缓冲区
+        std::mem::forget(buffer);
+        std::mem::forget(idle);
+
+        let report = gc.teardown_report();
+        assert_eq!((report.unflushed_thread_buffers, report.unflushed_buffer_ops), (1, 3));
+        // 缓冲的注册没有被应用
+        assert_eq!(report.leftover_objects, 1);
+        let text = report.to_string();
+        let buffers_at = text.find("thread buffers never flushed: 1 (3 operations)").unwrap();
+        assert!(buffers_at < text.find("objects still registered").unwrap());
+    }
+
+    #[test]
+    fn teardown_report_through_ffi_truncates() {
+        let gc = slime_gc_new();
+        slime_gc_register_object(gc, obj(1));
+        let mut buf = [0 as std::os::raw::c_char; 16];
+        let full = slime_gc_teardown_report(gc, buf.as_mut_ptr(), buf.len() as c_int);
+        assert!(full as usize > buf.len());
+        let text = unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) };
+        assert_eq!(text.to_bytes(), b"teardown report");
+    }
+
+    // ---- synth-246：对象数量上限与预留 ----
+
+    #[test]
+    fn object_cap_is_enforced_after_emergency_collection() {
+        let gc = slime_gc_new();
+        slime_gc_set_max_objects(gc, 2);
+        assert_eq!(slime_gc_register_object_checked(gc, obj(1)), SLIME_GC_OK);
+        assert_eq!(slime_gc_register_object_checked(gc, obj(2)), SLIME_GC_OK);
+        slime_gc_mark_root(gc, obj(1));
+
+        // 紧急回收清除了不可达的obj(2)，腾出空位
+        assert_eq!(slime_gc_register_object_checked(gc, obj(3)), SLIME_GC_OK);
+        assert!(!unsafe { &*gc }.objects.contains(&obj(2)));
+
+        slime_gc_mark_root(gc, obj(3));
+        assert_eq!(slime_gc_register_object_checked(gc, obj(4)), SLIME_GC_OBJECT_LIMIT);
+        // 不返回状态码的旧接口同样受上限约束
+        slime_gc_register_object(gc, obj(4));
+        assert_eq!(slime_gc_get_object_count(gc), 2);
+        // 重复注册已有对象不占用新的空位
+        assert_eq!(slime_gc_register_object_checked(gc, obj(1)), SLIME_GC_OK);
+        slime_gc_destroy(gc);
+    }
+
+    #[test]
+    fn reservation_prevents_partial_failure() {
+        let mut gc = GarbageCollector::new();
+        gc.set_max_objects(4);
+        gc.register_object(obj(1));
+        gc.mark_root(obj(1));
+
+        assert_eq!(gc.reserve_objects(4), SLIME_GC_OBJECT_LIMIT);
+        assert_eq!(gc.get_reserved_objects(), 0);
+        assert_eq!(gc.reserve_objects(3), SLIME_GC_OK);
+
+        // 预留的注册一定成功，并依次消耗预留
+        assert_eq!(gc.register_object(obj(2)), SLIME_GC_OK);
+        assert_eq!(gc.register_object(obj(3)), SLIME_GC_OK);
+        assert_eq!(gc.get_reserved_objects(), 1);
+        gc.add_references(obj(1), &[obj(2), obj(3)]);
+        // 剩余的空位仍被预留占用，紧急回收也无法腾出
+        assert_eq!(gc.reserve_objects(1), SLIME_GC_OBJECT_LIMIT);
+        gc.release_reservation(5);
+        assert_eq!(gc.get_reserved_objects(), 0);
+        assert_eq!(gc.reserve_objects(1), SLIME_GC_OK);
+    }
+
+    #[test]
+    fn leaked_reservation_shows_in_teardown_report() {
+        let mut gc = GarbageCollector::new();
+        gc.set_max_objects(8);
+        gc.reserve_objects(2);
+        gc.register_object(obj(1));
+        gc.unregister_object(obj(1));
+
+        let report = gc.teardown_report();
+        assert_eq!(report.outstanding_reservations, 1);
+        assert!(!report.is_empty());
+    }
+
+    #[test]
+    fn stats_report_cap_usage_and_reservations() {
+        let gc = slime_gc_new();
+        slime_gc_set_max_objects(gc, 8);
+        slime_gc_register_object(gc, obj(1));
+        assert_eq!(slime_gc_reserve_objects(gc, 3), SLIME_GC_OK);
+        slime_gc_register_object(gc, obj(2));
+        let mut stats = SlimeGcStats::default();
+        slime_gc_get_stats(gc, &mut stats);
+        assert_eq!((stats.max_objects, stats.reserved_objects, stats.used_object_slots, stats.total_objects), (8, 2, 4, 2));
+        slime_gc_destroy(gc);
+
+        let ts = slime_gc_new_threadsafe();
+        slime_gc_ts_register_object(ts, obj(1));
+        slime_gc_ts_mark_root(ts, obj(1));
+        slime_gc_ts_set_max_objects(ts, 5);
+        assert_eq!(slime_gc_ts_reserve_objects(ts, 4), SLIME_GC_OK);
+        assert_eq!(slime_gc_ts_reserve_objects(ts, 1), SLIME_GC_OBJECT_LIMIT);
+        let mut stats = SlimeGcStats::default();
+        slime_gc_ts_get_stats(ts, &mut stats);
+        assert_eq!((stats.max_objects, stats.reserved_objects, stats.used_object_slots), (5, 4, 5));
+        let stats = unsafe { &*ts }.get_stats();
+        assert_eq!((stats.max_objects, stats.reserved_objects, stats.used_object_slots), (5, 4, 5));
+        slime_gc_ts_destroy(ts);
+    }
+
+    // ---- synth-248：ObjRef ----
+
+    #[test]
+    fn obj_ref_conversions_round_trip_and_reject_null() {
+        let raw = obj(3);
+        let obj_ref = ObjRef::new(raw).unwrap();
+        assert_eq!(obj_ref.as_ptr(), raw);
+        assert_eq!(ObjRef::try_from(raw), Ok(obj_ref));
+        assert_eq!(ObjRef::from(NonNull::new(raw).unwrap()), obj_ref);
+        assert_eq!(NonNull::from(obj_ref).as_ptr(), raw);
+        assert_eq!(obj_ref, raw);
+        assert_eq!(raw, obj_ref);
+        assert_eq!(format!("{obj_ref:?}"), format!("{raw:?}"));
+        assert_eq!(std::mem::size_of::<ObjRef>(), std::mem::size_of::<*mut c_void>());
+        assert_eq!(std::mem::size_of::<Option<ObjRef>>(), std::mem::size_of::<*mut c_void>());
+
+        assert_eq!(ObjRef::new(std::ptr::null_mut()), None);
+        assert_eq!(ObjRef::try_from(std::ptr::null_mut()), Err(NullObjRef));
+        assert_eq!(std::ptr::null_mut::<c_void>().into_obj_ref(), None);
+    }
+
+    #[test]
+    fn obj_ref_sets_can_be_queried_by_raw_pointer() {
+        let set: HashSet<ObjRef> = (1..=3).map(oref).collect();
+        assert!(set.contains(&obj(2)));
+        assert!(!set.contains(&obj(4)));
+        assert!(!set.contains(&std::ptr::null_mut()));
+        let map: HashMap<ObjRef, usize> = [(oref(1), 7)].into();
+        assert_eq!(map.get(&obj(1)), Some(&7));
+    }
+
+    #[test]
+    fn null_arguments_to_rust_api_are_ignored() {
+        let null = std::ptr::null_mut::<c_void>();
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        assert_eq!(gc.register_object(null), SLIME_GC_OK);
+        assert_eq!(gc.register_object(None), SLIME_GC_OK);
+        gc.mark_root(null);
+        gc.add_reference(obj(1), null);
+        gc.add_references(obj(1), &[null, null]);
+        gc.add_owner(null, obj(1));
+
+        assert_eq!(gc.get_object_count(), 1);
+        assert!(gc.roots.is_empty());
+        assert!(gc.get_references(obj(1)).unwrap().is_empty());
+        assert!(gc.get_references(null).is_none());
+        assert_eq!(gc.redirect(null, obj(1), 0), 0);
+        assert_eq!(gc.query_many(&[null]), vec![SlimeGcObjectQuery::default()]);
+        gc.unregister_object(null);
+        assert_eq!(gc.collect_garbage(), 1);
+    }
+
+    #[test]
+    fn null_objects_through_ffi_behave_as_before() {
+        let null = std::ptr::null_mut::<c_void>();
+        let gc = slime_gc_new();
+        slime_gc_register_object(gc, obj(1));
+        slime_gc_register_object(gc, obj(2));
+        slime_gc_register_object(gc, null);
+        assert_eq!(slime_gc_register_object_checked(gc, null), SLIME_GC_OK);
+        assert_eq!(slime_gc_get_object_count(gc), 2);
+
+        // 空指针一侧的引用和根标记都被忽略
+        slime_gc_mark_root(gc, null);
+        slime_gc_add_reference(gc, obj(1), null);
+        slime_gc_add_reference(gc, null, obj(1));
+        slime_gc_add_references(gc, obj(1), [null, obj(2)].as_ptr(), 2);
+        assert_eq!(slime_gc_get_reference_count(gc, obj(1)), 1);
+        assert_eq!(slime_gc_get_reference_count(gc, null), 0);
+        let mut queries = [SlimeGcObjectQuery::default(); 2];
+        slime_gc_query_many(gc, [null, obj(1)].as_ptr(), 2, queries.as_mut_ptr());
+        assert_eq!((queries[0], queries[1].registered), (SlimeGcObjectQuery::default(), 1));
+
+        assert_eq!(slime_gc_redirect(gc, null, obj(1), 0), 0);
+        assert_eq!(slime_gc_redirect(gc, obj(2), null, 0), 0);
+        assert_eq!(slime_gc_get_reference_count(gc, obj(1)), 1);
+
+        slime_gc_unregister_object(gc, null);
+        slime_gc_clear_references(gc, null);
+        slime_gc_remove_reference(gc, obj(1), null);
+        slime_gc_unmark_root(gc, null);
+        assert_eq!(slime_gc_get_object_count(gc), 2);
+        assert_eq!(slime_gc_collect(gc), 2);
+        slime_gc_destroy(gc);
+    }
+
+    /// 引入ObjRef之前的调用方式：对象参数都是原始指针
+    mod compat {
+        use super::*;
+
+        #[test]
+        fn raw_pointer_call_sites_still_compile() {
+            let mut gc = GarbageCollector::new();
+            let objs: Vec<*mut c_void> = (1..=4).map(obj).collect();
+            for &o in &objs {
+                gc.register_object(o);
+            }
+            gc.add_roots(&objs[..1]);
+            gc.add_references(objs[0], &objs[1..2]);
+            gc.add_reference(objs[1], objs[2]);
+            gc.add_owners(objs[3], &objs[2..3]);
+            gc.remove_owner(objs[3], objs[2]);
+
+            // 返回的ObjRef可以直接与原始指针比较，集合也可以直接用原始指针查询
+            assert!(gc.get_references(objs[0]).unwrap().iter().any(|&to| to == objs[1]));
+            assert!(gc.get_references(objs[1]).unwrap().contains(&objs[2]));
+            assert_eq!(gc.query_many(&objs).iter().filter(|q| q.reachable != 0).count(), 3);
+            assert_eq!(gc.collect_garbage(), 1);
+        }
+
+        #[test]
+        fn obj_ref_and_non_null_arguments_mix_with_raw_pointers() {
+            let mut gc = GarbageCollector::new();
+            let (a, b) = (oref(1), NonNull::new(obj(2)).unwrap());
+            gc.register_object(a);
+            gc.register_object(b);
+            gc.register_object(Some(oref(3)));
+            gc.mark_root(obj(1));
+            gc.add_reference(a, b);
+            gc.add_references(b, &[Some(oref(3)), None]);
+
+            assert_eq!(gc.query_many(&[oref(3)])[0].reachable, 1);
+            assert_eq!(gc.get_references(b).unwrap().iter().copied().collect::<Vec<_>>(), vec![oref(3)]);
+            assert_eq!(gc.collect_garbage(), 0);
+        }
+    }
+
+    // ---- synth-251：回调重入 ----
+
+    struct ReentrantState {
+        gc: *mut GarbageCollector,
+        victims: Vec<*mut c_void>,
+        freed: Vec<*mut c_void>,
+    }
+
+    extern "C" fn unregister_victims_on_free(obj: *mut c_void, user_data: *mut c_void) {
+        let state = unsafe { &mut *(user_data as *mut ReentrantState) };
+        state.freed.push(obj);
+        for victim in std::mem::take(&mut state.victims) {
+            slime_gc_unregister_object(state.gc, victim);
+        }
+    }
+
+    extern "C" fn record_free(obj: *mut c_void, user_data: *mut c_void) {
+        let freed = unsafe { &mut *(user_data as *mut Vec<*mut c_void>) };
+        freed.push(obj);
+    }
+
+    #[test]
+    fn rootless_cycle_is_collected() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.register_object(obj(2));
+        gc.add_reference(obj(1), obj(2));
+        gc.add_reference(obj(2), obj(1));
+
+        assert_eq!(gc.collect_garbage(), 2);
+        assert_eq!(gc.get_object_count(), 0);
+        assert!(gc.get_references(obj(1)).is_none());
+    }
+
+    #[test]
+    fn free_callback_can_unregister_other_objects() {
+        let gc = slime_gc_new();
+        let mut state = ReentrantState {
+            gc,
+            victims: vec![obj(10), obj(11)],
+            freed: Vec::new(),
+        };
+        slime_gc_set_free_callback(gc, Some(unregister_victims_on_free), &mut state as *mut ReentrantState as *mut c_void);
+
+        slime_gc_register_object(gc, obj(1));
+        slime_gc_register_object(gc, obj(2));
+        slime_gc_register_object(gc, obj(10));
+        slime_gc_register_object(gc, obj(11));
+        slime_gc_add_reference(gc, obj(1), obj(2));
+        slime_gc_mark_root(gc, obj(10));
+        slime_gc_mark_root(gc, obj(11));
+
+        assert_eq!(slime_gc_collect(gc), 2);
+        assert_eq!(state.freed.len(), 2);
+        assert!(state.victims.is_empty());
+        assert_eq!(slime_gc_get_object_count(gc), 0);
+        assert_eq!(unsafe { &*gc }.get_root_count(), 0);
+
+        slime_gc_destroy(gc);
+    }
+
+    #[test]
+    fn free_callbacks_wait_for_take_in_rust_api() {
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        let mut gc = GarbageCollector::new();
+        gc.set_free_callback(Some(record_free), &mut freed as *mut Vec<*mut c_void> as *mut c_void);
+        gc.register_object(obj(1));
+
+        assert_eq!(gc.collect_garbage(), 1);
+        let pending = gc.take_pending_callbacks();
+        assert!(!pending.is_empty());
+        pending.run();
+        assert_eq!(freed, vec![obj(1)]);
+        assert!(gc.take_pending_callbacks().is_empty());
+    }
+
+    #[test]
+    fn collect_without_callback_queues_nothing() {
+        let gc = slime_gc_new();
+        slime_gc_set_free_callback(gc, None, std::ptr::null_mut());
+        slime_gc_register_object(gc, obj(1));
+        assert_eq!(slime_gc_collect(gc), 1);
+        assert!(unsafe { &mut *gc }.take_pending_callbacks().is_empty());
+        slime_gc_destroy(gc);
+    }
+
+    #[test]
+    fn collect_into_hands_objects_to_caller_without_free_callbacks() {
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        let gc = slime_gc_new();
+        slime_gc_set_free_callback(gc, Some(record_free), &mut freed as *mut Vec<*mut c_void> as *mut c_void);
+        slime_gc_register_object(gc, obj(1));
+        slime_gc_register_object(gc, obj(2));
+
+        let mut out = [std::ptr::null_mut(); 4];
+        assert_eq!(slime_gc_collect_into(gc, out.as_mut_ptr(), 4), 2);
+        assert!(freed.is_empty());
+        assert!(unsafe { &mut *gc }.take_pending_callbacks().is_empty());
+        slime_gc_destroy(gc);
+    }
+
+    #[test]
+    fn collect_into_drains_leftovers_before_a_new_collection() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=3 {
+            gc.register_object(obj(n));
+        }
+
+        let mut out = [std::ptr::null_mut(); 2];
+        assert_eq!(gc.collect_into(&mut out), 2);
+        let mut taken = out.to_vec();
+        // 剩下的一个对象仍待取出，期间注册的对象不会被新的回收清除
+        gc.register_object(obj(4));
+        assert_eq!(gc.collect_into(&mut out), 1);
+        taken.push(out[0]);
+        taken.sort();
+        assert_eq!(taken, vec![obj(1), obj(2), obj(3)]);
+
+        // 取完之后才开始新的回收
+        assert_eq!(gc.collect_into(&mut out), 1);
+        assert_eq!(out[0], obj(4));
+        assert_eq!(gc.collect_into(&mut out), 0);
+    }
+
+    #[test]
+    fn untaken_garbage_shows_in_teardown_report() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.register_object(obj(2));
+        let mut out = [std::ptr::null_mut(); 1];
+        assert_eq!(gc.collect_into(&mut out), 1);
+
+        let report = gc.teardown_report();
+        assert_eq!((report.untaken_garbage, report.leftover_objects), (1, 0));
+        assert!(report.to_string().contains("collected objects never taken: 1"));
+    }
+
+    // ---- synth-252：迭代标记 ----
+
+    #[test]
+    fn million_object_chain_does_not_overflow_stack() {
+        const LEN: usize = 1_000_000;
+        // 在小栈线程中回收，递归标记会在这里栈溢出
+        let handle = std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(|| {
+                let mut gc = GarbageCollector::new();
+                for n in 1..=LEN {
+                    gc.register_object(obj(n));
+                    if n > 1 {
+                        gc.add_reference(obj(n - 1), obj(n));
+                    }
+                }
+                gc.mark_root(obj(1));
+                assert_eq!(gc.collect_garbage(), 0);
+
+                // 从中间断开，后半段全部被回收
+                gc.remove_reference(obj(LEN / 2), obj(LEN / 2 + 1));
+                assert_eq!(gc.collect_garbage(), LEN / 2);
+                assert_eq!(gc.get_object_count(), LEN / 2);
+            })
+            .unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn diamond_and_cycle_graph_marks_exact_set() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=12 {
+            gc.register_object(obj(n));
+        }
+        // 菱形：1 -> 2,3 -> 4，并带有回到1的环
+        gc.add_references(obj(1), &[obj(2), obj(3)]);
+        gc.add_reference(obj(2), obj(4));
+        gc.add_reference(obj(3), obj(4));
+        gc.add_reference(obj(4), obj(1));
+        gc.add_reference(obj(4), obj(5));
+        gc.add_reference(obj(5), obj(5));
+        // 不可达的环和指向可达对象的不可达对象
+        gc.add_reference(obj(6), obj(7));
+        gc.add_reference(obj(7), obj(6));
+        gc.add_reference(obj(8), obj(1));
+        // 宽扇出：所有边都指向同一批对象
+        for n in 9..=12 {
+            gc.add_references(obj(n), &[obj(9), obj(10), obj(11), obj(12)]);
+        }
+        gc.add_reference(obj(5), obj(9));
+        gc.mark_root(obj(1));
+
+        let marked = gc.mark_from_roots();
+        let mut expected: HashSet<_> = (1..=5).map(oref).collect();
+        expected.extend((9..=12).map(oref));
+        assert_eq!(marked, expected);
+        assert_eq!(gc.collect_garbage(), 3);
+        assert_eq!(gc.get_object_count(), 9);
+    }
+
+    // ---- synth-253：根作用域 ----
+
+    #[test]
+    fn nested_scopes_release_roots_in_order() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=3 {
+            gc.register_object(obj(n));
+        }
+
+        assert_eq!(gc.add_scoped_root(obj(1)), SLIME_GC_NO_SCOPE);
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(1));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(2));
+        gc.add_reference(obj(2), obj(3));
+        assert_eq!(gc.get_scope_depth(), 2);
+
+        // 作用域仍打开时回收，作用域根及其引用的对象都存活
+        assert_eq!(gc.collect_garbage(), 0);
+
+        assert_eq!(gc.pop_root_scope(), SLIME_GC_OK);
+        assert_eq!(gc.collect_garbage(), 2);
+        assert!(gc.objects.contains(&obj(1)));
+
+        assert_eq!(gc.pop_root_scope(), SLIME_GC_OK);
+        assert_eq!(gc.pop_root_scope(), SLIME_GC_NO_SCOPE);
+        assert_eq!(gc.collect_garbage(), 1);
+        assert!(gc.scope_root_counts.is_empty());
+    }
+
+    #[test]
+    fn same_pointer_in_multiple_scopes() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(1));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(1));
+        gc.add_scoped_root(obj(1));
+        assert_eq!(gc.scope_root_counts[&oref(1)], 3);
+
+        gc.pop_root_scope();
+        assert_eq!(gc.scope_root_counts[&oref(1)], 1);
+        assert_eq!(gc.collect_garbage(), 0);
+
+        gc.pop_root_scope();
+        assert_eq!(gc.collect_garbage(), 1);
+    }
+
+    #[test]
+    fn unregistering_scoped_root_keeps_scope_layout() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.register_object(obj(2));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(1));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(2));
+
+        gc.unregister_object(obj(1));
+        assert!(!gc.scope_root_counts.contains_key(&oref(1)));
+        assert_eq!(gc.scope_roots, vec![None, Some(oref(2))]);
+
+        // 地址被复用后重新注册，不会因为旧的作用域槽位而存活
+        gc.register_object(obj(1));
+        gc.pop_root_scope();
+        assert_eq!(gc.collect_garbage(), 2);
+        assert_eq!(gc.pop_root_scope(), SLIME_GC_OK);
+        assert!(gc.scope_roots.is_empty());
+        assert!(gc.teardown_report().is_empty());
+    }
+
+    #[test]
+    fn open_scopes_show_up_in_teardown_report() {
+        let mut gc = GarbageCollector::new();
+        gc.push_root_scope();
+        gc.push_root_scope();
+        let report = gc.teardown_report();
+        assert_eq!(report.open_root_scopes, 2);
+        assert!(report.to_string().contains("root scopes never popped: 2"));
+    }
+
+    // ---- synth-254：按字节触发回收 ----
+
+    #[test]
+    fn live_bytes_track_register_unregister_and_sweep() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object_sized(obj(1), 100);
+        gc.register_object_sized(obj(2), 50);
+        gc.set_default_object_size(8);
+        gc.register_object(obj(3));
+        assert_eq!(gc.get_live_bytes(), 158);
+
+        // 重复注册以新的大小为准
+        gc.register_object_sized(obj(2), 30);
+        assert_eq!(gc.get_live_bytes(), 138);
+
+        gc.unregister_object(obj(1));
+        assert_eq!(gc.get_live_bytes(), 38);
+        gc.unregister_object(obj(1));
+        assert_eq!(gc.get_live_bytes(), 38);
+
+        gc.mark_root(obj(3));
+        assert_eq!(gc.collect_garbage(), 1);
+        assert_eq!(gc.get_live_bytes(), 8);
+        assert_eq!(gc.get_object_count(), 1);
+    }
+
+    #[test]
+    fn maybe_collect_respects_adaptive_threshold() {
+        let gc = slime_gc_new();
+        slime_gc_set_collection_threshold(gc, 100);
+        slime_gc_register_object_sized(gc, obj(1), 60);
+        slime_gc_mark_root(gc, obj(1));
+        slime_gc_register_object_sized(gc, obj(2), 40);
+
+        // 恰好等于阈值时不回收
+        assert_eq!(slime_gc_maybe_collect(gc), 0);
+        assert_eq!(slime_gc_get_object_count(gc), 2);
+
+        slime_gc_register_object_sized(gc, obj(3), 1);
+        assert_eq!(slime_gc_maybe_collect(gc), 2);
+        assert_eq!(slime_gc_get_live_bytes(gc), 60);
+        // 存活60字节，阈值为max(100, 120)
+        assert_eq!(unsafe { &*gc }.get_collection_threshold(), 120);
+
+        slime_gc_register_object_sized(gc, obj(4), 60);
+        assert_eq!(slime_gc_maybe_collect(gc), 0);
+        slime_gc_destroy(gc);
+    }
+
+    // ---- synth-255：弱引用 ----
+
+    extern "C" fn record_weak_clear(from: *mut c_void, to: *mut c_void, user_data: *mut c_void) {
+        let cleared = unsafe { &mut *(user_data as *mut Vec<(*mut c_void, *mut c_void)>) };
+        cleared.push((from, to));
+    }
+
+    #[test]
+    fn weakly_reachable_object_is_collected_and_reported() {
+        let mut cleared: Vec<(*mut c_void, *mut c_void)> = Vec::new();
+        let gc = slime_gc_new();
+        slime_gc_set_weak_clear_callback(gc, Some(record_weak_clear), &mut cleared as *mut _ as *mut c_void);
+        slime_gc_register_object(gc, obj(1));
+        slime_gc_register_object(gc, obj(2));
+        slime_gc_mark_root(gc, obj(1));
+        slime_gc_add_weak_reference(gc, obj(1), obj(2));
+
+        assert_eq!(slime_gc_is_alive(gc, obj(2)), 1);
+        assert_eq!(slime_gc_collect(gc), 1);
+        assert_eq!(slime_gc_is_alive(gc, obj(2)), 0);
+        assert_eq!(cleared, vec![(obj(1), obj(2))]);
+        assert!(unsafe { &*gc }.get_weak_references(obj(1)).is_none());
+        slime_gc_destroy(gc);
+    }
+
+    #[test]
+    fn strong_edge_keeps_weak_target_alive() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=3 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(1));
+        gc.add_weak_reference(obj(1), obj(3));
+        gc.add_reference(obj(2), obj(3));
+        gc.add_reference(obj(1), obj(2));
+        gc.add_weak_reference(obj(2), obj(3));
+
+        assert_eq!(gc.collect_garbage(), 0);
+        assert!(gc.get_weak_references(obj(1)).unwrap().contains(&obj(3)));
+
+        // 去掉强引用后只剩弱引用，目标被回收且两条弱引用都被清除
+        gc.remove_reference(obj(2), obj(3));
+        assert_eq!(gc.collect_garbage(), 1);
+        assert!(gc.get_weak_references(obj(1)).is_none());
+        assert!(gc.get_weak_references(obj(2)).is_none());
+    }
+
+    #[test]
+    fn weak_edge_from_collected_object_is_not_reported() {
+        let mut cleared: Vec<(*mut c_void, *mut c_void)> = Vec::new();
+        let mut gc = GarbageCollector::new();
+        gc.set_weak_clear_callback(Some(record_weak_clear), &mut cleared as *mut _ as *mut c_void);
+        for n in 1..=3 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(3));
+        gc.add_weak_reference(obj(1), obj(2));
+        gc.add_weak_reference(obj(1), obj(3));
+
+        assert_eq!(gc.collect_garbage(), 2);
+        gc.take_pending_callbacks().run();
+        assert!(cleared.is_empty());
+        assert!(gc.weak_references.is_empty());
+
+        // 注销目标时也清除并报告弱引用
+        gc.register_object(obj(4));
+        gc.mark_root(obj(4));
+        gc.add_weak_reference(obj(4), obj(3));
+        gc.remove_weak_reference(obj(4), obj(9));
+        gc.unregister_object(obj(3));
+        gc.take_pending_callbacks().run();
+        assert_eq!(cleared, vec![(obj(4), obj(3))]);
+    }
+
+    #[test]
+    fn redirect_leaves_weak_edges_and_teardown_reports_them() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=3 {
+            gc.register_object(obj(n));
+        }
+        gc.add_weak_reference(obj(1), obj(2));
+        gc.add_reference(obj(1), obj(2));
+
+        // 只有强引用被改写，弱引用仍指向原对象
+        assert_eq!(gc.redirect(obj(2), obj(3), 0), 1);
+        assert!(gc.get_weak_references(obj(1)).unwrap().contains(&obj(2)));
+
+        let report = gc.teardown_report();
+        assert_eq!(report.leftover_weak_references, 1);
+        assert_eq!(report.leftover_weak_holder_examples, vec![oref(1)]);
+        assert!(report.to_string().contains("weak references never removed (1)"));
+    }
+
+    // ---- synth-256：反向引用索引 ----
+
+    /// 简单的xorshift伪随机数，保证测试可重复
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+    }
+
+    /// 断言正向引用表与反向索引完全对应
+    fn assert_indexes_consistent(gc: &GarbageCollector) {
+        for (forward, reverse) in [(&gc.references, &gc.referrers), (&gc.weak_references, &gc.weak_referrers)] {
+            for (&from, tos) in forward {
+                for to in tos {
+                    assert!(reverse.get(to).is_some_and(|froms| froms.contains(&from)));
+                }
+            }
+            for (&to, froms) in reverse {
+                assert!(!froms.is_empty());
+                for from in froms {
+                    assert!(forward.get(from).is_some_and(|tos| tos.contains(&to)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn randomized_operations_keep_reverse_index_consistent() {
+        const OBJECTS: usize = 64;
+        let mut rng = XorShift(0x5eed_1234_abcd_0001);
+        let mut gc = GarbageCollector::new();
+        for n in 1..=OBJECTS {
+            gc.register_object(obj(n));
+        }
+
+        for step in 0..20_000 {
+            let a = obj(rng.next(OBJECTS) + 1);
+            let b = obj(rng.next(OBJECTS) + 1);
+            match rng.next(12) {
+                0..=3 => gc.add_reference(a, b),
+                4 => gc.remove_reference(a, b),
+                5 => gc.add_references(a, &[b, obj(rng.next(OBJECTS) + 1)]),
+                6 => gc.remove_references(a, &[b]),
+                7 => gc.clear_references(a),
+                8 => gc.add_weak_reference(a, b),
+                9 => gc.unregister_object(a),
+                10 => {
+                    gc.register_object(a);
+                    if rng.next(2) == 0 {
+                        gc.mark_root(a);
+                    } else {
+                        gc.unmark_root(a);
+                    }
+                }
+                _ => {
+                    if step % 4 == 0 {
+                        gc.collect_garbage();
+                    } else if step % 4 == 1 {
+                        gc.redirect(a, b, 0);
+                    } else {
+                        gc.remove_weak_reference(a, b);
+                    }
+                }
+            }
+            assert_indexes_consistent(&gc);
+        }
+
+        gc.clear_roots();
+        gc.collect_garbage();
+        assert_indexes_consistent(&gc);
+        assert!(gc.referrers.is_empty());
+        assert!(gc.weak_referrers.is_empty());
+    }
+
+    // ---- synth-257：线程安全回收器 ----
+
+    #[test]
+    fn threads_hammering_shared_collector_keep_counts() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 2_000;
+        let gc = ConcurrentGarbageCollector::new();
+
+        std::thread::scope(|scope| {
+            for t in 0..THREADS {
+                let gc = &gc;
+                scope.spawn(move || {
+                    let base = (t + 1) * 1_000_000;
+                    let root = obj(base);
+                    // 其他线程随时可能回收，注册和设为根须在同一次加锁内完成
+                    gc.with_write(|gc| {
+                        gc.register_object(root);
+                        gc.mark_root(root);
+                    });
+                    for i in 1..=PER_THREAD {
+                        let o = obj(base + i);
+                        // 偶数对象先挂到根上再注册，注册后立即可达；奇数对象成为垃圾
+                        if i % 2 == 0 {
+                            gc.add_reference(root, o);
+                        }
+                        gc.register_object(o);
+                        if i % 256 == 0 {
+                            gc.collect_garbage();
+                        }
+                    }
+                });
+            }
+        });
+
+        gc.collect_garbage();
+        let live = THREADS * (PER_THREAD / 2 + 1);
+        assert_eq!(gc.get_object_count(), live);
+        assert_eq!(gc.get_root_count(), THREADS);
+        assert_eq!(gc.get_reference_count(obj(1_000_000)), PER_THREAD / 2);
+
+        gc.clear_roots();
+        assert_eq!(gc.collect_garbage(), live);
+        assert!(gc.teardown_report().is_empty());
+    }
+
+    extern "C" fn reenter_threadsafe_on_free(obj: *mut c_void, user_data: *mut c_void) {
+        let gc = user_data as *const ConcurrentGarbageCollector;
+        // 回调在写锁释放后调用，读写重入都不会死锁
+        assert_eq!(slime_gc_ts_is_alive(gc, obj), 0);
+        slime_gc_ts_unregister_object(gc, (obj as usize + 1) as *mut c_void);
+    }
+
+    #[test]
+    fn threadsafe_callbacks_may_reenter_collector() {
+        let gc = slime_gc_new_threadsafe();
+        slime_gc_ts_set_free_callback(gc, Some(reenter_threadsafe_on_free), gc as *mut c_void);
+        slime_gc_ts_register_object(gc, obj(1));
+        slime_gc_ts_register_object(gc, (obj(1) as usize + 1) as *mut c_void);
+        slime_gc_ts_mark_root(gc, (obj(1) as usize + 1) as *mut c_void);
+
+        assert_eq!(slime_gc_ts_collect(gc), 1);
+        assert_eq!(slime_gc_ts_get_object_count(gc), 0);
+        slime_gc_ts_destroy(gc);
+    }
+
+    #[test]
+    fn threadsafe_ffi_covers_scopes_owners_and_teardown() {
+        let gc = slime_gc_new_threadsafe();
+        slime_gc_ts_register_object_sized(gc, obj(1), 16);
+        slime_gc_ts_register_object(gc, obj(2));
+        slime_gc_ts_register_object(gc, obj(3));
+        slime_gc_ts_add_owner(gc, obj(3), obj(2));
+        assert_eq!(slime_gc_ts_get_live_bytes(gc), 16);
+
+        slime_gc_ts_push_scope(gc);
+        assert_eq!(slime_gc_ts_add_scoped_root(gc, obj(2)), SLIME_GC_OK);
+        assert_eq!(slime_gc_ts_collect(gc), 1);
+        assert_eq!(slime_gc_ts_is_alive(gc, obj(3)), 1);
+
+        assert_eq!(slime_gc_ts_pop_scope(gc), SLIME_GC_OK);
+        assert_eq!(slime_gc_ts_pop_scope(gc), SLIME_GC_NO_SCOPE);
+        let mut out = [std::ptr::null_mut(); 4];
+        assert_eq!(slime_gc_ts_collect_into(gc, out.as_mut_ptr(), 4), 2);
+
+        slime_gc_ts_register_object(gc, obj(5));
+        slime_gc_ts_mark_root(gc, obj(5));
+        assert_eq!(slime_gc_ts_reserve_objects(gc, 2), SLIME_GC_OK);
+        assert_eq!(slime_gc_ts_reserve_objects(std::ptr::null(), 2), SLIME_GC_OK);
+        let mut buf = [0 as std::os::raw::c_char; 512];
+        slime_gc_ts_teardown_report(gc, buf.as_mut_ptr(), buf.len() as c_int);
+        let text = unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) }.to_str().unwrap();
+        assert!(text.contains("roots never unrooted (1)"));
+        assert!(text.contains("reservations never released: 2"));
+    }
+
+    // ---- synth-258：统计、导出与引用路径 ----
+
+    /// 测试用的最小JSON值，只覆盖导出格式用到的类型
+    #[derive(Debug, PartialEq)]
+    enum Json {
+        Bool(bool),
+        Number(u64),
+        Str(String),
+        Array(Vec<Json>),
+        Object(Vec<(String, Json)>),
+    }
+
+    impl Json {
+        fn get(&self, key: &str) -> &Json {
+            match self {
+                Json::Object(fields) => &fields.iter().find(|(k, _)| k == key).unwrap().1,
+                _ => panic!("not an object"),
+            }
+        }
+
+        fn len(&self) -> usize {
+            match self {
+                Json::Array(items) => items.len(),
+                _ => panic!("not an array"),
+            }
+        }
+    }
+
+    fn parse_json(text: &str) -> Json {
+        fn value(bytes: &[u8], pos: &mut usize) -> Json {
+            match bytes[*pos] {
+                b'{' => {
+                    *pos += 1;
+                    let mut fields = Vec::new();
+                    while bytes[*pos] != b'}' {
+                        let Json::Str(key) = value(bytes, pos) else { panic!("bad key") };
+                        assert_eq!(bytes[*pos], b':');
+                        *pos += 1;
+                        fields.push((key, value(bytes, pos)));
+                        if bytes[*pos] == b',' {
+                            *pos += 1;
+                        }
+                    }
+                    *pos += 1;
+                    Json::Object(fields)
+                }
+                b'[' => {
+                    *pos += 1;
+                    let mut items = Vec::new();
+                    while bytes[*pos] != b']' {
+                        items.push(value(bytes, pos));
+                        if bytes[*pos] == b',' {
+                            *pos += 1;
+                        }
+                    }
+                    *pos += 1;
+                    Json::Array(items)
+                }
+                b'"' => {
+                    let end = *pos + 1 + bytes[*pos + 1..].iter().position(|&b| b == b'"').unwrap();
+                    let text = String::from_utf8(bytes[*pos + 1..end].to_vec()).unwrap();
+                    *pos = end + 1;
+                    Json::Str(text)
+                }
+                b't' => {
+                    *pos += 4;
+                    Json::Bool(true)
+                }
+                b'f' => {
+                    *pos += 5;
+                    Json::Bool(false)
+                }
+                _ => {
+                    let start = *pos;
+                    while bytes[*pos].is_ascii_digit() {
+                        *pos += 1;
+                    }
+                    Json::Number(std::str::from_utf8(&bytes[start..*pos]).unwrap().parse().unwrap())
+                }
+            }
+        }
+
+        let mut pos = 0;
+        let parsed = value(text.trim_end().as_bytes(), &mut pos);
+        assert_eq!(pos, text.trim_end().len());
+        parsed
+    }
+
+    fn build_export_graph() -> GarbageCollector {
+        let mut gc = GarbageCollector::new();
+        gc.register_object_sized(obj(1), 32);
+        gc.register_object(obj(2));
+        gc.register_object(obj(3));
+        gc.register_object(obj(4));
+        gc.mark_root(obj(1));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(4));
+        gc.add_reference(obj(1), obj(2));
+        gc.add_reference(obj(2), obj(3));
+        // 指向未注册对象的引用不出现在导出中
+        gc.add_reference(obj(3), obj(99));
+        gc.add_weak_reference(obj(4), obj(1));
+        gc
+    }
+
+    #[test]
+    fn json_export_round_trips() {
+        let mut gc = build_export_graph();
+        gc.register_object(obj(5));
+        gc.add_owner(obj(5), obj(2));
+        let json = parse_json(&gc.dump_graph_json());
+
+        assert_eq!(json.get("format_version"), &Json::Number(SLIME_GC_GRAPH_FORMAT_VERSION));
+        assert_eq!(json.get("nodes").len(), 5);
+        assert_eq!(json.get("edges").len(), 4);
+        assert_eq!((edges_of_kind(&json, "strong"), edges_of_kind(&json, "owner"), edges_of_kind(&json, "weak")), (2, 1, 1));
+        let Json::Array(edges) = json.get("edges") else { unreachable!() };
+        let owner_edge = edges.iter().find(|edge| edge.get("kind") == &Json::Str("owner".into())).unwrap();
+        assert_eq!(owner_edge.get("from"), &Json::Str(format!("{:p}", obj(2))));
+        assert_eq!(owner_edge.get("to"), &Json::Str(format!("{:p}", obj(5))));
+        let Json::Array(nodes) = json.get("nodes") else { unreachable!() };
+        assert_eq!(nodes[0].get("id"), &Json::Str(format!("{:p}", obj(1))));
+        assert_eq!(nodes[0].get("flags").get("root"), &Json::Bool(true));
+        assert_eq!(nodes[0].get("size"), &Json::Number(32));
+        assert_eq!(nodes[1].get("size"), &Json::Number(0));
+        assert_eq!(nodes[3].get("flags").get("root"), &Json::Bool(false));
+        assert_eq!(nodes[3].get("flags").get("scoped_root"), &Json::Bool(true));
+        assert_eq!(gc.get_stats().total_edges, edges_of_kind(&json, "strong") + edges_of_kind(&json, "owner"));
+        assert_eq!(validate_graph_json(&gc.dump_graph_json()), Ok(()));
+
+        // 引用被移除后owner记录随之消失，之后以add_reference重新添加的是普通强引用
+        gc.remove_reference(obj(2), obj(5));
+        gc.add_reference(obj(2), obj(5));
+        let json = parse_json(&gc.dump_graph_json());
+        assert_eq!((edges_of_kind(&json, "strong"), edges_of_kind(&json, "owner")), (3, 0));
+        // 重定向后owner引用仍是owner引用
+        gc.register_object(obj(6));
+        gc.add_owner(obj(6), obj(1));
+        gc.redirect(obj(6), obj(5), 0);
+        let json = parse_json(&gc.dump_graph_json());
+        assert_eq!((edges_of_kind(&json, "strong"), edges_of_kind(&json, "owner")), (3, 1));
+        gc.unregister_object(obj(5));
+        assert!(gc.owner_edges.is_empty());
+    }
+
+    /// 导出中指定种类的边的数量
+    fn edges_of_kind(json: &Json, kind: &str) -> usize {
+        let Json::Array(edges) = json.get("edges") else { panic!("edges is not an array") };
+        edges.iter().filter(|edge| edge.get("kind") == &Json::Str(kind.to_string())).count()
+    }
+
+    #[test]
+    fn dot_export_lists_nodes_and_edges() {
+        let gc = build_export_graph();
+        let dot = gc.dump_graph_dot();
+        assert!(dot.starts_with("digraph slime_gc {"));
+        assert!(dot.trim_end().ends_with('}'));
+        assert_eq!(dot.matches(" -> ").count(), 3);
+        assert_eq!(dot.lines().filter(|line| line.contains("shape=box")).count(), 2);
+        assert!(dot.contains(&format!("\"{:p}\" -> \"{:p}\"", obj(1), obj(2))));
+        assert!(!dot.contains(&format!("{:p}", obj(99))));
+    }
+
+    #[test]
+    fn stats_and_dump_files_through_ffi() {
+        let gc = Box::into_raw(Box::new(build_export_graph()));
+        assert_eq!(slime_gc_pop_scope(gc), SLIME_GC_OK);
+        assert_eq!(slime_gc_collect(gc), 1);
+
+        let mut stats = SlimeGcStats::default();
+        slime_gc_get_stats(gc, &mut stats);
+        assert_eq!((stats.total_objects, stats.root_count, stats.total_edges, stats.collected_last_cycle), (3, 1, 2, 1));
+
+        let path = std::env::temp_dir().join(format!("slime_gc_dump_{}.json", std::process::id()));
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(slime_gc_dump_json(gc, c_path.as_ptr()), SLIME_GC_OK);
+        let json = parse_json(&std::fs::read_to_string(&path).unwrap());
+        assert_eq!((json.get("nodes").len(), edges_of_kind(&json, "strong"), edges_of_kind(&json, "weak")), (3, 2, 0));
+        std::fs::remove_file(&path).unwrap();
+
+        let bad = std::ffi::CString::new("/nonexistent-dir/slime_gc.dot").unwrap();
+        assert_eq!(slime_gc_dump_dot(gc, bad.as_ptr()), SLIME_GC_IO_ERROR);
+        assert_eq!(slime_gc_dump_dot(gc, std::ptr::null()), SLIME_GC_IO_ERROR);
+        slime_gc_destroy(gc);
+    }
+
+    #[test]
+    fn retaining_path_is_shortest_and_truncates() {
+        let mut gc = build_export_graph();
+        gc.register_object(obj(5));
+        assert_eq!(gc.find_path_to_root(obj(3)), Some(vec![oref(1), oref(2), oref(3)]));
+        assert_eq!(gc.find_path_to_root(obj(4)), Some(vec![oref(4)]));
+        assert_eq!(gc.find_path_to_root(obj(5)), None);
+
+        // 加一条捷径后返回更短的路径
+        gc.add_reference(obj(1), obj(3));
+        assert_eq!(gc.find_path_to_root(obj(3)), Some(vec![oref(1), oref(3)]));
+
+        gc.remove_reference(obj(1), obj(3));
+        let gc = Box::into_raw(Box::new(gc));
+        let mut out = [std::ptr::null_mut(); 2];
+        assert_eq!(slime_gc_retaining_path(gc, obj(3), out.as_mut_ptr(), 2), 3);
+        assert_eq!(out, [obj(1), obj(2)]);
+        assert_eq!(slime_gc_retaining_path(gc, obj(5), out.as_mut_ptr(), 2), 0);
+        slime_gc_destroy(gc);
+    }
+
+    #[test]
+    fn threadsafe_stats_dump_and_retaining_path() {
+        let gc = slime_gc_new_threadsafe();
+        slime_gc_ts_register_object(gc, obj(1));
+        slime_gc_ts_register_object(gc, obj(2));
+        slime_gc_ts_register_object(gc, obj(3));
+        slime_gc_ts_add_owner(gc, obj(3), obj(2));
+        slime_gc_ts_push_scope(gc);
+        assert_eq!(slime_gc_ts_add_scoped_root(gc, obj(2)), SLIME_GC_OK);
+
+        let mut path = [std::ptr::null_mut(); 4];
+        assert_eq!(slime_gc_ts_retaining_path(gc, obj(3), path.as_mut_ptr(), 4), 2);
+        assert_eq!(&path[..2], &[obj(2), obj(3)]);
+        assert_eq!(slime_gc_ts_retaining_path(gc, obj(1), path.as_mut_ptr(), 4), 0);
+
+        assert_eq!(slime_gc_ts_collect(gc), 1);
+        let mut stats = SlimeGcStats::default();
+        slime_gc_ts_get_stats(gc, &mut stats);
+        assert_eq!((stats.total_objects, stats.total_edges, stats.collected_last_cycle), (2, 1, 1));
+
+        assert_eq!(slime_gc_ts_dump_json(gc, std::ptr::null()), SLIME_GC_IO_ERROR);
+        assert_eq!(slime_gc_ts_dump_dot(std::ptr::null(), std::ptr::null()), SLIME_GC_IO_ERROR);
+        slime_gc_ts_destroy(gc);
+    }
+
+    // ---- synth-259：类型标签与按标签排序的清除 ----
+
+    extern "C" fn record_free_tag(obj: *mut c_void, user_data: *mut c_void) {
+        let freed = unsafe { &mut *(user_data as *mut Vec<*mut c_void>) };
+        freed.push(obj);
+    }
+
+    #[test]
+    fn cross_tag_cycle_is_finalized_in_tag_order() {
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        let gc = slime_gc_new();
+        slime_gc_set_free_callback(gc, Some(record_free_tag), &mut freed as *mut _ as *mut c_void);
+        // 三种标签的对象构成一个环，另有一个未设置标签的对象
+        slime_gc_register_object_tagged(gc, obj(1), 30);
+        slime_gc_register_object_tagged(gc, obj(2), 10);
+        slime_gc_register_object_tagged(gc, obj(3), 20);
+        slime_gc_register_object_tagged(gc, obj(4), 10);
+        slime_gc_register_object(gc, obj(5));
+        for (from, to) in [(1, 2), (2, 3), (3, 4), (4, 1)] {
+            slime_gc_add_reference(gc, obj(from), obj(to));
+        }
+
+        let order = [10, 20, 30];
+        slime_gc_set_finalization_order(gc, order.as_ptr(), order.len() as c_int);
+        assert_eq!(slime_gc_collect(gc), 5);
+
+        let tags_in_order: Vec<_> = freed.iter()
+            .map(|&o| [(1, 30), (2, 10), (3, 20), (4, 10), (5, 0)].iter().find(|(n, _)| obj(*n) == o).unwrap().1)
+            .collect();
+        assert_eq!(tags_in_order, vec![10, 10, 20, 30, 0]);
+        slime_gc_destroy(gc);
+    }
+
+    #[test]
+    fn retagging_and_sized_tagged_registration() {
+        let mut gc = GarbageCollector::new();
+        gc.set_default_object_size(4);
+        gc.register_object_with(obj(1), 64, 7);
+        assert_eq!((gc.get_live_bytes(), gc.get_type_tag(obj(1))), (64, Some(7)));
+
+        // 只改标签的重新注册会把大小重置为默认大小，只改大小的会把标签重置为0
+        gc.register_object_tagged(obj(1), 8);
+        assert_eq!((gc.get_live_bytes(), gc.get_type_tag(obj(1))), (4, Some(8)));
+        assert_eq!((gc.count_objects_by_tag(7), gc.count_objects_by_tag(8)), (0, 1));
+        gc.register_object_sized(obj(1), 16);
+        assert_eq!((gc.get_live_bytes(), gc.get_type_tag(obj(1))), (16, Some(0)));
+
+        // 同时指定时两者都保留
+        let gc = Box::into_raw(Box::new(gc));
+        assert_eq!(slime_gc_register_object_sized_tagged(gc, obj(1), 48, 9), SLIME_GC_OK);
+        let mut tag = 0;
+        assert_eq!(slime_gc_get_type_tag(gc, obj(1), &mut tag), 1);
+        assert_eq!((slime_gc_get_live_bytes(gc), tag), (48, 9));
+        assert_eq!(slime_gc_count_by_tag(gc, 8), 0);
+        slime_gc_destroy(gc);
+
+        let ts = slime_gc_new_threadsafe();
+        assert_eq!(slime_gc_ts_register_object_sized_tagged(ts, obj(2), 12, 3), SLIME_GC_OK);
+        assert_eq!((slime_gc_ts_get_live_bytes(ts), slime_gc_ts_count_by_tag(ts, 3)), (12, 1));
+        slime_gc_ts_destroy(ts);
+    }
+
+    // ---- synth-260：有界的增量回收步 ----
+
+    extern "C" fn ignore_free(_obj: *mut c_void, _user_data: *mut c_void) {}
+
+    /// 一条从根对象obj(1)开始、长度为live的引用链，其余对象直到total都是垃圾
+    fn chain_heap(live: usize, total: usize) -> GarbageCollector {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=total {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(1));
+        for n in 2..=live {
+            gc.add_reference(obj(n - 1), obj(n));
+        }
+        gc
+    }
+
+    /// 用固定种子生成随机对象图：1..=objects，前roots个为根对象
+    fn random_heap(seed: u64, objects: usize, edges: usize, roots: usize) -> GarbageCollector {
+        let mut rng = XorShift(seed);
+        let mut gc = GarbageCollector::new();
+        for n in 1..=objects {
+            gc.register_object(obj(n));
+        }
+        for n in 1..=roots {
+            gc.mark_root(obj(n));
+        }
+        for _ in 0..edges {
+            gc.add_reference(obj(rng.next(objects) + 1), obj(rng.next(objects) + 1));
+        }
+        gc
+    }
+
+    #[test]
+    fn collect_step_checks_at_most_budget_objects_per_step() {
+        const BUDGET: usize = 50;
+        let mut gc = chain_heap(1_000, 5_000);
+        for n in 1_001..=5_000 {
+            gc.set_object_tag(oref(n), (n % 3) as u32);
+        }
+        gc.set_finalization_order(&[2, 1]);
+        gc.set_free_callback(Some(ignore_free), std::ptr::null_mut());
+
+        let mut steps = 0;
+        let mut freed = 0;
+        loop {
+            let before = gc.incremental.as_ref().map(|cycle| (cycle.marked.len(), cycle.next_sweep));
+            let result = gc.collect_step(BUDGET);
+            steps += 1;
+            if let (Some(before), Some(cycle)) = (before, &gc.incremental) {
+                // 引用链上每个对象只引用一个对象，标记一步最多新发现BUDGET个
+                assert!(cycle.marked.len() - before.0 <= BUDGET);
+                assert!(cycle.next_sweep - before.1 <= BUDGET);
+            }
+
+            // 每步清除的对象不超过预算，且在该步内按终结顺序排列
+            for call in gc.take_pending_callbacks().calls {
+                if let PendingCall::Free { objs, .. } = call {
+                    assert!(objs.len() <= BUDGET);
+                    let ranks: Vec<_> = objs.iter().map(|&o| [2, 1, 0][(o.as_ptr() as usize / 16) % 3]).collect();
+                    assert!(ranks.is_sorted());
+                    freed += objs.len();
+                }
+            }
+            if result.finished {
+                assert_eq!(result.freed, 4_000);
+                break;
+            }
+        }
+        assert_eq!(freed, 4_000);
+        assert!(steps >= 1_000 / BUDGET + 5_000 / BUDGET);
+        assert_eq!(gc.get_object_count(), 1_000);
+    }
+
+    #[test]
+    fn many_small_steps_free_the_same_objects_as_one_full_collection() {
+        for seed in [1, 0x9e37_79b9_7f4a_7c15, 0xdead_beef] {
+            let mut full = random_heap(seed, 2_000, 2_500, 15);
+            let expected = full.collect_garbage();
+
+            let mut stepped = random_heap(seed, 2_000, 2_500, 15);
+            let result = loop {
+                let result = stepped.collect_step(7);
+                if result.finished {
+                    break result;
+                }
+            };
+            assert_eq!(result.freed, expected);
+            assert_eq!(stepped.sorted_objects(), full.sorted_objects());
+            assert_indexes_consistent(&stepped);
+        }
+    }
+
+    #[test]
+    fn mutation_interleaved_with_steps_never_sweeps_reachable_objects() {
+        let mut rng = XorShift(0x0bad_cafe_f00d_0001);
+        let mut gc = random_heap(42, 1_500, 1_800, 10);
+        let roots: Vec<_> = (1..=10).map(oref).collect();
+        // 宿主一侧记录的对象图
+        let mut model: HashMap<ObjRef, HashSet<ObjRef>> = (1..=1_500)
+            .map(|n| (oref(n), gc.get_references(obj(n)).unwrap().clone()))
+            .collect();
+
+        let reachable = |model: &HashMap<ObjRef, HashSet<ObjRef>>| {
+            let mut seen: HashSet<_> = roots.iter().copied().collect();
+            let mut stack = roots.clone();
+            while let Some(o) = stack.pop() {
+                for &to in &model[&o] {
+                    if seen.insert(to) {
+                        stack.push(to);
+                    }
+                }
+            }
+            seen
+        };
+
+        let mut next = 1_501;
+        let mut cycles = 0;
+        while cycles < 3 {
+            if gc.collect_step(16).finished {
+                cycles += 1;
+            }
+
+            for o in reachable(&model) {
+                assert!(gc.is_alive(o), "reachable object {o:?} was swept");
+            }
+            model.retain(|o, _| gc.is_alive(*o));
+            let live: HashSet<_> = model.keys().copied().collect();
+            for tos in model.values_mut() {
+                tos.retain(|to| live.contains(to));
+            }
+
+            let mut nodes: Vec<_> = model.keys().copied().collect();
+            nodes.sort();
+            let reach: Vec<_> = {
+                let mut r: Vec<_> = reachable(&model).into_iter().collect();
+                r.sort();
+                r
+            };
+            for _ in 0..4 {
+                match rng.next(3) {
+                    0 => {
+                        // 新对象挂到某个可达对象上
+                        let (from, o) = (reach[rng.next(reach.len())], oref(next));
+                        next += 1;
+                        gc.register_object(o);
+                        gc.add_reference(from, o);
+                        model.insert(o, HashSet::new());
+                        model.get_mut(&from).unwrap().insert(o);
+                    }
+                    1 => {
+                        // 任意两个仍存活的对象之间加一条引用，可能让垃圾重新可达
+                        let (from, to) = (nodes[rng.next(nodes.len())], nodes[rng.next(nodes.len())]);
+                        gc.add_reference(from, to);
+                        model.get_mut(&from).unwrap().insert(to);
+                    }
+                    _ => {
+                        let from = reach[rng.next(reach.len())];
+                        let to = model[&from].iter().copied().min();
+                        if let Some(to) = to {
+                            gc.remove_reference(from, to);
+                            model.get_mut(&from).unwrap().remove(&to);
+                        }
+                    }
+                }
+            }
+        }
+
+        // 完整回收后回收器中恰好剩下宿主认为可达的对象
+        gc.collect_garbage();
+        let mut expected: Vec<_> = reachable(&model).into_iter().collect();
+        expected.sort();
+        assert_eq!(gc.sorted_objects(), expected);
+        assert_indexes_consistent(&gc);
+    }
+
+    // ---- synth-261：固定对象 ----
+
+    #[test]
+    fn pinned_object_survives_clear_roots() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=3 {
+            gc.register_object(obj(n));
+        }
+        gc.add_roots(&[obj(1), obj(2)]);
+        gc.add_reference(obj(2), obj(3));
+        assert_eq!(gc.pin(obj(2)), SLIME_GC_OK);
+        assert_eq!(gc.pin(obj(2)), SLIME_GC_OK);
+
+        gc.clear_roots();
+        assert_eq!(gc.get_root_count(), 0);
+        assert_eq!(gc.collect_garbage(), 1);
+        assert!(gc.is_alive(obj(2)) && gc.is_alive(obj(3)));
+
+        // 最后一次取消固定后才可回收
+        gc.unpin(obj(2));
+        assert_eq!(gc.collect_garbage(), 0);
+        gc.unpin(obj(2));
+        assert!(!gc.is_pinned(obj(2)));
+        assert_eq!(gc.collect_garbage(), 2);
+    }
+
+    #[test]
+    fn pin_rejects_unregistered_and_unpin_saturates() {
+        let gc = slime_gc_new();
+        assert_eq!(slime_gc_pin(gc, obj(1)), SLIME_GC_NOT_REGISTERED);
+        assert_eq!(slime_gc_pin(gc, std::ptr::null_mut()), SLIME_GC_NOT_REGISTERED);
+        slime_gc_register_object(gc, obj(1));
+        assert_eq!(slime_gc_pin(gc, obj(1)), SLIME_GC_OK);
+        slime_gc_unpin(gc, obj(1));
+        slime_gc_unpin(gc, obj(1));
+        assert_eq!(slime_gc_pin_count(gc, obj(1)), 0);
+
+        // 注销会清除固定状态，重新注册后不再固定
+        slime_gc_pin(gc, obj(1));
+        slime_gc_unregister_object(gc, obj(1));
+        slime_gc_register_object(gc, obj(1));
+        assert_eq!(slime_gc_pin_count(gc, obj(1)), 0);
+        assert_eq!(slime_gc_collect(gc), 1);
+        slime_gc_destroy(gc);
+
+        let ts = slime_gc_new_threadsafe();
+        slime_gc_ts_register_object(ts, obj(1));
+        assert_eq!(slime_gc_ts_pin(ts, obj(1)), SLIME_GC_OK);
+        assert_eq!((slime_gc_ts_collect(ts), slime_gc_ts_pin_count(ts, obj(1))), (0, 1));
+        slime_gc_ts_unpin(ts, obj(1));
+        assert_eq!(slime_gc_ts_collect(ts), 1);
+        slime_gc_ts_destroy(ts);
+    }
+
+    #[test]
+    fn pin_during_incremental_cycle_keeps_object_alive() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.register_object(obj(2));
+        gc.mark_root(obj(1));
+        assert!(!gc.collect_step(1).finished);
+        gc.pin(obj(2));
+        let result = loop {
+            let result = gc.collect_step(1);
+            if result.finished {
+                break result;
+            }
+        };
+        assert_eq!(result.freed, 0);
+        assert!(gc.is_alive(obj(2)));
+    }
+
+    #[test]
+    fn redirect_migrates_pins_only_when_asked() {
+        let build = || {
+            let mut gc = GarbageCollector::new();
+            gc.register_object(obj(1));
+            gc.register_object(obj(2));
+            gc.pin(obj(1));
+            gc.pin(obj(1));
+            gc.pin(obj(2));
+            gc
+        };
+
+        let mut gc = build();
+        gc.redirect(obj(1), obj(2), SLIME_GC_REDIRECT_UNREGISTER | SLIME_GC_REDIRECT_MIGRATE_PIN);
+        assert_eq!(gc.pin_count(obj(2)), 3);
+        assert!(!gc.is_pinned(obj(1)));
+        assert_eq!(gc.collect_garbage(), 0);
+        let report = gc.teardown_report();
+        assert_eq!((report.leftover_pins, report.leftover_pin_examples.clone()), (1, vec![oref(2)]));
+        assert!(report.to_string().contains(&format!("pinned objects never unpinned (1): {:p}", obj(2))));
+
+        // 不带标志时固定留在原对象上，注销时一并丢弃
+        let mut gc = build();
+        gc.redirect(obj(1), obj(2), 0);
+        assert_eq!((gc.pin_count(obj(1)), gc.pin_count(obj(2))), (2, 1));
+        gc.redirect(obj(1), obj(2), SLIME_GC_REDIRECT_UNREGISTER);
+        assert_eq!((gc.pin_count(obj(1)), gc.pin_count(obj(2))), (0, 1));
+    }
+
+    // ---- synth-201：按类型查找引用方 ----
+
+    #[test]
+    fn referrers_of_type_reports_exact_direct_pairs() {
+        let mut gc = GarbageCollector::new();
+        // 1(类型0) -> 2(类型5) -> 3(类型9)，4(类型5) -> 3，5(类型9) -> 2，6(类型0) -> 1
+        for (n, tag) in [(1, 0), (2, 5), (3, 9), (4, 5), (5, 9), (6, 0)] {
+            gc.register_object_with(obj(n), 8, tag);
+        }
+        gc.add_reference(obj(1), obj(2));
+        gc.add_reference(obj(2), obj(3));
+        gc.add_reference(obj(4), obj(3));
+        gc.add_reference(obj(5), obj(2));
+        gc.add_reference(obj(6), obj(1));
+
+        assert_eq!(gc.referrers_of_type(9), vec![(oref(2), oref(3)), (oref(4), oref(3))]);
+        assert_eq!(gc.referrers_of_type(5), vec![(oref(1), oref(2)), (oref(5), oref(2))]);
+        assert_eq!(gc.referrers_of_type(0), vec![(oref(6), oref(1))]);
+        assert!(gc.referrers_of_type(42).is_empty());
+    }
+
+    #[test]
+    fn referrers_of_type_skips_indirect_and_unregistered_referrers() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object_with(obj(1), 8, 1);
+        gc.register_object_with(obj(2), 8, 2);
+        gc.register_object_with(obj(3), 8, 3);
+        // 1 -> 2 -> 3：1只间接引用3，不应出现在类型3的结果中
+        gc.add_reference(obj(1), obj(2));
+        gc.add_reference(obj(2), obj(3));
+        assert_eq!(gc.referrers_of_type(3), vec![(oref(2), oref(3))]);
+
+        // 未注册的引用方不报告
+        gc.add_reference(obj(99), obj(3));
+        assert_eq!(gc.referrers_of_type(3), vec![(oref(2), oref(3))]);
+
+        gc.unregister_object(obj(2));
+        assert!(gc.referrers_of_type(3).is_empty());
+    }
+
+    #[test]
+    fn find_referrers_of_type_ffi_truncates_and_returns_total() {
+        let gc = slime_gc_new();
+        for n in 1..=4 {
+            slime_gc_register_object_sized_tagged(gc, obj(n), 8, if n == 4 { 7 } else { 0 });
+        }
+        for n in 1..=3 {
+            slime_gc_add_reference(gc, obj(n), obj(4));
+        }
+
+        let empty = SlimeGcReferrerRecord { from: std::ptr::null_mut(), to: std::ptr::null_mut() };
+        let mut out = [empty; 2];
+        assert_eq!(slime_gc_find_referrers_of_type(gc, 7, out.as_mut_ptr(), out.len()), 3);
+        assert_eq!(out, [SlimeGcReferrerRecord { from: obj(1), to: obj(4) }, SlimeGcReferrerRecord { from: obj(2), to: obj(4) }]);
+        assert_eq!(slime_gc_find_referrers_of_type(gc, 7, std::ptr::null_mut(), 0), 3);
+        assert_eq!(slime_gc_find_referrers_of_type(std::ptr::null(), 7, out.as_mut_ptr(), out.len()), 0);
+        slime_gc_destroy(gc);
+
+        let ts = slime_gc_new_threadsafe();
+        slime_gc_ts_register_object_sized_tagged(ts, obj(1), 8, 0);
+        slime_gc_ts_register_object_sized_tagged(ts, obj(2), 8, 7);
+        slime_gc_ts_add_reference(ts, obj(1), obj(2));
+        assert_eq!(slime_gc_ts_find_referrers_of_type(ts, 7, out.as_mut_ptr(), out.len()), 1);
+        assert_eq!(out[0], SlimeGcReferrerRecord { from: obj(1), to: obj(2) });
+        slime_gc_ts_destroy(ts);
+    }
+
+    // ---- synth-216：线程注册缓冲区 ----
+
+    /// 线程缓冲区当前持有的对象数量
+    fn held_by_buffers(gc: &ConcurrentGarbageCollector) -> usize {
+        gc.with_read(|gc| gc.buffer_holds.values().map(HashSet::len).sum())
+    }
+
+    #[test]
+    fn buffered_operations_apply_on_flush() {
+        let gc = ConcurrentGarbageCollector::new();
+        gc.with_write(|gc| {
+            gc.register_object(obj(1));
+            gc.mark_root(obj(1));
+        });
+
+        let buffer = gc.thread_buffer_begin();
+        buffer.register(obj(2), 24, 5);
+        buffer.add_reference(obj(1), obj(2));
+        assert_eq!(buffer.len(), 2);
+        assert!(!gc.is_alive(obj(2)));
+
+        assert_eq!(buffer.flush(), SLIME_GC_OK);
+        assert!(buffer.is_empty());
+        assert!(gc.is_alive(obj(2)));
+        assert_eq!(gc.with_read(|gc| (gc.get_type_tag(obj(2)), gc.get_live_bytes() - gc.default_object_size)), (Some(5), 24));
+        assert_eq!(gc.get_reference_count(obj(1)), 1);
+        assert_eq!(held_by_buffers(&gc), 0);
+
+        assert_eq!(gc.collect_garbage(), 0);
+        assert_eq!(buffer.end(), SLIME_GC_OK);
+    }
+
+    #[test]
+    fn collection_force_flushes_and_holds_buffered_objects_until_owner_flushes() {
+        let gc = ConcurrentGarbageCollector::new();
+        let buffer = gc.thread_buffer_begin();
+        buffer.register(obj(1), 8, 0);
+        buffer.register(obj(2), 8, 0);
+        buffer.add_reference(obj(1), obj(2));
+
+        // 缓冲区中的对象尚未连到根上，本次回收仍视为存活
+        assert_eq!(gc.collect_garbage(), 0);
+        assert!(buffer.is_empty());
+        assert_eq!(gc.get_object_count(), 2);
+
+        // 所属线程可能还没缓冲指向它们的引用，刷新之前的回收都不会清除它们；
+        // 这种持有与固定无关，不出现在固定次数中
+        assert_eq!(gc.collect_garbage(), 0);
+        assert_eq!(held_by_buffers(&gc), 2);
+        assert_eq!(gc.pin_count(obj(1)), 0);
+        assert!(!gc.is_pinned(obj(1)));
+
+        // 刷新后它们只是普通的不可达对象
+        assert_eq!(buffer.flush(), SLIME_GC_OK);
+        assert_eq!(gc.collect_garbage(), 2);
+
+        // 增量回收的每一步同样先应用缓冲区
+        buffer.register(obj(3), 8, 0);
+        while !gc.collect_step(1).finished {}
+        assert!(gc.is_alive(obj(3)));
+        buffer.end();
+    }
+
+    #[test]
+    fn buffer_holds_do_not_disturb_user_pins() {
+        let gc = ConcurrentGarbageCollector::new();
+        let buffer = gc.thread_buffer_begin();
+        buffer.register(obj(1), 8, 0);
+        gc.collect_garbage();
+        // 用户在缓冲区持有期间固定并取消固定，不会解除缓冲区的持有
+        assert_eq!(gc.pin(obj(1)), SLIME_GC_OK);
+        gc.unpin(obj(1));
+        assert_eq!(gc.collect_garbage(), 0);
+
+        // 反过来，刷新释放持有也不会取消用户的固定
+        assert_eq!(gc.pin(obj(1)), SLIME_GC_OK);
+        buffer.flush();
+        assert_eq!(gc.pin_count(obj(1)), 1);
+        assert_eq!(gc.collect_garbage(), 0);
+        gc.unpin(obj(1));
+        assert_eq!(gc.collect_garbage(), 1);
+        buffer.end();
+    }
+
+    #[test]
+    fn unregister_clears_buffer_holds() {
+        let gc = ConcurrentGarbageCollector::new();
+        let buffer = gc.thread_buffer_begin();
+        buffer.register(obj(1), 8, 0);
+        buffer.register(obj(2), 8, 0);
+        gc.collect_garbage();
+        assert_eq!(held_by_buffers(&gc), 2);
+
+        gc.unregister_object(obj(1));
+        assert_eq!(held_by_buffers(&gc), 1);
+        // 重新注册的同一地址是新对象，不再被持有
+        gc.register_object(obj(1));
+        assert_eq!(gc.collect_garbage(), 1);
+        assert!(!gc.is_alive(obj(1)));
+        assert!(gc.is_alive(obj(2)));
+        buffer.end();
+        assert_eq!(held_by_buffers(&gc), 0);
+    }
+
+    #[test]
+    fn dropping_a_buffer_applies_its_operations_and_releases_holds() {
+        let gc = ConcurrentGarbageCollector::new();
+        gc.with_write(|gc| {
+            gc.register_object(obj(1));
+            gc.mark_root(obj(1));
+        });
+        {
+            let buffer = gc.thread_buffer_begin();
+            buffer.register(obj(2), 8, 0);
+            buffer.register(obj(3), 8, 0);
+            gc.collect_garbage();
+            buffer.add_reference(obj(1), obj(2));
+        }
+
+        assert!(gc.buffers.lock().unwrap().is_empty());
+        assert_eq!(held_by_buffers(&gc), 0);
+        assert_eq!(gc.get_reference_count(obj(1)), 1);
+        assert_eq!(gc.collect_garbage(), 1);
+        assert!(gc.is_alive(obj(2)));
+    }
+
+    #[test]
+    fn reference_buffered_after_a_foreign_collection_still_keeps_object() {
+        let gc = ConcurrentGarbageCollector::new();
+        gc.with_write(|gc| {
+            gc.register_object(obj(1));
+            gc.mark_root(obj(1));
+        });
+        let buffer = gc.thread_buffer_begin();
+
+        // 其他线程的回收在注册和添加引用之间应用了缓冲区，随后又有一次回收
+        buffer.register(obj(2), 8, 0);
+        assert_eq!(gc.collect_garbage(), 0);
+        assert_eq!(gc.collect_garbage(), 0);
+        buffer.add_reference(obj(1), obj(2));
+        assert_eq!(gc.collect_garbage(), 0);
+
+        assert_eq!(buffer.end(), SLIME_GC_OK);
+        assert_eq!(gc.collect_garbage(), 0);
+        assert!(gc.is_alive(obj(2)));
+        assert_eq!(held_by_buffers(&gc), 0);
+    }
+
+    #[test]
+    fn flush_reports_registrations_refused_by_object_limit() {
+        let gc = ConcurrentGarbageCollector::new();
+        gc.set_max_objects(2);
+        let buffer = gc.thread_buffer_begin();
+        for n in 1..=3 {
+            buffer.register(obj(n), 8, 0);
+        }
+
+        // 同批注册的对象已被缓冲区持有，紧急回收清除不了它们，第三个注册失败
+        assert_eq!(buffer.flush(), SLIME_GC_OBJECT_LIMIT);
+        assert_eq!(gc.get_object_count(), 2);
+        assert!(!gc.is_alive(obj(3)));
+        // 失败只报告一次
+        assert_eq!(buffer.flush(), SLIME_GC_OK);
+
+        // 由回收强制应用时，失败留到下一次刷新报告
+        gc.with_write(|gc| gc.add_roots(&[obj(1), obj(2)]));
+        buffer.register(obj(4), 8, 0);
+        gc.collect_garbage();
+        assert!(!gc.is_alive(obj(4)));
+        assert_eq!(buffer.end(), SLIME_GC_OBJECT_LIMIT);
+    }
+
+    #[test]
+    fn buffered_registration_from_threads_with_periodic_collections_loses_nothing() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 2_000;
+        let gc = ConcurrentGarbageCollector::new();
+
+        std::thread::scope(|scope| {
+            for t in 0..THREADS {
+                let gc = &gc;
+                scope.spawn(move || {
+                    let base = (t + 1) * 1_000_000;
+                    let root = obj(base);
+                    gc.with_write(|gc| {
+                        gc.register_object(root);
+                        gc.mark_root(root);
+                    });
+                    let buffer = gc.thread_buffer_begin();
+                    for i in 1..=PER_THREAD {
+                        let o = obj(base + i);
+                        buffer.register(o, 8, t as u32);
+                        // 偶数对象挂到根上，奇数对象成为垃圾
+                        if i % 2 == 0 {
+                            buffer.add_reference(root, o);
+                        }
+                        if i % 100 == 0 {
+                            assert_eq!(buffer.flush(), SLIME_GC_OK);
+                        }
+                        if i % 250 == 0 {
+                            gc.collect_garbage();
+                        }
+                    }
+                    assert_eq!(buffer.end(), SLIME_GC_OK);
+                });
+            }
+        });
+
+        gc.collect_garbage();
+        assert_eq!(gc.get_object_count(), THREADS * (PER_THREAD / 2 + 1));
+        for t in 0..THREADS {
+            assert_eq!(gc.get_reference_count(obj((t + 1) * 1_000_000)), PER_THREAD / 2);
+            assert_eq!(gc.count_objects_by_tag(t as u32 + 1), if t + 1 < THREADS { PER_THREAD / 2 } else { 0 });
+        }
+        gc.with_read(assert_indexes_consistent);
+        assert!(gc.buffers.lock().unwrap().is_empty());
+        assert_eq!(held_by_buffers(&gc), 0);
+    }
+
+    #[test]
+    fn buffered_registration_takes_far_fewer_write_locks() {
+        const OBJECTS: usize = 10_000;
+        const BATCH: usize = 100;
+
+        let unbuffered = ConcurrentGarbageCollector::new();
+        let start = Instant::now();
+        for n in 1..=OBJECTS {
+            unbuffered.register_object_with(obj(n), 8, 0);
+        }
+        let unbuffered_time = start.elapsed();
+
+        let buffered = ConcurrentGarbageCollector::new();
+        let buffer = buffered.thread_buffer_begin();
+        let start = Instant::now();
+        for n in 1..=OBJECTS {
+            buffer.register(obj(n), 8, 0);
+            if n % BATCH == 0 {
+                buffer.flush();
+            }
+        }
+        buffer.end();
+        let buffered_time = start.elapsed();
+
+        assert_eq!(unbuffered.get_object_count(), buffered.get_object_count());
+        let (unbuffered_locks, buffered_locks) = (unbuffered.get_write_lock_count(), buffered.get_write_lock_count());
+        eprintln!("{OBJECTS}次注册：逐个加锁{unbuffered_locks}次/{unbuffered_time:?}，缓冲{buffered_locks}次/{buffered_time:?}");
+        assert_eq!(unbuffered_locks, OBJECTS as u64);
+        assert!(buffered_locks <= (OBJECTS / BATCH + 1) as u64);
+    }
+
+    #[test]
+    fn thread_buffer_ffi_round_trip() {
+        let gc = slime_gc_new_threadsafe();
+        slime_gc_ts_register_object(gc, obj(1));
+        slime_gc_ts_mark_root(gc, obj(1));
+
+        let buf = slime_gc_thread_buffer_begin(gc);
+        assert!(!buf.is_null());
+        slime_gc_buffered_register(buf, obj(2), 16, 3);
+        slime_gc_buffered_add_reference(buf, obj(1), obj(2));
+        assert_eq!(slime_gc_ts_collect(gc), 0);
+        assert_eq!(slime_gc_ts_count_by_tag(gc, 3), 1);
+        assert_eq!(slime_gc_thread_buffer_flush(gc, buf), SLIME_GC_OK);
+        assert_eq!(slime_gc_thread_buffer_end(gc, buf), SLIME_GC_OK);
+        assert_eq!(slime_gc_ts_collect(gc), 0);
+        assert!(slime_gc_ts_get_write_lock_count(gc) > 0);
+
+        assert!(slime_gc_thread_buffer_begin(std::ptr::null()).is_null());
+        slime_gc_buffered_register(std::ptr::null_mut(), obj(3), 8, 0);
+        assert_eq!(slime_gc_thread_buffer_flush(gc, std::ptr::null_mut()), SLIME_GC_OK);
+        slime_gc_ts_destroy(gc);
+    }
+
+    // ---- synth-239：统一推进延迟工作 ----
+
+    /// 本次pump推进了哪些类别
+    fn pumped_categories(result: &SlimeGcPumpResult) -> u32 {
+        [
+            (result.mark_done, SLIME_GC_WORK_MARK),
+            (result.sweep_done, SLIME_GC_WORK_SWEEP),
+            (result.garbage_done, SLIME_GC_WORK_GARBAGE),
+            (result.callbacks_done, SLIME_GC_WORK_CALLBACKS),
+        ]
+        .into_iter()
+        .filter(|&(done, _)| done > 0)
+        .fold(0, |mask, (_, category)| mask | category)
+    }
+
+    #[test]
+    fn pump_without_pending_work_does_nothing() {
+        let mut gc = chain_heap(3, 5);
+        assert_eq!(gc.pending_work(), 0);
+        assert_eq!(gc.pump(Duration::from_secs(1), SLIME_GC_WORK_ALL), SlimeGcPumpResult::default());
+        assert_eq!(gc.get_object_count(), 5);
+    }
+
+    #[test]
+    fn pump_starts_a_cycle_once_collection_is_due() {
+        let mut gc = GarbageCollector::new();
+        gc.set_collection_threshold(100);
+        gc.register_object_sized(obj(1), 60);
+        gc.mark_root(obj(1));
+        assert_eq!(gc.pending_work(), 0);
+
+        // 超过阈值后报告标记工作，pump开始新一轮增量回收而不是完整回收
+        gc.register_object_sized(obj(2), 60);
+        assert_eq!(gc.pending_work(), SLIME_GC_WORK_MARK);
+        let result = gc.pump(Duration::ZERO, SLIME_GC_WORK_MARK);
+        assert_eq!(result.mark_done, 1);
+        assert!(gc.incremental.is_some());
+
+        let result = gc.pump(Duration::from_secs(1), SLIME_GC_WORK_ALL);
+        assert_eq!(result.freed, 1);
+        assert_eq!(gc.pending_work(), 0);
+        assert!(!gc.is_alive(obj(2)));
+    }
+
+    #[test]
+    fn pump_follows_priority_order_and_respects_mask() {
+        let mut gc = chain_heap(600, 1_000);
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        gc.set_free_callback(Some(record_free), &mut freed as *mut _ as *mut c_void);
+        // 留下400个未取走的对象，再开始一轮增量回收
+        let mut out = [std::ptr::null_mut(); 1];
+        assert_eq!(gc.collect_into(&mut out), 1);
+        for n in 2_001..=2_300 {
+            gc.register_object(obj(n));
+        }
+        gc.collect_step(1);
+        assert_eq!(gc.pending_work(), SLIME_GC_WORK_MARK | SLIME_GC_WORK_GARBAGE);
+
+        // 未选中当前阶段时不推进增量回收，只报告剩余工作
+        let idle = gc.pump(Duration::from_secs(1), SLIME_GC_WORK_SWEEP | SLIME_GC_WORK_CALLBACKS);
+        assert_eq!(pumped_categories(&idle), 0);
+        assert_eq!((idle.mark_remaining, idle.garbage_remaining), (1, 399));
+
+        // 只选中标记时停在清除之前
+        let marked = gc.pump(Duration::from_secs(1), SLIME_GC_WORK_MARK);
+        assert_eq!((marked.mark_done, marked.mark_remaining, marked.sweep_remaining), (599, 0, 900));
+        assert_eq!(gc.pending_work(), SLIME_GC_WORK_SWEEP | SLIME_GC_WORK_GARBAGE);
+
+        // 零预算每次只推进一块，推进的类别按清除、未取走对象的顺序出现，不会回头
+        let mut order = Vec::new();
+        while gc.pending_work() & !SLIME_GC_WORK_CALLBACKS != 0 {
+            let result = gc.pump(Duration::ZERO, SLIME_GC_WORK_ALL);
+            let category = pumped_categories(&result);
+            assert_eq!(category.count_ones(), 1);
+            if order.last() != Some(&category) {
+                order.push(category);
+            }
+        }
+        assert_eq!(order, vec![SLIME_GC_WORK_SWEEP, SLIME_GC_WORK_GARBAGE]);
+
+        // 回收器自身不派发回调，只报告剩余批数
+        assert_eq!(gc.pending_work(), SLIME_GC_WORK_CALLBACKS);
+        let result = gc.pump(Duration::from_secs(1), SLIME_GC_WORK_ALL);
+        assert_eq!(pumped_categories(&result), 0);
+        assert!(result.callbacks_remaining > 0);
+        gc.take_pending_callbacks().run();
+        assert_eq!(gc.pending_work(), 0);
+        // 300个新对象和399个未取走的对象都交给了释放回调
+        assert_eq!(freed.len(), 699);
+        assert_eq!(gc.get_object_count(), 600);
+    }
+
+    #[test]
+    fn pump_with_zero_budget_advances_one_chunk_until_drained() {
+        let mut gc = chain_heap(1_000, 3_000);
+        gc.collect_step(1);
+
+        let mut pumps = 0;
+        let mut freed = 0;
+        loop {
+            let result = gc.pump(Duration::ZERO, SLIME_GC_WORK_ALL);
+            pumps += 1;
+            assert!(result.mark_done + result.sweep_done <= PUMP_CHUNK_OBJECTS);
+            freed += result.freed;
+            let drained = result.mark_remaining == 0 && result.sweep_remaining == 0 && gc.incremental.is_none();
+            // 没有剩余工作时pending_work恰好归零
+            assert_eq!(gc.pending_work() == 0, drained);
+            if drained {
+                break;
+            }
+        }
+        assert_eq!(freed, 2_000);
+        assert!(pumps >= (999 + 3_000) / PUMP_CHUNK_OBJECTS);
+        assert_eq!(gc.get_object_count(), 1_000);
+    }
+
+    #[test]
+    fn pump_stays_within_its_time_budget() {
+        let mut gc = chain_heap(200_000, 200_000);
+        gc.collect_step(1);
+
+        // 零预算恰好推进一块，用它估计一块的耗时
+        let start = Instant::now();
+        let result = gc.pump(Duration::ZERO, SLIME_GC_WORK_ALL);
+        let chunk_time = start.elapsed();
+        assert_eq!(result.mark_done, PUMP_CHUNK_OBJECTS);
+
+        let budget = Duration::from_millis(2);
+        let start = Instant::now();
+        let result = gc.pump(budget, SLIME_GC_WORK_ALL);
+        let elapsed = start.elapsed();
+        // 预算用完后最多再多出一块
+        assert!(elapsed < budget + chunk_time * 10 + Duration::from_millis(5), "{elapsed:?} for a {budget:?} budget");
+        assert!(result.mark_done >= PUMP_CHUNK_OBJECTS);
+        assert!(result.mark_remaining > 0);
+        assert_ne!(gc.pending_work(), 0);
+    }
+
+    #[test]
+    fn shading_during_sweep_is_incremental() {
+        // 1是根对象，2..=1001是一条不可达的链
+        let mut gc = GarbageCollector::new();
+        for n in 1..=1_001 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(1));
+        for n in 3..=1_001 {
+            gc.add_reference(obj(n - 1), obj(n));
+        }
+        while gc.incremental.as_ref().is_none_or(|cycle| cycle.phase == IncrementalPhase::Mark) {
+            gc.collect_step(1);
+        }
+
+        // 清除阶段中把链头重新连到根上，只标记链头，不会一次标记整条链
+        let marked_before = gc.incremental.as_ref().unwrap().marked.len();
+        gc.add_reference(obj(1), obj(2));
+        assert_eq!(gc.incremental.as_ref().unwrap().marked.len(), marked_before + 1);
+        assert_eq!(gc.pending_work(), SLIME_GC_WORK_MARK);
+
+        // 之后每步先扫描重新标记的对象，扫描完之前不清除任何对象
+        let result = gc.pump(Duration::ZERO, SLIME_GC_WORK_ALL);
+        assert_eq!((result.mark_done, result.sweep_done, result.freed), (PUMP_CHUNK_OBJECTS, 0, 0));
+        while !gc.collect_step(1).finished {}
+        assert_eq!(gc.get_object_count(), 1_001);
+    }
+
+    #[test]
+    fn pump_ffi_dispatches_callbacks_only_when_selected() {
+        let gc = slime_gc_new();
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        slime_gc_set_free_callback(gc, Some(record_free), &mut freed as *mut _ as *mut c_void);
+        for n in 1..=3 {
+            slime_gc_register_object(gc, obj(n));
+        }
+        slime_gc_mark_root(gc, obj(1));
+        assert_eq!(slime_gc_pending_work(gc), 0);
+        assert_eq!(slime_gc_collect_step(gc, 1), -1);
+        assert_eq!(slime_gc_pending_work(gc), SLIME_GC_WORK_SWEEP);
+
+        // 未选中回调时，释放回调留在队列中
+        let result = slime_gc_pump(gc, 1_000_000, SLIME_GC_WORK_MARK | SLIME_GC_WORK_SWEEP);
+        assert_eq!((result.freed, result.callbacks_done, result.callbacks_remaining), (2, 0, 1));
+        assert!(freed.is_empty());
+        assert_eq!(slime_gc_pending_work(gc), SLIME_GC_WORK_CALLBACKS);
+
+        let result = slime_gc_pump(gc, 0, SLIME_GC_WORK_CALLBACKS);
+        assert_eq!((result.callbacks_done, result.callbacks_remaining), (1, 0));
+        assert_eq!(slime_gc_pending_work(gc), 0);
+        freed.sort();
+        assert_eq!(freed, vec![obj(2), obj(3)]);
+        assert_eq!(slime_gc_pump(std::ptr::null_mut(), 0, SLIME_GC_WORK_ALL), SlimeGcPumpResult::default());
+        assert_eq!(slime_gc_pending_work(std::ptr::null()), 0);
+        slime_gc_destroy(gc);
+
+        let ts = slime_gc_new_threadsafe();
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        slime_gc_ts_set_free_callback(ts, Some(record_free), &mut freed as *mut _ as *mut c_void);
+        slime_gc_ts_register_object(ts, obj(1));
+        slime_gc_ts_collect_step(ts, 1);
+        assert_eq!(slime_gc_ts_pending_work(ts), SLIME_GC_WORK_SWEEP);
+        let result = slime_gc_ts_pump(ts, 1_000_000, SLIME_GC_WORK_SWEEP);
+        assert_eq!((result.freed, result.callbacks_remaining), (1, 1));
+        assert_eq!(slime_gc_ts_pending_work(ts), SLIME_GC_WORK_CALLBACKS);
+        let result = slime_gc_ts_pump(ts, 1_000_000, SLIME_GC_WORK_ALL);
+        assert_eq!((result.callbacks_done, result.callbacks_remaining), (1, 0));
+        assert_eq!(freed, vec![obj(1)]);
+        assert_eq!(slime_gc_ts_pending_work(ts), 0);
+        slime_gc_ts_destroy(ts);
+    }
+
+    // ---- synth-202：替换回收器配置 ----
+
+    /// 构造一个带根、固定、作用域根、弱引用、大小和标签的堆，返回回收器
+    fn swap_in_heap() -> GarbageCollector {
+        let mut gc = GarbageCollector::new();
+        gc.register_object_with(obj(1), 100, 7);
+        gc.register_object_with(obj(2), 200, 8);
+        for n in 3..=8 {
+            gc.register_object(obj(n));
+        }
+        gc.mark_root(obj(1));
+        gc.add_reference(obj(1), obj(2));
+        gc.pin(obj(3));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(4));
+        gc.add_weak_reference(obj(1), obj(5));
+        gc.add_reference(obj(6), obj(7));
+        gc
+    }
+
+    #[test]
+    fn swap_in_keeps_the_same_objects_alive() {
+        let mut gc = swap_in_heap();
+        let config = SlimeGcConfig { max_objects: 100, default_object_size: 64, ..SlimeGcConfig::default() };
+        assert_eq!(gc.swap_in(&config), SLIME_GC_OK);
+
+        assert_eq!(gc.get_object_count(), 8);
+        assert_eq!(gc.get_live_bytes(), 300);
+        assert_eq!(gc.get_type_tag(obj(1)), Some(7));
+        assert_eq!(gc.get_type_tag(obj(2)), Some(8));
+        assert_eq!(gc.pin_count(obj(3)), 1);
+        assert_eq!(gc.get_scope_depth(), 1);
+        assert!(gc.get_weak_references(obj(1)).unwrap().contains(&oref(5)));
+        assert!(gc.get_referrers(obj(2)).unwrap().contains(&oref(1)));
+        assert_eq!(gc.get_max_objects(), 100);
+
+        // 与未替换的回收器清除同样的对象
+        let mut reference = swap_in_heap();
+        assert_eq!(gc.collect_garbage(), reference.collect_garbage());
+        for n in 1..=8 {
+            assert_eq!(gc.is_alive(obj(n)), reference.is_alive(obj(n)), "object {n}");
+        }
+        assert!(gc.get_weak_references(obj(1)).is_none_or(|w| w.is_empty()));
+
+        // 新的默认大小只作用于之后的注册
+        gc.register_object(obj(9));
+        assert_eq!(gc.get_live_bytes(), 364);
+        assert_eq!(gc.pop_root_scope(), SLIME_GC_OK);
+    }
+
+    #[test]
+    fn swap_in_resets_stats_and_requires_callbacks_again() {
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        let mut cleared: Vec<(*mut c_void, *mut c_void)> = Vec::new();
+        let mut gc = swap_in_heap();
+        gc.set_free_callback(Some(record_free), &mut freed as *mut _ as *mut c_void);
+        gc.set_weak_clear_callback(Some(record_weak_clear), &mut cleared as *mut _ as *mut c_void);
+        gc.set_recent_unregister_window(4);
+        gc.register_object(obj(20));
+        gc.collect_garbage();
+        gc.take_pending_callbacks().run();
+        assert_eq!(freed.len(), 5);
+        gc.unregister_object(obj(2));
+        gc.add_reference(obj(1), obj(2));
+        assert_eq!(gc.get_recently_unregistered_count(), 1);
+        let subscription = gc.subscribe_invalidation(record_invalidation, std::ptr::null_mut());
+        assert_ne!(gc.get_stats().collected_last_cycle, 0);
+
+        assert_eq!(gc.swap_in(&SlimeGcConfig::default()), SLIME_GC_OK);
+        let stats = gc.get_stats();
+        assert_eq!(stats.total_objects, 3);
+        assert_eq!(stats.collected_last_cycle, 0);
+        assert_eq!(stats.last_mark_duration, Duration::ZERO);
+        assert_eq!(gc.get_recently_unregistered_count(), 0);
+        let resubscribed = gc.subscribe_invalidation(record_invalidation, std::ptr::null_mut());
+        assert!(resubscribed > subscription);
+        gc.unsubscribe_invalidation(resubscribed);
+
+        // 回调没有迁移，重新设置之前回收不会通知宿主
+        freed.clear();
+        cleared.clear();
+        gc.add_weak_reference(obj(3), obj(1));
+        gc.unmark_root(obj(1));
+        gc.collect_garbage();
+        gc.take_pending_callbacks().run();
+        assert!(freed.is_empty());
+        assert!(cleared.is_empty());
+
+        gc.set_free_callback(Some(record_free), &mut freed as *mut _ as *mut c_void);
+        gc.unpin(obj(3));
+        gc.collect_garbage();
+        gc.take_pending_callbacks().run();
+        assert_eq!(freed, vec![obj(3)]);
+    }
+
+    #[test]
+    fn swap_in_rejects_a_limit_below_the_live_objects() {
+        let mut gc = swap_in_heap();
+        gc.reserve_objects(2);
+        let before = gc.dump_graph_json();
+        let config = SlimeGcConfig { max_objects: 9, ..SlimeGcConfig::default() };
+        assert_eq!(gc.swap_in(&config), SLIME_GC_OBJECT_LIMIT);
+        assert_eq!(gc.dump_graph_json(), before);
+        assert_eq!(gc.get_max_objects(), 0);
+        assert_eq!(gc.get_reserved_objects(), 2);
+
+        let config = SlimeGcConfig { max_objects: 10, ..SlimeGcConfig::default() };
+        assert_eq!(gc.swap_in(&config), SLIME_GC_OK);
+        assert_eq!(gc.get_reserved_objects(), 2);
+        assert_eq!(gc.register_object(obj(9)), SLIME_GC_OK);
+        assert_eq!(gc.register_object(obj(10)), SLIME_GC_OK);
+    }
+
+    #[test]
+    fn swap_in_through_c_interface_keeps_the_handle() {
+        let gc = slime_gc_new();
+        slime_gc_register_object(gc, obj(1));
+        slime_gc_register_object(gc, obj(2));
+        slime_gc_mark_root(gc, obj(1));
+        let config = SlimeGcConfig { collection_threshold: 4096, ..SlimeGcConfig::default() };
+        assert_eq!(slime_gc_swap_in(gc, &config), SLIME_GC_OK);
+        assert_eq!(unsafe { (*gc).get_collection_threshold() }, 4096);
+        assert_eq!(slime_gc_swap_in(gc, std::ptr::null()), SLIME_GC_OK);
+        assert_eq!(slime_gc_collect(gc), 1);
+        assert_eq!(slime_gc_get_object_count(gc), 1);
+        slime_gc_destroy(gc);
+
+        let ts = slime_gc_new_threadsafe();
+        let buffer = unsafe { &*ts }.thread_buffer_begin();
+        buffer.register(obj(1), 0, 0);
+        let config = SlimeGcConfig { max_objects: 1, ..SlimeGcConfig::default() };
+        assert_eq!(slime_gc_ts_swap_in(ts, &config), SLIME_GC_OK);
+        assert_eq!(unsafe { &*ts }.get_object_count(), 1);
+        assert_eq!(buffer.end(), SLIME_GC_OK);
+        slime_gc_ts_destroy(ts);
+    }
+
+    // ---- synth-204：外部内存 ----
+
+    #[test]
+    fn external_bytes_make_the_threshold_fire_earlier() {
+        let mut gc = GarbageCollector::new();
+        gc.set_collection_threshold(1000);
+        for n in 1..=4 {
+            gc.register_object_sized(obj(n), 100);
+        }
+        gc.mark_root(obj(1));
+        assert_eq!(gc.maybe_collect(), 0);
+
+        assert_eq!(gc.add_external_bytes(obj(2), 500), SLIME_GC_OK);
+        assert_eq!(gc.add_external_bytes(obj(2), 300), SLIME_GC_OK);
+        assert_eq!(gc.get_external_bytes(obj(2)), 800);
+        assert_eq!(gc.get_live_bytes(), 1200);
+        assert_ne!(gc.pending_work() & SLIME_GC_WORK_MARK, 0);
+        assert_eq!(gc.maybe_collect(), 3);
+        assert_eq!(gc.get_live_bytes(), 100);
+        assert_eq!(gc.get_external_bytes(obj(2)), 0);
+    }
+
+    #[test]
+    fn external_bytes_return_to_baseline() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object_sized(obj(1), 16);
+        gc.register_object_sized(obj(2), 16);
+        gc.mark_root(obj(1));
+        gc.add_reference(obj(1), obj(2));
+        let baseline = gc.get_live_bytes();
+
+        gc.add_external_bytes(obj(1), 64);
+        gc.add_external_bytes(obj(2), 128);
+        assert_eq!(gc.get_live_bytes(), baseline + 192);
+
+        // 扣除超过归属的部分不会影响其他对象
+        gc.remove_external_bytes(obj(1), 1000);
+        assert_eq!(gc.get_external_bytes(obj(1)), 0);
+        assert_eq!(gc.get_live_bytes(), baseline + 128);
+
+        // 重复注册只重置自身大小，外部内存保留
+        gc.register_object_sized(obj(2), 32);
+        assert_eq!(gc.get_live_bytes(), baseline + 16 + 128);
+        gc.unregister_object(obj(2));
+        assert_eq!(gc.get_live_bytes(), 16);
+
+        gc.add_external_bytes(obj(1), 40);
+        gc.unmark_root(obj(1));
+        gc.collect_garbage();
+        assert_eq!(gc.get_live_bytes(), 0);
+
+        assert_eq!(gc.add_external_bytes(obj(3), 10), SLIME_GC_NOT_REGISTERED);
+        assert_eq!(gc.get_live_bytes(), 0);
+    }
+
+    #[test]
+    fn external_bytes_are_reported_separately_in_the_export() {
+        let mut gc = GarbageCollector::new();
+        gc.register_object_sized(obj(1), 24);
+        gc.add_external_bytes(obj(1), 4096);
+        let graph = parse_json(&gc.dump_graph_json());
+        let Json::Array(nodes) = graph.get("nodes") else { unreachable!() };
+        assert_eq!(nodes[0].get("size"), &Json::Number(24));
+        assert_eq!(nodes[0].get("external_bytes"), &Json::Number(4096));
+
+        // 替换配置时外部内存随对象迁移
+        assert_eq!(gc.swap_in(&SlimeGcConfig::default()), SLIME_GC_OK);
+        assert_eq!(gc.get_external_bytes(obj(1)), 4096);
+        assert_eq!(gc.get_live_bytes(), 4120);
+    }
+
+    #[test]
+    fn external_bytes_through_c_interface() {
+        let gc = slime_gc_new();
+        slime_gc_register_object(gc, obj(1));
+        assert_eq!(slime_gc_add_external_bytes(gc, obj(1), 100), SLIME_GC_OK);
+        slime_gc_remove_external_bytes(gc, obj(1), 30);
+        assert_eq!(slime_gc_get_external_bytes(gc, obj(1)), 70);
+        assert_eq!(slime_gc_get_live_bytes(gc), 70);
+        assert_eq!(slime_gc_add_external_bytes(std::ptr::null_mut(), obj(1), 1), SLIME_GC_NOT_REGISTERED);
+        slime_gc_destroy(gc);
+
+        let ts = slime_gc_new_threadsafe();
+        slime_gc_ts_register_object(ts, obj(1));
+        assert_eq!(slime_gc_ts_add_external_bytes(ts, obj(1), 100), SLIME_GC_OK);
+        slime_gc_ts_remove_external_bytes(ts, obj(1), 100);
+        assert_eq!(slime_gc_ts_get_external_bytes(ts, obj(1)), 0);
+        assert_eq!(slime_gc_ts_get_live_bytes(ts), 0);
+        slime_gc_ts_destroy(ts);
+    }
+
+    // ---- synth-206：统一的对象拆除流程 ----
+
+    /// 记录带原因的弱引用清除：(引用方, 目标, 原因)
+    extern "C" fn record_weak_clear_reason(from: *mut c_void, to: *mut c_void, reason: c_int, user_data: *mut c_void) {
+        let cleared = unsafe { &mut *(user_data as *mut Vec<(*mut c_void, *mut c_void, c_int)>) };
+        cleared.push((from, to, reason));
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum TeardownPath {
+        Unregister,
+        Sweep,
+        Redirect,
+        Destroy,
     }
 
-    /// 注册新对象
-    pub fn register_object(&mut self, obj: *mut c_void) {
-        if !obj.is_null() {
-            self.objects.insert(obj);
-            self.references.insert(obj, HashSet::new());
-        }
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum HeldBy {
+        Strong,
+        Weak,
+        Pinned,
     }
 
-    /// 注销对象
-    pub fn unregister_object(&mut self, obj: *mut c_void) {
-        if !obj.is_null() {
-            self.objects.remove(&obj);
-            self.roots.remove(&obj);
-            self.references.remove(&obj);
-            
-            // 从其他对象的引用列表中移除该对象
-            for refs in self.references.values_mut() {
-                refs.remove(&obj);
+    /// 根对象obj(1)以held方式持有obj(2)，根对象obj(4)总是弱引用obj(2)，obj(3)为重定向目标；
+    /// obj(2)带有大小、外部内存和类型标签
+    fn teardown_matrix_heap(held: HeldBy) -> *mut GarbageCollector {
+        let gc = slime_gc_new();
+        let g = unsafe { &mut *gc };
+        g.register_object(obj(1));
+        g.register_object_with(obj(2), 8, 5);
+        g.register_object(obj(3));
+        g.register_object(obj(4));
+        g.add_roots(&[obj(1), obj(3), obj(4)]);
+        g.add_external_bytes(obj(2), 100);
+        g.add_weak_reference(obj(4), obj(2));
+        match held {
+            HeldBy::Strong => g.add_reference(obj(1), obj(2)),
+            HeldBy::Weak => g.add_weak_reference(obj(1), obj(2)),
+            HeldBy::Pinned => {
+                g.pin(obj(2));
             }
         }
+        gc
     }
 
-    /// 添加对象引用
-    pub fn add_reference(&mut self, from: *mut c_void, to: *mut c_void) {
-        if !from.is_null() && !to.is_null() {
-            // 确保from对象已注册
-            if self.objects.contains(&from) {
-                // 获取或创建from对象的引用集合
-                let refs = self.references.entry(from).or_insert(HashSet::new());
-                // 添加引用
-                refs.insert(to);
+    #[test]
+    fn teardown_matrix_reports_weak_clear_reasons() {
+        use HeldBy::*;
+        use TeardownPath::*;
+        const COLLECTED: c_int = SLIME_GC_WEAK_CLEARED_COLLECTED;
+        const REMOVED: c_int = SLIME_GC_WEAK_CLEARED_REMOVED;
+
+        for path in [Unregister, Sweep, Redirect, Destroy] {
+            for held in [Strong, Weak, Pinned] {
+                let gc = teardown_matrix_heap(held);
+                let mut cleared: Vec<(*mut c_void, *mut c_void, c_int)> = Vec::new();
+                slime_gc_set_weak_clear_reason_callback(gc, Some(record_weak_clear_reason), &mut cleared as *mut _ as *mut c_void);
+
+                match path {
+                    Unregister => slime_gc_unregister_object(gc, obj(2)),
+                    Sweep => {
+                        slime_gc_collect(gc);
+                    }
+                    Redirect => {
+                        slime_gc_redirect(gc, obj(2), obj(3), SLIME_GC_REDIRECT_UNREGISTER);
+                    }
+                    Destroy => {
+                        slime_gc_destroy(gc);
+                    }
+                }
+
+                // 只有强引用或固定能让obj(2)在回收中存活；注销和重定向无论如何都会移除它
+                let survives = matches!(path, Sweep | Destroy) && held != Weak;
+                let reason = if matches!(path, Sweep | Destroy) { COLLECTED } else { REMOVED };
+                let mut expected = Vec::new();
+                if !survives {
+                    if held == Weak {
+                        expected.push((obj(1), obj(2), reason));
+                    }
+                    expected.push((obj(4), obj(2), reason));
+                }
+                cleared.sort();
+                assert_eq!(cleared, expected, "{path:?} x {held:?}");
+
+                if path == Destroy {
+                    continue;
+                }
+                let g = unsafe { &*gc };
+                assert_indexes_consistent(g);
+                assert_eq!(g.is_alive(obj(2)), survives, "{path:?} x {held:?}");
+                if !survives {
+                    // 拆除后不留任何痕迹，与从未注册过的对象相同
+                    assert_eq!(g.pin_count(obj(2)), 0);
+                    assert_eq!(g.get_type_tag(obj(2)), None);
+                    assert_eq!(g.count_objects_by_tag(5), 0);
+                    assert_eq!(g.get_external_bytes(obj(2)), 0);
+                    assert_eq!(g.get_live_bytes(), 0);
+                    assert!(g.get_referrers(obj(2)).is_none());
+                    assert!(!g.weak_referrers.contains_key(&oref(2)));
+                    for n in [1, 3, 4] {
+                        assert!(g.get_references(obj(n)).is_none_or(|refs| !refs.contains(&oref(2))));
+                        assert!(g.get_weak_references(obj(n)).is_none_or(|refs| !refs.contains(&oref(2))));
+                    }
+                }
+                if path == Redirect {
+                    // 重定向改写了指向obj(2)的引用，固定次数未迁移
+                    assert_eq!(g.pin_count(obj(3)), 0);
+                    if held == Strong {
+                        assert!(g.get_references(obj(1)).unwrap().contains(&oref(3)));
+                    }
+                }
+                slime_gc_destroy(gc);
             }
         }
     }
 
-    /// 移除对象引用
-    pub fn remove_reference(&mut self, from: *mut c_void, to: *mut c_void) {
-        if !from.is_null() && !to.is_null() {
-            if let Some(refs) = self.references.get_mut(&from) {
-                refs.remove(&to);
-            }
+    #[test]
+    fn plain_and_reason_weak_clear_callbacks_replace_each_other() {
+        let mut plain: Vec<(*mut c_void, *mut c_void)> = Vec::new();
+        let mut with_reason: Vec<(*mut c_void, *mut c_void, c_int)> = Vec::new();
+        let mut gc = GarbageCollector::new();
+        for n in 1..=3 {
+            gc.register_object(obj(n));
         }
+        gc.mark_root(obj(1));
+        gc.add_weak_reference(obj(1), obj(2));
+        gc.add_weak_reference(obj(1), obj(3));
+
+        gc.set_weak_clear_callback(Some(record_weak_clear), &mut plain as *mut _ as *mut c_void);
+        gc.set_weak_clear_reason_callback(Some(record_weak_clear_reason), &mut with_reason as *mut _ as *mut c_void);
+        gc.unregister_object(obj(2));
+        gc.take_pending_callbacks().run();
+        assert!(plain.is_empty());
+        assert_eq!(with_reason, vec![(obj(1), obj(2), SLIME_GC_WEAK_CLEARED_REMOVED)]);
+
+        gc.set_weak_clear_callback(Some(record_weak_clear), &mut plain as *mut _ as *mut c_void);
+        gc.collect_garbage();
+        gc.take_pending_callbacks().run();
+        assert_eq!(plain, vec![(obj(1), obj(3))]);
+        assert_eq!(with_reason.len(), 1);
+
+        let ts = ConcurrentGarbageCollector::new();
+        with_reason.clear();
+        ts.register_object(obj(1));
+        ts.register_object(obj(2));
+        ts.mark_root(obj(1));
+        ts.add_weak_reference(obj(1), obj(2));
+        slime_gc_ts_set_weak_clear_reason_callback(&ts, Some(record_weak_clear_reason), &mut with_reason as *mut _ as *mut c_void);
+        ts.collect_garbage();
+        assert_eq!(with_reason, vec![(obj(1), obj(2), SLIME_GC_WEAK_CLEARED_COLLECTED)]);
     }
 
-    /// 移除对象的所有引用
-    pub fn clear_references(&mut self, obj: *mut c_void) {
-        if !obj.is_null() {
-            self.references.remove(&obj);
-        }
+    // ---- synth-208：一致性检查与修复 ----
+
+    /// 根对象obj(1)强引用obj(2)、弱引用obj(3)，obj(2)带大小和标签，obj(4)为固定的对象
+    fn repair_heap() -> GarbageCollector {
+        let mut gc = GarbageCollector::new();
+        gc.register_object(obj(1));
+        gc.register_object_with(obj(2), 16, 3);
+        gc.register_object(obj(3));
+        gc.register_object(obj(4));
+        gc.mark_root(obj(1));
+        gc.add_reference(obj(1), obj(2));
+        gc.add_weak_reference(obj(1), obj(3));
+        gc.pin(obj(4));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(3));
+        gc
     }
 
-    /// 获取对象的引用集合
-    pub fn get_references(&self, obj: *mut c_void) -> Option<&HashSet<*mut c_void>> {
-        self.references.get(&obj)
+    /// 破坏一种状态后检查报告，修复后再次检查应当通过
+    fn assert_repairs(corrupt: impl FnOnce(&mut GarbageCollector), expected: SlimeGcRepairReport) {
+        let mut gc = repair_heap();
+        assert!(gc.verify().is_clean());
+        corrupt(&mut gc);
+        assert_eq!(gc.verify(), expected);
+        assert_eq!(gc.verify(), expected, "verify must not modify without auto repair");
+        assert_eq!(gc.repair(), SlimeGcRepairReport { repaired: 1, ..expected });
+        assert!(gc.verify().is_clean());
+        assert_indexes_consistent(&gc);
     }
 
-    /// 批量添加引用
-    pub fn add_references(&mut self, from: *mut c_void, to_list: &[*mut c_void]) {
-        if !from.is_null() && !to_list.is_empty() {
-            if self.objects.contains(&from) {
-                let refs = self.references.entry(from).or_insert(HashSet::new());
-                for &to in to_list {
-                    if !to.is_null() {
-                        refs.insert(to);
-                    }
-                }
-            }
-        }
+    #[test]
+    fn repair_fixes_each_category() {
+        let none = SlimeGcRepairReport::default();
+        assert_repairs(|gc| {
+            gc.referrers.remove(&oref(2));
+            gc.weak_referrers.remove(&oref(3));
+        }, SlimeGcRepairReport { missing_reverse_edges: 2, ..none });
+        assert_repairs(|gc| {
+            index_insert(&mut gc.referrers, oref(3), oref(4));
+        }, SlimeGcRepairReport { stale_reverse_edges: 1, ..none });
+        // 绕过注销直接移除对象，留下它发出的引用和指向它的根
+        assert_repairs(|gc| {
+            gc.objects.remove(&oref(1));
+        }, SlimeGcRepairReport { dangling_edges: 1, dangling_weak_edges: 1, stale_reverse_edges: 2, dangling_roots: 1, ..none });
+        assert_repairs(|gc| {
+            gc.objects.remove(&oref(4));
+            gc.buffer_holds.entry(7).or_default().insert(oref(9));
+        }, SlimeGcRepairReport { dangling_pins: 2, ..none });
+        assert_repairs(|gc| {
+            gc.pins.insert(oref(2), 0);
+        }, SlimeGcRepairReport { counters_fixed: 1, ..none });
+        // 作用域根失效时，对应的作用域根计数也不再正确
+        assert_repairs(|gc| {
+            gc.objects.remove(&oref(3));
+        }, SlimeGcRepairReport { dangling_scope_roots: 1, counters_fixed: 1, ..none });
+        assert_repairs(|gc| {
+            gc.object_sizes.insert(oref(9), 10);
+            gc.object_tags.insert(oref(9), 1);
+            gc.external_bytes.insert(oref(9), 5);
+        }, SlimeGcRepairReport { stale_records: 3, ..none });
+        assert_repairs(|gc| {
+            gc.live_bytes += 7;
+        }, SlimeGcRepairReport { counters_fixed: 1, ..none });
     }
 
-    /// 批量移除引用
-    pub fn remove_references(&mut self, from: *mut c_void, to_list: &[*mut c_void]) {
-        if !from.is_null() && !to_list.is_empty() {
-            if let Some(refs) = self.references.get_mut(&from) {
-                for &to in to_list {
-                    refs.remove(&to);
-                }
-            }
-        }
+    #[test]
+    fn repair_keeps_edges_to_unregistered_targets_and_live_objects() {
+        let mut gc = repair_heap();
+        gc.add_reference(obj(1), obj(9));
+        gc.objects.remove(&oref(4));
+        gc.repair();
+        assert!(gc.get_references(obj(1)).unwrap().contains(&oref(9)));
+        assert_eq!(gc.get_object_count(), 3);
+        assert_eq!(gc.get_live_bytes(), 16);
+        assert_eq!(gc.collect_garbage(), 0);
+        assert_eq!(gc.pop_root_scope(), SLIME_GC_OK);
     }
 
-    /// 将对象标记为根对象
-    pub fn mark_root(&mut self, obj: *mut c_void) {
-        if !obj.is_null() && self.objects.contains(&obj) {
-            self.roots.insert(obj);
-        }
+    #[test]
+    fn auto_repair_fixes_problems_found_by_verify() {
+        let gc = slime_gc_new();
+        let mut report = SlimeGcRepairReport::default();
+        slime_gc_register_object(gc, obj(1));
+        slime_gc_register_object(gc, obj(2));
+        slime_gc_add_reference(gc, obj(1), obj(2));
+        assert_eq!(slime_gc_verify(gc, &mut report), SLIME_GC_OK);
+        assert!(report.is_clean());
+
+        unsafe { (*gc).referrers.clear() };
+        assert_eq!(slime_gc_verify(gc, &mut report), SLIME_GC_INCONSISTENT);
+        assert_eq!((report.missing_reverse_edges, report.repaired), (1, 0));
+        slime_gc_set_auto_repair(gc, 1);
+        assert_eq!(slime_gc_verify(gc, &mut report), SLIME_GC_INCONSISTENT);
+        assert_eq!((report.missing_reverse_edges, report.repaired), (1, 1));
+        assert_eq!(slime_gc_verify(gc, std::ptr::null_mut()), SLIME_GC_OK);
+        assert_eq!(slime_gc_repair(gc, &mut report), SLIME_GC_OK);
+        assert!(report.is_clean() && report.repaired == 0);
+        slime_gc_destroy(gc);
+
+        let ts = slime_gc_new_threadsafe();
+        slime_gc_ts_register_object(ts, obj(1));
+        unsafe { &*ts }.with_write(|gc| gc.live_bytes = 3);
+        assert_eq!(slime_gc_ts_verify(ts, &mut report), SLIME_GC_INCONSISTENT);
+        assert_eq!(slime_gc_ts_repair(ts, &mut report), SLIME_GC_OK);
+        assert_eq!(report.counters_fixed, 1);
+        slime_gc_ts_set_auto_repair(ts, 1);
+        assert_eq!(slime_gc_ts_verify(ts, &mut report), SLIME_GC_OK);
+        slime_gc_ts_destroy(ts);
     }
 
-    /// 将对象标记为非根对象
-    pub fn unmark_root(&mut self, obj: *mut c_void) {
-        self.roots.remove(&obj);
+    // ---- synth-211：导出标志与存活原因 ----
+
+    /// obj(1)同时是根对象、被固定两次、作用域根、线程缓冲区持有、被弱引用并设置了释放回调
+    fn every_flag_heap() -> GarbageCollector {
+        let mut gc = GarbageCollector::new();
+        gc.register_object_with(obj(1), 48, 9);
+        gc.register_object(obj(2));
+        gc.add_external_bytes(obj(1), 1000);
+        gc.mark_root(obj(1));
+        gc.pin(obj(1));
+        gc.pin(obj(1));
+        gc.push_root_scope();
+        gc.add_scoped_root(obj(1));
+        gc.hold_for_buffer(1, oref(1));
+        gc.mark_root(obj(2));
+        gc.add_weak_reference(obj(2), obj(1));
+        gc.set_free_callback(Some(ignore_free), std::ptr::null_mut());
+        gc
     }
 
-    /// 批量添加根对象
-    pub fn add_roots(&mut self, roots: &[*mut c_void]) {
-        for &obj in roots {
-            self.mark_root(obj);
+    #[test]
+    fn export_and_explain_report_every_flag() {
+        let gc = every_flag_heap();
+        let json = parse_json(&gc.dump_graph_json());
+        let Json::Array(nodes) = json.get("nodes") else { unreachable!() };
+        for flag in GRAPH_NODE_FLAGS {
+            assert_eq!(nodes[0].get("flags").get(flag), &Json::Bool(true), "{flag}");
+        }
+        assert_eq!(nodes[0].get("pin_count"), &Json::Number(2));
+        assert_eq!(nodes[0].get("external_bytes"), &Json::Number(1000));
+        assert_eq!(nodes[1].get("flags").get("pinned"), &Json::Bool(false));
+        assert_eq!(nodes[1].get("flags").get("weakly_referenced"), &Json::Bool(false));
+        let Json::Array(edges) = json.get("edges") else { unreachable!() };
+        assert_eq!(edges[0].get("kind"), &Json::Str("weak".into()));
+
+        let text = gc.explain(obj(1));
+        for part in ["size 48 bytes", "1000 external bytes", "tag 9", "root", "pinned (2)", "scoped root",
+                     "held by a thread buffer", "weakly referenced by 1", "finalizer registered", "alive because: it is a root"] {
+            assert!(text.contains(part), "{part:?} missing from {text}");
         }
+        assert_eq!(gc.explain(obj(3)), "not registered");
     }
 
-    /// 批量移除根对象
-    pub fn remove_roots(&mut self, roots: &[*mut c_void]) {
-        for &obj in roots {
-            self.unmark_root(obj);
+    #[test]
+    fn pinned_liveness_is_reported_as_pin_not_root_path() {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=4 {
+            gc.register_object(obj(n));
         }
+        gc.mark_root(obj(1));
+        gc.add_reference(obj(1), obj(2));
+        gc.add_reference(obj(2), obj(3));
+        gc.pin(obj(3));
+        gc.add_reference(obj(3), obj(4));
+
+        assert_eq!(gc.find_retaining_path(obj(3)), Some((LivenessAnchor::Pin, vec![oref(3)])));
+        assert_eq!(gc.find_retaining_path(obj(4)), Some((LivenessAnchor::Pin, vec![oref(3), oref(4)])));
+        assert_eq!(gc.find_retaining_path(obj(2)), Some((LivenessAnchor::Root, vec![oref(1), oref(2)])));
+        assert!(gc.explain(obj(3)).contains("alive because: it is a pinned object"));
+        let text = gc.explain(obj(4));
+        assert!(text.contains(&format!("reachable from a pinned object: {:p} -> {:p}", obj(3), obj(4))), "{text}");
+
+        let gc = Box::into_raw(Box::new(gc));
+        assert_eq!(slime_gc_retaining_anchor(gc, obj(4)), SLIME_GC_ANCHOR_PIN);
+        assert_eq!(slime_gc_retaining_anchor(gc, obj(2)), SLIME_GC_ANCHOR_ROOT);
+        slime_gc_unpin(gc, obj(3));
+        slime_gc_remove_reference(gc, obj(2), obj(3));
+        assert_eq!(slime_gc_retaining_anchor(gc, obj(4)), 0);
+        let mut buf = [0 as c_char; 256];
+        slime_gc_explain(gc, obj(4), buf.as_mut_ptr(), buf.len() as c_int);
+        let text = unsafe { CStr::from_ptr(buf.as_ptr()) }.to_str().unwrap();
+        assert!(text.contains("unreachable"), "{text}");
+        slime_gc_destroy(gc);
     }
 
-    /// 清除所有根对象标记
-    pub fn clear_roots(&mut self) {
-        self.roots.clear();
+    #[test]
+    fn graph_schema_validation_checks_version_and_fields() {
+        let json = every_flag_heap().dump_graph_json();
+        assert_eq!(validate_graph_json(&json), Ok(()));
+        let c_text = std::ffi::CString::new(json.clone()).unwrap();
+        assert_eq!(slime_gc_validate_graph_json(c_text.as_ptr()), SLIME_GC_OK);
+
+        let old = json.replacen("\"format_version\":2", "\"format_version\":1", 1);
+        assert_eq!(validate_graph_json(&old), Err(GraphSchemaError::Version(Some(1))));
+        let c_old = std::ffi::CString::new(old).unwrap();
+        assert_eq!(slime_gc_validate_graph_json(c_old.as_ptr()), SLIME_GC_VERSION_MISMATCH);
+        assert_eq!(validate_graph_json("{\"nodes\":[],\"edges\":[]}"), Err(GraphSchemaError::Version(None)));
+
+        let missing_flag = json.replacen("\"buffer_held\":true,", "", 1);
+        assert_eq!(validate_graph_json(&missing_flag), Err(GraphSchemaError::Field("nodes[0].flags.buffer_held".into())));
+        let bad_kind = json.replacen("\"kind\":\"weak\"", "\"kind\":\"soft\"", 1);
+        assert_eq!(validate_graph_json(&bad_kind), Err(GraphSchemaError::Field("edges[0].kind".into())));
+        // 多出的字段不影响校验
+        let extra = json.replacen("\"nodes\":[", "\"generator\":\"test\",\"nodes\":[", 1);
+        assert_eq!(validate_graph_json(&extra), Ok(()));
+
+        assert_eq!(validate_graph_json(&json[..json.len() / 2]), Err(GraphSchemaError::Syntax));
+        assert_eq!(validate_graph_json(&"[".repeat(1000)), Err(GraphSchemaError::Syntax));
+        let c_bad = std::ffi::CString::new("{\"format_version\":2}").unwrap();
+        assert_eq!(slime_gc_validate_graph_json(c_bad.as_ptr()), SLIME_GC_INVALID_FORMAT);
+        assert_eq!(slime_gc_validate_graph_json(std::ptr::null()), SLIME_GC_INVALID_FORMAT);
     }
 
-    /// 获取当前根对象数量
-    pub fn get_root_count(&self) -> usize {
-        self.roots.len()
+    // ---- synth-213：按类型的处置队列 ----
+
+    const GPU_TAG: u32 = 5;
+
+    #[test]
+    fn disposal_queue_keeps_objects_registered_until_popped() {
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        let mut log = InvalidationLog { gc: None, unsubscribe: Vec::new(), batches: Vec::new() };
+        let mut gc = GarbageCollector::new();
+        gc.set_free_callback(Some(record_free), &mut freed as *mut _ as *mut c_void);
+        gc.subscribe_invalidation(record_invalidation, &mut log as *mut _ as *mut c_void);
+        gc.set_type_disposal_queue(GPU_TAG, 4);
+        gc.register_object_with(obj(1), 0, GPU_TAG);
+        gc.register_object_with(obj(3), 0, GPU_TAG);
+        gc.register_object(obj(2));
+        gc.register_object(obj(4));
+        gc.register_object(obj(9));
+        gc.mark_root(obj(9));
+        // obj(1)引用的obj(2)在obj(1)被取出之前一起保留
+        gc.add_reference(obj(1), obj(2));
+
+        let result = gc.collect_garbage_detailed();
+        assert_eq!(result, SlimeGcCollectResult { freed: 1, queued_for_disposal: 2, deferred_by_backpressure: 0 });
+        gc.take_pending_callbacks().run();
+        assert_eq!(freed, vec![obj(4)]);
+        assert!([1, 2, 3].iter().all(|&n| gc.objects.contains(&oref(n))));
+        // 已在队列中的对象不会重复放入
+        assert_eq!(gc.collect_garbage_detailed(), SlimeGcCollectResult::default());
+
+        let mut out = [std::ptr::null_mut(); 8];
+        let popped = gc.pop_disposal(GPU_TAG, &mut out[..1]);
+        assert_eq!(popped, 1);
+        let first = out[0];
+        assert!(first == obj(1) || first == obj(3));
+        assert!(!gc.objects.contains(&ObjRef::new(first).unwrap()));
+        log.batches.clear();
+        gc.take_pending_callbacks().run();
+        assert_eq!(log.batches, vec![(vec![first], SLIME_GC_INVALIDATE_SWEPT)]);
+
+        assert_eq!(gc.pop_disposal(GPU_TAG, &mut out), 1);
+        assert_eq!(gc.pop_disposal(GPU_TAG, &mut out), 0);
+        assert_eq!(gc.pop_disposal(GPU_TAG + 1, &mut out), 0);
+        // 取出的对象不调用释放回调；obj(2)失去引用方后按普通对象回收
+        assert_eq!(gc.collect_garbage_detailed().freed, 1);
+        gc.take_pending_callbacks().run();
+        assert_eq!(freed, vec![obj(4), obj(2)]);
+        assert_indexes_consistent(&gc);
     }
 
-    /// 执行垃圾回收
-    pub fn collect_garbage(&mut self) -> usize {
-        if self.objects.is_empty() {
-            return 0;
+    #[test]
+    fn full_disposal_queue_defers_collection_until_popped() {
+        let mut gc = GarbageCollector::new();
+        gc.set_type_disposal_queue(GPU_TAG, 1);
+        for n in 1..=3 {
+            gc.register_object_with(obj(n), 0, GPU_TAG);
         }
+        gc.register_object(obj(4));
 
-        // 步骤1: 标记所有可达对象
-        let mut marked = HashSet::new();
-        
-        // 从根对象开始标记
-        for &root in &self.roots {
-            self.mark(root, &mut marked);
-        }
+        let result = gc.collect_garbage_detailed();
+        assert_eq!(result, SlimeGcCollectResult { freed: 1, queued_for_disposal: 1, deferred_by_backpressure: 2 });
+        assert_eq!(gc.get_object_count(), 3);
+        assert_eq!(gc.collect_garbage_detailed(), SlimeGcCollectResult { freed: 0, queued_for_disposal: 0, deferred_by_backpressure: 2 });
 
-        // 步骤2: 清除所有未标记的对象
-        let mut collected = 0;
-        let mut to_remove = Vec::new();
+        let mut out = [std::ptr::null_mut(); 4];
+        assert_eq!(gc.pop_disposal(GPU_TAG, &mut out), 1);
+        assert_eq!(gc.collect_garbage_detailed(), SlimeGcCollectResult { freed: 0, queued_for_disposal: 1, deferred_by_backpressure: 1 });
 
-        for &obj in &self.objects {
-            if !marked.contains(&obj) {
-                // 注意：这里不直接释放对象，因为对象是在C++中用new创建的
-                // 对象的释放由C++的析构函数负责
-                to_remove.push(obj);
-                collected += 1;
-            }
-        }
+        // 增量回收在标记结束时同样放入队列或推迟
+        assert_eq!(gc.pop_disposal(GPU_TAG, &mut out), 1);
+        while !gc.collect_step(1).finished {}
+        assert_eq!((gc.last_queued_for_disposal, gc.last_deferred_by_backpressure), (1, 0));
+        assert_eq!(gc.get_object_count(), 1);
 
-        // 从集合中移除已释放的对象
-        for obj in to_remove {
-            self.objects.remove(&obj);
-            self.references.remove(&obj);
-            
-            // 从其他对象的引用列表中移除该对象
-            for refs in self.references.values_mut() {
-                refs.remove(&obj);
-            }
-        }
+        // 注销队列中的对象会把它移出队列；容量为0时不再放入新对象
+        gc.unregister_object(out[0]);
+        gc.unregister_object(out[1]);
+        let last = gc.objects.iter().next().copied().unwrap();
+        gc.unregister_object(last);
+        assert!(gc.disposal_queues[&GPU_TAG].objs.is_empty() && gc.condemned.is_empty());
+        gc.set_type_disposal_queue(GPU_TAG, 0);
+        gc.register_object_with(obj(5), 0, GPU_TAG);
+        assert_eq!(gc.collect_garbage_detailed(), SlimeGcCollectResult { freed: 1, queued_for_disposal: 0, deferred_by_backpressure: 0 });
+    }
+
+    #[test]
+    fn destroy_drains_disposal_queues_through_free_callback() {
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        let gc = slime_gc_new();
+        slime_gc_set_free_callback(gc, Some(record_free), &mut freed as *mut _ as *mut c_void);
+        slime_gc_set_type_disposal_queue(gc, GPU_TAG, 1);
+        slime_gc_register_object_tagged(gc, obj(1), GPU_TAG);
+        slime_gc_register_object_tagged(gc, obj(2), GPU_TAG);
+        let result = slime_gc_collect_detailed(gc);
+        assert_eq!((result.queued_for_disposal, result.deferred_by_backpressure), (1, 1));
+        assert!(freed.is_empty());
+        slime_gc_destroy(gc);
+        freed.sort();
+        assert_eq!(freed, vec![obj(1), obj(2)]);
 
-        collected
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        let gc = Box::into_raw(Box::new(ConcurrentGarbageCollector::new()));
+        slime_gc_ts_set_free_callback(gc, Some(record_free), &mut freed as *mut _ as *mut c_void);
+        slime_gc_ts_set_type_disposal_queue(gc, GPU_TAG, 2);
+        slime_gc_ts_register_object_tagged(gc, obj(1), GPU_TAG);
+        slime_gc_ts_register_object_tagged(gc, obj(2), GPU_TAG);
+        assert_eq!(slime_gc_ts_collect_detailed(gc).queued_for_disposal, 2);
+        let mut out = [std::ptr::null_mut(); 1];
+        assert_eq!(slime_gc_ts_pop_disposal(gc, GPU_TAG, out.as_mut_ptr(), 1), 1);
+        slime_gc_ts_destroy(gc);
+        assert_eq!(freed.len(), 1);
+        assert_ne!(freed[0], out[0]);
     }
 
-    /// 递归标记对象及其引用的对象
-    fn mark(&self, obj: *mut c_void, marked: &mut HashSet<*mut c_void>) {
-        // 检查对象是否为空或已标记
-        if obj.is_null() || marked.contains(&obj) {
-            return;
-        }
+    // ---- synth-221：流式导出 ----
 
-        // 检查对象是否在已注册的对象集合中
-        if !self.objects.contains(&obj) {
-            return;
+    /// 记录每次写入大小的写入器
+    #[derive(Default)]
+    struct ChunkRecorder {
+        out: Vec<u8>,
+        writes: usize,
+        largest_write: usize,
+    }
+
+    impl std::io::Write for ChunkRecorder {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes += 1;
+            self.largest_write = self.largest_write.max(buf.len());
+            self.out.extend_from_slice(buf);
+            Ok(buf.len())
         }
 
-        // 标记当前对象
-        marked.insert(obj);
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
 
-        // 标记所有引用的对象
-        if let Some(refs) = self.references.get(&obj) {
-            for &ref_obj in refs {
-                self.mark(ref_obj, marked);
+    fn large_export_heap(count: usize) -> GarbageCollector {
+        let mut gc = GarbageCollector::new();
+        for n in 1..=count {
+            gc.register_object_with(obj(n), n % 64, (n % 3) as u32);
+        }
+        gc.mark_root(obj(1));
+        gc.pin(obj(2));
+        for n in 1..count {
+            gc.add_reference(obj(n), obj(n + 1));
+            if n % 7 == 0 {
+                gc.add_weak_reference(obj(n), obj(n / 7));
             }
         }
+        gc
     }
-}
 
-/// C接口函数，用于创建垃圾回收器
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_new() -> *mut GarbageCollector {
-    Box::into_raw(Box::new(GarbageCollector::new()))
-}
+    #[test]
+    fn streamed_export_matches_monolithic_export_in_bounded_writes() {
+        let gc = large_export_heap(20_000);
+        let mut recorder = ChunkRecorder::default();
+        gc.export_json_streaming(&mut recorder, 100).unwrap();
 
-/// C接口函数，用于销毁垃圾回收器
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_destroy(gc: *mut GarbageCollector) {
-    if !gc.is_null() {
-        unsafe {
-            // 销毁GC之前，先释放所有对象
-            (*gc).collect_garbage();
-            drop(Box::from_raw(gc));
-        }
+        // 每块最多100个节点或边，单个节点的JSON不超过256字节
+        assert!(recorder.largest_write <= 100 * 256, "{}", recorder.largest_write);
+        assert!(recorder.writes > 2 * 20_000 / 100);
+        let streamed = String::from_utf8(recorder.out).unwrap();
+        assert_eq!(streamed, gc.dump_graph_json());
+        assert_eq!(parse_json(&streamed), parse_json(&gc.dump_graph_json()));
+        assert_eq!(validate_graph_json(&streamed), Ok(()));
+
+        // 空对象图和块大小0同样得到完整的JSON
+        let mut out = Vec::new();
+        GarbageCollector::new().export_json_streaming(&mut out, 0).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), GarbageCollector::new().dump_graph_json());
     }
-}
 
-/// C接口函数，用于注册对象
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_register_object(gc: *mut GarbageCollector, obj: *mut c_void) {
-    if !gc.is_null() && !obj.is_null() {
-        unsafe {
-            (*gc).register_object(obj);
-        }
+    /// 写入回调的上下文：收到的数据、调用次数和在第几次调用时中止（0表示不中止）
+    #[derive(Default)]
+    struct ExportSink {
+        data: Vec<u8>,
+        calls: usize,
+        abort_at: usize,
     }
-}
 
-/// C接口函数，用于注销对象
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_unregister_object(gc: *mut GarbageCollector, obj: *mut c_void) {
-    if !gc.is_null() && !obj.is_null() {
-        unsafe {
-            (*gc).unregister_object(obj);
+    extern "C" fn sink_write(data: *const u8, len: usize, ctx: *mut c_void) -> c_int {
+        let sink = unsafe { &mut *(ctx as *mut ExportSink) };
+        sink.calls += 1;
+        if sink.calls == sink.abort_at {
+            return 1;
         }
+        sink.data.extend_from_slice(unsafe { std::slice::from_raw_parts(data, len) });
+        0
     }
-}
 
-/// C接口函数，用于添加对象引用
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_add_reference(gc: *mut GarbageCollector, from: *mut c_void, to: *mut c_void) {
-    if !gc.is_null() && !from.is_null() && !to.is_null() {
-        unsafe {
-            (*gc).add_reference(from, to);
-        }
+    #[test]
+    fn export_json_through_callback_and_abort() {
+        let gc = Box::into_raw(Box::new(large_export_heap(5000)));
+        let mut sink = ExportSink::default();
+        assert_eq!(slime_gc_export_json_cb(gc, Some(sink_write), &mut sink as *mut _ as *mut c_void), SLIME_GC_OK);
+        assert_eq!(String::from_utf8(sink.data).unwrap(), unsafe { &*gc }.dump_graph_json());
+        assert!(sink.calls > 1);
+
+        // 回调返回非0时立即中止，不再调用回调
+        let mut aborted = ExportSink { abort_at: 2, ..Default::default() };
+        assert_eq!(slime_gc_export_json_cb(gc, Some(sink_write), &mut aborted as *mut _ as *mut c_void), SLIME_GC_IO_ERROR);
+        assert_eq!(aborted.calls, 2);
+        assert_eq!(slime_gc_export_json_cb(gc, None, std::ptr::null_mut()), SLIME_GC_IO_ERROR);
+        slime_gc_destroy(gc);
+
+        let ts = Box::into_raw(Box::new(ConcurrentGarbageCollector::new()));
+        slime_gc_ts_register_object(ts, obj(1));
+        let mut sink = ExportSink::default();
+        assert_eq!(slime_gc_ts_export_json_cb(ts, Some(sink_write), &mut sink as *mut _ as *mut c_void), SLIME_GC_OK);
+        assert_eq!(String::from_utf8(sink.data).unwrap(), unsafe { &*ts }.dump_graph_json());
+        slime_gc_ts_destroy(ts);
     }
-}
 
-/// C接口函数，用于移除对象引用
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_remove_reference(gc: *mut GarbageCollector, from: *mut c_void, to: *mut c_void) {
-    if !gc.is_null() && !from.is_null() && !to.is_null() {
-        unsafe {
-            (*gc).remove_reference(from, to);
+    // ---- synth-228：操作耗时直方图 ----
+
+    #[test]
+    fn op_latency_histograms_count_every_operation() {
+        const THREADS: usize = 4;
+        const PER_THREAD: usize = 2000;
+        let gc = Arc::new(ConcurrentGarbageCollector::new());
+        gc.register_object(obj(1));
+        assert_eq!(gc.op_latency(SLIME_GC_OP_REGISTER).unwrap().count, 0);
+        gc.set_op_latency_enabled(true);
+
+        // 多个线程注册足够多的对象触发哈希表扩容，同时另一个线程反复回收
+        let running = Arc::new(AtomicBool::new(true));
+        let collector = {
+            let (gc, running) = (Arc::clone(&gc), Arc::clone(&running));
+            std::thread::spawn(move || {
+                while running.load(Ordering::Relaxed) {
+                    gc.collect_garbage();
+                }
+            })
+        };
+        let workers: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let gc = Arc::clone(&gc);
+                std::thread::spawn(move || {
+                    let base = 10 + t * PER_THREAD;
+                    gc.register_object(obj(base));
+                    gc.mark_root(obj(base));
+                    for n in base + 1..base + PER_THREAD {
+                        gc.register_object(obj(n));
+                        gc.add_reference(obj(n - 1), obj(n));
+                    }
+                    for n in base..base + 10 {
+                        gc.add_weak_reference(obj(n), obj(n + 1));
+                    }
+                    for n in base..base + 100 {
+                        gc.remove_reference(obj(n), obj(n + 1));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
         }
-    }
-}
+        running.store(false, Ordering::Relaxed);
+        collector.join().unwrap();
 
-/// C接口函数，用于移除对象的所有引用
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_clear_references(gc: *mut GarbageCollector, obj: *mut c_void) {
-    if !gc.is_null() && !obj.is_null() {
-        unsafe {
-            (*gc).clear_references(obj);
+        let expected = [
+            (SLIME_GC_OP_REGISTER, THREADS * PER_THREAD),
+            (SLIME_GC_OP_ADD_REFERENCE, THREADS * (PER_THREAD - 1)),
+            (SLIME_GC_OP_REMOVE_REFERENCE, THREADS * 100),
+            (SLIME_GC_OP_ROOT, THREADS),
+            (SLIME_GC_OP_WEAK, THREADS * 10),
+        ];
+        for (op, count) in expected {
+            let histogram = gc.op_latency(op).unwrap();
+            assert_eq!(histogram.count, count as u64, "op {op}");
+            assert_eq!(histogram.buckets.iter().sum::<u64>(), histogram.count);
+            assert!(histogram.max_nanos > 0 && histogram.max_nanos < 10_000_000_000, "{}", histogram.max_nanos);
+            assert!(histogram.total_nanos >= histogram.max_nanos);
         }
+        assert_eq!(gc.op_latency(OP_KINDS as c_int), None);
+        assert_eq!(gc.op_latency(-1), None);
+
+        let text = gc.metrics_text();
+        assert!(text.contains(&format!("slime_gc_op_latency_seconds_count{{op=\"register\"}} {}", THREADS * PER_THREAD)));
+        assert!(text.contains(&format!("slime_gc_op_latency_seconds_bucket{{op=\"root\",le=\"+Inf\"}} {THREADS}")));
+        assert!(text.contains("# TYPE slime_gc_op_latency_seconds histogram"));
+
+        // 关闭后不再记录，已记录的直方图保留
+        let gc = Arc::into_raw(gc);
+        slime_gc_ts_set_op_latency(gc, 0);
+        slime_gc_ts_register_object(gc, obj(5));
+        let mut histogram = SlimeGcHistogram::default();
+        assert_eq!(slime_gc_ts_op_latency(gc, SLIME_GC_OP_REGISTER, &mut histogram), 1);
+        assert_eq!(histogram.count, (THREADS * PER_THREAD) as u64);
+        assert_eq!(slime_gc_ts_op_latency(gc, 99, &mut histogram), 0);
+        let mut buf = [0 as c_char; 64];
+        let len = slime_gc_ts_metrics_text(gc, buf.as_mut_ptr(), buf.len() as c_int);
+        assert_eq!(len as usize, unsafe { &*gc }.metrics_text().len());
+        drop(unsafe { Arc::from_raw(gc) });
     }
-}
 
-/// C接口函数，用于获取对象的引用集合大小
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_get_reference_count(gc: *const GarbageCollector, obj: *mut c_void) -> c_int {
-    if !gc.is_null() && !obj.is_null() {
-        unsafe {
-            if let Some(refs) = (*gc).get_references(obj) {
-                return refs.len() as c_int;
-            }
+    #[test]
+    fn latency_buckets_are_powers_of_two() {
+        let histogram = LatencyHistogram::new();
+        for nanos in [0, 1, 2, 3, 4, 1000, u64::MAX] {
+            histogram.record(nanos);
         }
+        let snapshot = histogram.snapshot();
+        assert_eq!(&snapshot.buckets[..4], &[1, 1, 2, 1]);
+        assert_eq!(snapshot.buckets[10], 1);
+        assert_eq!(snapshot.buckets[SLIME_GC_LATENCY_BUCKETS - 1], 1);
+        assert_eq!((snapshot.count, snapshot.max_nanos), (7, u64::MAX));
     }
-    0
-}
 
-/// C接口函数，用于批量添加引用
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_add_references(gc: *mut GarbageCollector, from: *mut c_void, to_list: *const *mut c_void, count: c_int) {
-    if !gc.is_null() && !from.is_null() && !to_list.is_null() && count > 0 {
-        unsafe {
-            let to_slice = std::slice::from_raw_parts(to_list, count as usize);
-            (*gc).add_references(from, to_slice);
-        }
+    // ---- synth-235：回收复用回调 ----
+
+    struct CountingRecycler {
+        accept: usize,
+        offered: Vec<Vec<*mut c_void>>,
     }
-}
 
-/// C接口函数，用于批量移除引用
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_remove_references(gc: *mut GarbageCollector, from: *mut c_void, to_list: *const *mut c_void, count: c_int) {
-    if !gc.is_null() && !from.is_null() && !to_list.is_null() && count > 0 {
-        unsafe {
-            let to_slice = std::slice::from_raw_parts(to_list, count as usize);
-            (*gc).remove_references(from, to_slice);
-        }
+    extern "C" fn counting_recycler(objs: *const *mut c_void, count: usize, user_data: *mut c_void) -> usize {
+        let recycler = unsafe { &mut *(user_data as *mut CountingRecycler) };
+        recycler.offered.push(unsafe { std::slice::from_raw_parts(objs, count) }.to_vec());
+        recycler.accept
     }
-}
 
-/// C接口函数，用于标记根对象
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_mark_root(gc: *mut GarbageCollector, obj: *mut c_void) {
-    if !gc.is_null() && !obj.is_null() {
-        unsafe {
-            (*gc).mark_root(obj);
+    #[test]
+    fn recycler_partial_acceptance_splits_the_batch() {
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        let mut recycler = CountingRecycler { accept: 2, offered: Vec::new() };
+        let mut gc = GarbageCollector::new();
+        gc.set_free_callback(Some(record_free), &mut freed as *mut _ as *mut c_void);
+        gc.set_recycler(7, Some(counting_recycler), &mut recycler as *mut _ as *mut c_void);
+        gc.register_object(obj(20));
+        gc.mark_root(obj(20));
+        for n in 1..=5 {
+            gc.register_object_with(obj(n), 16, 7);
         }
+        gc.register_object(obj(6));
+        gc.register_object_with(obj(7), 16, 9);
+
+        assert_eq!(gc.collect_garbage(), 7);
+        gc.take_pending_callbacks().run();
+
+        // 同一类型的对象成批交给回调一次，其他类型和未设置类型的对象不经过回调
+        assert_eq!(recycler.offered.len(), 1);
+        let batch = &recycler.offered[0];
+        let mut offered = batch.clone();
+        offered.sort();
+        assert_eq!(offered, (1..=5).map(obj).collect::<Vec<_>>());
+        // 接受的前两个对象不再调用释放回调，其余对象照常释放
+        assert!(batch[..2].iter().all(|o| !freed.contains(o)));
+        assert!(batch[2..].iter().all(|o| freed.contains(o)));
+        assert_eq!(freed.len(), 5);
+        assert!(freed.contains(&obj(6)) && freed.contains(&obj(7)));
+        assert_eq!(gc.get_live_bytes(), 0);
+
+        // 返回值超过数量时按全部接受处理；移除回调后对象直接释放
+        recycler.accept = usize::MAX;
+        freed.clear();
+        gc.register_object_with(obj(1), 0, 7);
+        gc.collect_garbage();
+        gc.take_pending_callbacks().run();
+        assert_eq!((recycler.offered.len(), freed.len()), (2, 0));
+        gc.set_recycler(7, None, std::ptr::null_mut());
+        gc.register_object_with(obj(1), 0, 7);
+        gc.collect_garbage();
+        gc.take_pending_callbacks().run();
+        assert_eq!((recycler.offered.len(), freed.clone()), (2, vec![obj(1)]));
     }
-}
 
-/// C接口函数，用于取消标记根对象
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_unmark_root(gc: *mut GarbageCollector, obj: *mut c_void) {
-    if !gc.is_null() && !obj.is_null() {
-        unsafe {
-            (*gc).unmark_root(obj);
-        }
+    #[test]
+    fn recycled_addresses_can_be_registered_again_without_diagnostics() {
+        let mut hits: Vec<(*mut c_void, *mut c_void, u64)> = Vec::new();
+        let mut recycler = CountingRecycler { accept: usize::MAX, offered: Vec::new() };
+        let mut gc = GarbageCollector::new();
+        gc.set_recent_unregister_window(8);
+        gc.set_recently_unregistered_callback(Some(record_recent_hit), &mut hits as *mut _ as *mut c_void);
+        gc.set_recycler(7, Some(counting_recycler), &mut recycler as *mut _ as *mut c_void);
+        gc.register_object_with(obj(1), 0, 7);
+        gc.register_object_with(obj(2), 0, 7);
+
+        // 没有根对象，走不标记的快速清除路径
+        assert_eq!(gc.collect_garbage(), 2);
+        gc.take_pending_callbacks().run();
+        assert_eq!(recycler.offered.len(), 1);
+
+        // 宿主复用obj(1)的地址并重新注册，不产生诊断
+        gc.register_object_with(obj(1), 0, 7);
+        gc.register_object(obj(3));
+        gc.add_reference(obj(1), obj(3));
+        gc.mark_root(obj(1));
+        gc.take_pending_callbacks().run();
+        assert_eq!(gc.get_recently_unregistered_count(), 0);
+
+        // 未重新注册就使用交出的地址仍会被报告
+        gc.add_reference(obj(3), obj(2));
+        gc.take_pending_callbacks().run();
+        assert_eq!(gc.get_recently_unregistered_count(), 1);
+        // 快速清除路径中两个对象交出的顺序不确定，不检查age
+        assert_eq!(hits.iter().map(|&(from, to, _)| (from, to)).collect::<Vec<_>>(), vec![(obj(3), obj(2))]);
     }
-}
 
-/// C接口函数，用于清除所有根对象标记
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_clear_roots(gc: *mut GarbageCollector) {
-    if !gc.is_null() {
-        unsafe {
-            (*gc).clear_roots();
+    #[test]
+    fn recycler_is_offered_incremental_and_threadsafe_sweeps() {
+        let mut recycler = CountingRecycler { accept: 1, offered: Vec::new() };
+        let mut gc = GarbageCollector::new();
+        gc.set_recycler(7, Some(counting_recycler), &mut recycler as *mut _ as *mut c_void);
+        gc.register_object(obj(20));
+        gc.mark_root(obj(20));
+        for n in 1..=4 {
+            gc.register_object_with(obj(n), 0, 7);
         }
+        while !gc.collect_step(2).finished {}
+        gc.take_pending_callbacks().run();
+        assert_eq!(recycler.offered.iter().map(Vec::len).sum::<usize>(), 4);
+        assert!(recycler.offered.iter().all(|batch| batch.len() <= 2));
+
+        let mut freed: Vec<*mut c_void> = Vec::new();
+        recycler.offered.clear();
+        let ts = slime_gc_new_threadsafe();
+        slime_gc_ts_set_free_callback(ts, Some(record_free), &mut freed as *mut _ as *mut c_void);
+        slime_gc_ts_set_recycler(ts, 7, Some(counting_recycler), &mut recycler as *mut _ as *mut c_void);
+        slime_gc_ts_register_object_tagged(ts, obj(1), 7);
+        slime_gc_ts_register_object_tagged(ts, obj(2), 7);
+        assert_eq!(slime_gc_ts_collect(ts), 2);
+        assert_eq!(recycler.offered.len(), 1);
+        assert_eq!(freed, recycler.offered[0][1..].to_vec());
+        slime_gc_ts_destroy(ts);
     }
-}
 
-/// C接口函数，用于执行垃圾回收
-#[unsafe(no_mangle)]
-pub extern "C" fn slime_gc_collect(gc: *mut GarbageCollector) -> c_int {
-    if !gc.is_null() {
-        unsafe {
-            (*gc).collect_garbage() as c_int
+    // ---- synth-238：存活状态 ----
+
+    #[test]
+    fn liveness_follows_objects_through_the_disposal_queue() {
+        let mut gc = GarbageCollector::new();
+        gc.set_type_disposal_queue(GPU_TAG, 1);
+        gc.register_object(obj(9));
+        gc.mark_root(obj(9));
+        gc.register_object_with(obj(1), 0, GPU_TAG);
+        gc.register_object_with(obj(2), 0, GPU_TAG);
+        assert_eq!(gc.liveness(obj(1)), Liveness::Live);
+        assert_eq!(gc.liveness(obj(7)), Liveness::Dead);
+
+        // 队列只容得下一个对象，另一个被推迟到以后的回收，仍报告为Live
+        assert_eq!(gc.collect_garbage_detailed().queued_for_disposal, 1);
+        let (queued, deferred) = if gc.condemned.contains(&oref(1)) { (obj(1), obj(2)) } else { (obj(2), obj(1)) };
+        assert_eq!((gc.liveness(queued), gc.liveness(deferred)), (Liveness::Condemned, Liveness::Live));
+        let queries = gc.query_many(&[queued, deferred, obj(7)]);
+        assert_eq!(queries.iter().map(|q| q.liveness).collect::<Vec<_>>(),
+                   vec![SLIME_GC_LIVENESS_CONDEMNED, SLIME_GC_LIVENESS_LIVE, SLIME_GC_LIVENESS_DEAD]);
+        assert!(gc.explain(queued).contains("condemned"));
+        let json = parse_json(&gc.dump_graph_json());
+        let Json::Array(nodes) = json.get("nodes") else { unreachable!() };
+        let node = nodes.iter().find(|node| node.get("id") == &Json::Str(format!("{:p}", queued))).unwrap();
+        assert_eq!(node.get("liveness"), &Json::Str("condemned".into()));
+        assert_eq!(validate_graph_json(&gc.dump_graph_json()), Ok(()));
+
+        // 重新变为可达也不会移出队列
+        gc.add_reference(obj(9), queued);
+        assert_eq!(gc.liveness(queued), Liveness::Condemned);
+        let mut out = [std::ptr::null_mut(); 2];
+        assert_eq!(gc.pop_disposal(GPU_TAG, &mut out), 1);
+        assert_eq!(gc.liveness(queued), Liveness::Dead);
+        assert_eq!(slime_gc_liveness(&gc, queued), SLIME_GC_LIVENESS_DEAD);
+        assert_eq!(slime_gc_liveness(&gc, deferred), SLIME_GC_LIVENESS_LIVE);
+        assert_eq!(slime_gc_liveness(std::ptr::null(), deferred), SLIME_GC_LIVENESS_DEAD);
+    }
+
+    #[test]
+    fn liveness_during_incremental_sweep_and_rescue() {
+        let mut gc = GarbageCollector::new();
+        for n in [1, 2, 3, 9, 10, 11] {
+            gc.register_object(obj(n));
         }
-    } else {
-        0
+        gc.mark_root(obj(9));
+        gc.add_reference(obj(9), obj(10));
+        gc.add_reference(obj(10), obj(11));
+        gc.add_reference(obj(1), obj(2));
+
+        // 标记阶段还没有证明任何对象不可达
+        assert!(!gc.collect_step(1).finished);
+        assert_eq!(gc.incremental.as_ref().map(|cycle| cycle.phase), Some(IncrementalPhase::Mark));
+        assert_eq!(gc.liveness(obj(1)), Liveness::Live);
+        gc.collect_step(100);
+        assert_eq!(gc.incremental.as_ref().map(|cycle| cycle.phase), Some(IncrementalPhase::Sweep));
+        assert_eq!(gc.liveness(obj(9)), Liveness::Live);
+        assert!([1, 2, 3].iter().all(|&n| gc.liveness(obj(n)) == Liveness::Condemned));
+
+        // 清除阶段中重新变为可达：它引用的对象扫描完之前，未标记的对象都报告为Live
+        gc.add_reference(obj(9), obj(1));
+        assert!([1, 2, 3].iter().all(|&n| gc.liveness(obj(n)) == Liveness::Live));
+        while gc.incremental.as_ref().is_some_and(|cycle| !cycle.worklist.is_empty()) {
+            gc.collect_step(1);
+        }
+        assert_eq!(gc.query_many(&[obj(1), obj(2), obj(3)]).iter().map(|q| q.liveness).collect::<Vec<_>>(),
+                   vec![SLIME_GC_LIVENESS_LIVE, SLIME_GC_LIVENESS_LIVE, SLIME_GC_LIVENESS_CONDEMNED]);
+
+        while !gc.collect_step(1).finished {}
+        assert_eq!((gc.liveness(obj(1)), gc.liveness(obj(2)), gc.liveness(obj(3))), (Liveness::Live, Liveness::Live, Liveness::Dead));
+
+        let ts = ConcurrentGarbageCollector::new();
+        ts.register_object(obj(1));
+        assert_eq!(slime_gc_ts_liveness(&ts, obj(1)), SLIME_GC_LIVENESS_LIVE);
+        ts.unregister_object(obj(1));
+        assert_eq!(ts.liveness(obj(1)), Liveness::Dead);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,90 @@
+#include <cstddef>
+#include <iostream>
+#include "slime_gc.h"
+
+// 只通过slime_gc_get_vtable取得的函数表驱动回收器，不直接调用其他slime_gc_*函数
+
+static int failures = 0;
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            std::cout << "FAILED: " #cond " (line " << __LINE__ << ")" << std::endl; \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+// 用整数构造假对象地址，回收器从不解引用对象指针
+static void* obj(size_t n) {
+    return reinterpret_cast<void*>(n * 16);
+}
+
+static void test_version_bounds() {
+    CHECK(slime_gc_get_vtable(0) == nullptr);
+    CHECK(slime_gc_get_vtable(SLIME_GC_VTABLE_VERSION + 1) == nullptr);
+
+    const SlimeGcVTable* latest = slime_gc_get_vtable(SLIME_GC_VTABLE_VERSION);
+    CHECK(latest != nullptr);
+    CHECK(latest->version == SLIME_GC_VTABLE_VERSION);
+    CHECK(latest->size == sizeof(SlimeGcVTable));
+
+    // 旧版本的表更短，但已有字段与最新版本指向同一实现
+    const SlimeGcVTable* v1 = slime_gc_get_vtable(1);
+    CHECK(v1 != nullptr);
+    CHECK(v1->version == 1);
+    CHECK(v1->size == offsetof(SlimeGcVTable, redirect));
+    CHECK(v1->size < latest->size);
+    CHECK(v1->new_gc == latest->new_gc);
+    CHECK(v1->collect == latest->collect);
+}
+
+static void test_scenario_through_table() {
+    const SlimeGcVTable* vt = slime_gc_get_vtable(SLIME_GC_VTABLE_VERSION);
+    if (vt == nullptr) {
+        return;
+    }
+
+    GarbageCollector* gc = vt->new_gc();
+    for (size_t n = 1; n <= 4; n++) {
+        vt->register_object(gc, obj(n));
+    }
+    vt->mark_root(gc, obj(1));
+    vt->add_reference(gc, obj(1), obj(2));
+    vt->add_reference(gc, obj(2), obj(3));
+    CHECK(vt->get_reference_count(gc, obj(1)) == 1);
+    CHECK(vt->get_object_count(gc) == 4);
+
+    CHECK(vt->collect(gc) == 1);
+    CHECK(vt->get_object_count(gc) == 3);
+
+    vt->remove_reference(gc, obj(1), obj(2));
+    CHECK(vt->collect(gc) == 2);
+    vt->destroy(gc);
+}
+
+static void test_old_table_is_usable() {
+    const SlimeGcVTable* v1 = slime_gc_get_vtable(1);
+    if (v1 == nullptr) {
+        return;
+    }
+
+    GarbageCollector* gc = v1->new_gc();
+    v1->register_object(gc, obj(1));
+    v1->register_object(gc, obj(2));
+    v1->mark_root(gc, obj(1));
+    CHECK(v1->collect(gc) == 1);
+    v1->destroy(gc);
+}
+
+int main() {
+    test_version_bounds();
+    test_scenario_through_table();
+    test_old_table_is_usable();
+
+    if (failures != 0) {
+        std::cout << failures << " vtable checks failed" << std::endl;
+        return 1;
+    }
+    std::cout << "All vtable checks passed" << std::endl;
+    return 0;
+}